@@ -1,191 +1,30 @@
-use std::cell::UnsafeCell;
-use std::cmp;
-use std::mem::{self, MaybeUninit};
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::{Arc, Mutex, Weak};
-
-use crate::buffers::pow2_ceil;
-
-const MIN_BUCKET: usize = 256;
-const MAX_BUCKET: usize = 1 << 20;
-
-pub struct BufferPoolEntry {
-    buckets: Vec<Mutex<Vec<Vec<u8>>>>,
-    current_bytes: AtomicUsize,
-    max_bytes: usize,
-    min_bucket: usize,
-    max_bucket: usize,
-}
+//! `BufferPool` and `PoolPage` were a second, nearly identical pool implementation whose
+//! pages double-counted their bytes when returned. They are now thin aliases of
+//! `SmartBufferPool` and `SmartPage`, kept so existing callers still compile.
+//!
+//! Unlike the old pool, requests above `MAX_BUCKET` get their full size and are freed on
+//! drop rather than being capped to `MAX_BUCKET` and cached.
 
-impl BufferPoolEntry {
-    #[inline]
-    fn bucket_index(&self, cap: usize) -> usize {
-        let mut c = self.min_bucket;
-        let mut idx = 0;
-        while c < cap {
-            c <<= 1;
-            idx += 1;
-        }
-        idx
-    }
-}
+use crate::buffers::smart_pool::{SmartBufferPool, SmartPage};
 
-#[derive(Clone)]
-pub struct BufferPool {
-    inner: Arc<BufferPoolEntry>,
-}
-
-impl BufferPool {
-    pub fn new(max_bytes: usize) -> Self {
-        let mut caps = vec![];
-        let mut c = MIN_BUCKET;
-        while c <= MAX_BUCKET {
-            caps.push(c);
-            c <<= 1;
-        }
+#[deprecated(note = "use `SmartBufferPool`, which this now aliases")]
+pub type BufferPool = SmartBufferPool;
 
-        let buckets = caps.into_iter().map(|_| Mutex::new(Vec::new())).collect();
-        Self {
-            inner: Arc::new(BufferPoolEntry {
-                buckets,
-                current_bytes: AtomicUsize::new(0),
-                max_bytes,
-                min_bucket: MIN_BUCKET,
-                max_bucket: MAX_BUCKET,
-            }),
-        }
-    }
-
-    pub fn get(&self, min_capacity: usize) -> PoolPage {
-        let want = pow2_ceil(min_capacity.max(MIN_BUCKET)).min(self.inner.max_bucket);
-        let idx = self.inner.bucket_index(want);
-
-        if let Ok(mut bin) = self.inner.buckets[idx].lock()
-            && let Some(mut buf) = bin.pop()
-        {
-            self.inner
-                .current_bytes
-                .fetch_sub(buf.capacity(), Ordering::Relaxed);
-            buf.clear();
-            return PoolPage {
-                buf,
-                cap_bucket: want,
-                pool: Arc::downgrade(&self.inner),
-            };
-        }
-
-        let mut buf = Vec::with_capacity(want);
-        self.inner.current_bytes.fetch_add(want, Ordering::Relaxed);
-        PoolPage {
-            buf: {
-                buf.clear();
-                buf
-            },
-            cap_bucket: want,
-            pool: Arc::downgrade(&self.inner),
-        }
-    }
-
-    pub fn bytes_in_pool(&self) -> usize {
-        self.inner.current_bytes.load(Ordering::Relaxed)
-    }
-}
-
-pub struct PoolPage {
-    buf: Vec<u8>,
-    cap_bucket: usize,
-    pool: Weak<BufferPoolEntry>,
-}
-
-impl PoolPage {
-    #[inline]
-    pub fn as_slice(&self) -> &[u8] {
-        &self.buf
-    }
-
-    #[inline]
-    pub fn as_mut_slice(&mut self) -> &mut [u8] {
-        &mut self.buf
-    }
-
-    #[inline]
-    pub fn len(&self) -> usize {
-        self.buf.len()
-    }
-
-    #[inline]
-    pub fn is_empty(&self) -> bool {
-        self.buf.is_empty()
-    }
-
-    #[inline]
-    pub fn capacity(&self) -> usize {
-        self.buf.capacity()
-    }
-
-    #[inline]
-    pub fn clear(&mut self) {
-        self.buf.clear()
-    }
-
-    pub fn resize_uninit(&mut self, new_len: usize) {
-        if new_len > self.buf.len() {
-            let additional = new_len - self.buf.len();
-            // SAFELY: we immediately set_len; users must write before read.
-            self.buf.reserve(additional);
-            unsafe {
-                let spare = self.buf.spare_capacity_mut();
-                let to_uninit = cmp::min(spare.len(), additional);
-                for i in 0..to_uninit {
-                    spare[i] = MaybeUninit::uninit();
-                }
-                self.buf.set_len(new_len);
-            }
-        } else {
-            self.buf.truncate(new_len);
-        }
-    }
-
-    #[inline]
-    pub fn vec_mut(&mut self) -> &mut Vec<u8> {
-        &mut self.buf
-    }
-}
-
-impl Drop for PoolPage {
-    fn drop(&mut self) {
-        if let Some(pool) = self.pool.upgrade() {
-            if self.cap_bucket > pool.max_bucket {
-                pool.current_bytes
-                    .fetch_sub(self.capacity(), Ordering::Relaxed);
-                return;
-            }
-
-            self.buf.clear();
-            let cap = self.buf.capacity();
-            pool.current_bytes.fetch_add(cap, Ordering::Relaxed);
-
-            // return to the bucket
-            let idx = pool.bucket_index(self.cap_bucket);
-            let mut bin = pool.buckets[idx].lock().unwrap();
-            pool.current_bytes.fetch_add(cap, Ordering::Relaxed);
-            bin.push(mem::take(&mut self.buf));
-        }
-    }
-}
+#[deprecated(note = "use `SmartPage`, which this now aliases")]
+pub type PoolPage = SmartPage;
 
 #[cfg(test)]
+#[allow(deprecated)]
 mod tests {
     use std::{thread, time::Duration};
 
     use super::*;
+    use crate::buffers::pow2_ceil;
+    use crate::buffers::smart_pool::MAX_BUCKET;
+    use std::sync::Arc;
 
     fn total_buffers(pool: &BufferPool) -> usize {
-        pool.inner
-            .buckets
-            .iter()
-            .map(|b| b.lock().unwrap().len())
-            .sum()
+        pool.bucket_report().iter().map(|&(_, count)| count).sum()
     }
 
     #[test]
@@ -233,13 +72,13 @@ mod tests {
 
     #[test]
     fn test_pow2_ceil_correctness() {
-        assert_eq!(super::pow2_ceil(0), 1);
-        assert_eq!(super::pow2_ceil(1), 1);
-        assert_eq!(super::pow2_ceil(2), 2);
-        assert_eq!(super::pow2_ceil(3), 4);
-        assert_eq!(super::pow2_ceil(5), 8);
-        assert_eq!(super::pow2_ceil(255), 256);
-        assert_eq!(super::pow2_ceil(4097), 8192);
+        assert_eq!(pow2_ceil(0), 1);
+        assert_eq!(pow2_ceil(1), 1);
+        assert_eq!(pow2_ceil(2), 2);
+        assert_eq!(pow2_ceil(3), 4);
+        assert_eq!(pow2_ceil(5), 8);
+        assert_eq!(pow2_ceil(255), 256);
+        assert_eq!(pow2_ceil(4097), 8192);
     }
 
     #[test]
@@ -255,25 +94,29 @@ mod tests {
     }
 
     #[test]
-    fn test_pool_eviction_large_buffer() {
+    fn test_oversized_buffer_is_not_cached() {
         let pool = BufferPool::new(1 << 20);
 
-        // Request a buffer bigger than MAX_BUCKET
-        let large = pool.get(2 * super::MAX_BUCKET);
+        // the smart backend hands out the full size instead of capping at MAX_BUCKET...
+        let large = pool.get(2 * MAX_BUCKET);
+        assert_eq!(large.capacity(), 2 * MAX_BUCKET);
 
-        // Expect the pool to cap it to MAX_BUCKET
-        let cap = large.capacity();
-        assert_eq!(
-            cap,
-            super::MAX_BUCKET,
-            "capacity should be capped to MAX_BUCKET"
-        );
-
-        // Dropping it should still return to the pool (since it was capped)
+        // ...and frees it on drop rather than caching it.
         drop(large);
+        assert_eq!(total_buffers(&pool), 0);
+        assert_eq!(pool.bytes_in_pool(), 0);
+    }
 
-        // There should be exactly one buffer reused in the correct bucket
-        assert_eq!(total_buffers(&pool), 1);
+    #[test]
+    fn test_returned_page_is_counted_once() {
+        // the old pool added a returned page's capacity twice on drop.
+        let pool = BufferPool::new(1 << 20);
+        let page: PoolPage = pool.get(4096);
+        assert_eq!(pool.bytes_in_pool(), 4096);
+        drop(page);
+        assert_eq!(pool.bytes_in_pool(), 4096);
+        drop(pool.get(4096));
+        assert_eq!(pool.bytes_in_pool(), 4096);
     }
 
     #[test]
@@ -359,7 +202,7 @@ mod tests {
         for _ in 0..20 {
             pool.get(4096);
         }
-        assert!(pool.bytes_in_pool() <= pool.inner.max_bytes * 2);
+        assert!(pool.bytes_in_pool() <= 64 * 1024 * 2);
     }
 
     #[test]
@@ -1,6 +1,7 @@
 pub mod buffer_pool;
 pub mod errors;
 pub mod smart_pool;
+pub mod typed_pool;
 
 #[inline]
 pub(crate) fn pow2_ceil(mut n: usize) -> usize {
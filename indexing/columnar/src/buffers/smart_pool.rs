@@ -1,25 +1,69 @@
 use crate::buffers::errors::CapacityError;
 use crate::buffers::pow2_ceil;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::{Arc, Mutex, Weak};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
 use std::{cmp, mem};
 
+/// Default smallest bucket; see `SmartBufferPool::with_bucket_range`.
 pub const MIN_BUCKET: usize = 256;
+/// Default largest bucket; larger requests are served but not cached.
 pub const MAX_BUCKET: usize = 1 << 20; // 1 MiB
 
 pub struct SmartBufferPool {
     entry: Arc<SmartEntry>,
 }
 
+/// What `SmartBufferPool::get` does when the pool holds more than `max_bytes`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Drop every idle buffer.
+    #[default]
+    TrimAll,
+    /// Drop idle buffers, largest first, only until the pool is back under `max_bytes`.
+    TrimToMax,
+    /// Keep the idle buffers, but serve misses with buffers the pool neither counts nor
+    /// takes back, so the pool stops growing.
+    RejectNew,
+    /// Do nothing; the budget is advisory.
+    Ignore,
+}
+
 pub struct SmartEntry {
     buckets: Vec<Mutex<Vec<Vec<u8>>>>,
     bytes_in_use: AtomicUsize,
     #[allow(dead_code)]
     max_bytes: usize,
+    overflow: OverflowPolicy,
+    /// Capacity of the first bucket; every request is rounded up to at least this.
+    min_bucket: usize,
+    /// Capacity of the last bucket; pages above it are freed on drop instead of cached.
+    max_bucket: usize,
     hit_count: AtomicUsize,
     miss_count: AtomicUsize,
 }
 
+impl SmartEntry {
+    /// Locks a bucket, recovering it if a thread panicked while holding the lock. A bucket
+    /// only lists idle buffers, which a panic cannot leave half-updated, so the pool keeps
+    /// using it rather than panicking or quietly allocating around it.
+    fn bucket(&self, index: usize) -> MutexGuard<'_, Vec<Vec<u8>>> {
+        self.buckets[index]
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Index of the bucket holding buffers of power-of-two capacity `cap`, clamped to the
+    /// first and last bucket.
+    #[inline(always)]
+    fn bucket_index(&self, cap: usize) -> usize {
+        let min_log2 = self.min_bucket.trailing_zeros();
+        let max_index = (self.max_bucket.trailing_zeros() - min_log2) as usize;
+        // .saturating_sub maps a cap below the first bucket to it instead of panicking.
+        let index = cap.trailing_zeros().saturating_sub(min_log2) as usize;
+        index.min(max_index)
+    }
+}
+
 impl Default for SmartBufferPool {
     fn default() -> Self {
         Self::new(8 * 1024 * 1024) // 8 MiB default max
@@ -28,18 +72,40 @@ impl Default for SmartBufferPool {
 
 impl SmartBufferPool {
     pub fn new(max_bytes: usize) -> Self {
-        let mut caps = Vec::new();
-        let mut c = MIN_BUCKET;
-        while c <= MAX_BUCKET {
-            caps.push(c);
-            c <<= 1;
-        }
+        Self::with_overflow_policy(max_bytes, OverflowPolicy::default())
+    }
+
+    /// Like `new`, but `policy` decides how `get` reacts when the pool is over `max_bytes`.
+    pub fn with_overflow_policy(max_bytes: usize, policy: OverflowPolicy) -> Self {
+        Self::build(max_bytes, policy, MIN_BUCKET, MAX_BUCKET)
+    }
+
+    /// Like `new`, but with buckets from `min_bucket` to `max_bucket` bytes instead of
+    /// `MIN_BUCKET..=MAX_BUCKET`. Both are rounded up to powers of two. A smaller minimum
+    /// wastes less on many tiny buffers, e.g. per-token postings; requests above the
+    /// maximum are still served, just not cached.
+    pub fn with_bucket_range(max_bytes: usize, min_bucket: usize, max_bucket: usize) -> Self {
+        Self::build(max_bytes, OverflowPolicy::default(), min_bucket, max_bucket)
+    }
+
+    fn build(
+        max_bytes: usize,
+        policy: OverflowPolicy,
+        min_bucket: usize,
+        max_bucket: usize,
+    ) -> Self {
+        let min_bucket = pow2_ceil(min_bucket.max(1));
+        let max_bucket = pow2_ceil(max_bucket).max(min_bucket);
+        let count = (max_bucket.trailing_zeros() - min_bucket.trailing_zeros()) as usize + 1;
 
-        let buckets = caps.into_iter().map(|_| Mutex::new(Vec::new())).collect();
+        let buckets = (0..count).map(|_| Mutex::new(Vec::new())).collect();
         let entry = Arc::new(SmartEntry {
             bytes_in_use: AtomicUsize::new(0),
             buckets,
             max_bytes,
+            overflow: policy,
+            min_bucket,
+            max_bucket,
             hit_count: AtomicUsize::new(0),
             miss_count: AtomicUsize::new(0),
         });
@@ -47,22 +113,28 @@ impl SmartBufferPool {
     }
 
     pub fn get(&self, min_capacity: usize) -> SmartPage {
+        let mut reject_new = false;
         if self.bytes_in_pool() > self.entry.max_bytes {
-            self.trim();
+            match self.entry.overflow {
+                OverflowPolicy::TrimAll => self.trim(),
+                OverflowPolicy::TrimToMax => self.trim_to(self.entry.max_bytes),
+                OverflowPolicy::RejectNew => reject_new = true,
+                OverflowPolicy::Ignore => {}
+            }
         }
 
         // let want = pow2_ceil(min_capacity.max(MIN_BUCKET)).min(MAX_BUCKET);
-        let want = pow2_ceil(min_capacity).max(MIN_BUCKET);
-        if want <= MAX_BUCKET {
+        let want = pow2_ceil(min_capacity).max(self.entry.min_bucket);
+        if want <= self.entry.max_bucket {
             let index = self.bucket_index(want);
-            if let Ok(mut bin) = self.entry.buckets[index].lock()
-                && let Some(mut buf) = bin.pop()
-            {
+            let popped = self.entry.bucket(index).pop();
+            if let Some(mut buf) = popped {
                 self.entry.hit_count.fetch_add(1, Ordering::Relaxed);
                 buf.clear();
                 return SmartPage {
                     buf,
                     cap_bucket: want,
+                    exact: None,
                     pool: Arc::downgrade(&self.entry),
                 };
             }
@@ -70,33 +142,36 @@ impl SmartBufferPool {
 
         self.entry.miss_count.fetch_add(1, Ordering::Relaxed);
         let buf = Vec::with_capacity(want);
-        self.entry.bytes_in_use.fetch_add(want, Ordering::Relaxed);
+        // a detached page is freed on drop instead of growing the pool.
+        let pool = if reject_new {
+            Weak::new()
+        } else {
+            self.entry.bytes_in_use.fetch_add(want, Ordering::Relaxed);
+            Arc::downgrade(&self.entry)
+        };
         SmartPage {
             buf,
             cap_bucket: want,
-            pool: Arc::downgrade(&self.entry),
+            exact: None,
+            pool,
         }
     }
 
+    /// Like `get`, but the page is limited to exactly `capacity` bytes for writing.
+    ///
+    /// The backing buffer still comes from (and returns to) the power-of-two bucket, so
+    /// `capacity()` may be larger and `bytes_in_pool` accounts for the full bucket size.
+    /// `writable_capacity()` reports `capacity` and `append_slice` refuses to go past it.
+    pub fn get_exact(&self, capacity: usize) -> SmartPage {
+        let mut page = self.get(capacity);
+        page.exact = Some(capacity);
+        page
+    }
+
     #[inline(always)]
     pub(crate) fn bucket_index(&self, cap: usize) -> usize {
-        // This optimized version assumes `cap` is already a power of two,
-        // which the `get` method guarantees.
-        const MIN_BUCKET_LOG2: u32 = MIN_BUCKET.trailing_zeros();
-        const MAX_BUCKET_LOG2: u32 = MAX_BUCKET.trailing_zeros();
-        const MAX_INDEX: usize = (MAX_BUCKET_LOG2 - MIN_BUCKET_LOG2) as usize;
-
-        // Calculate the log2 of the capacity.
-        let cap_log2 = cap.trailing_zeros();
-
-        // Calculate the index relative to the minimum bucket size.
-        // .saturating_sub ensures that if cap is somehow smaller than MIN_BUCKET,
-        // it returns 0 instead of panicking.
-        let index = (cap_log2.saturating_sub(MIN_BUCKET_LOG2)) as usize;
-
-        // Clamp the index to the maximum valid index. This is the crucial
-        // step that handles requests larger than MAX_BUCKET.
-        index.min(MAX_INDEX)
+        // `get` only asks for power-of-two capacities.
+        self.entry.bucket_index(cap)
     }
 
     pub fn bytes_in_pool(&self) -> usize {
@@ -110,9 +185,54 @@ impl SmartBufferPool {
         )
     }
 
+    /// Idle buffers held per bucket, as `(bucket capacity, buffer count)` from the smallest
+    /// bucket to the largest. Each bucket is locked in turn, so under concurrent use the
+    /// counts are not a single consistent snapshot.
+    pub fn bucket_report(&self) -> Vec<(usize, usize)> {
+        self.entry
+            .buckets
+            .iter()
+            .enumerate()
+            .map(|(i, _)| (self.entry.min_bucket << i, self.entry.bucket(i).len()))
+            .collect()
+    }
+
+    /// Drops idle buffers, largest buckets first, until at most `target` bytes remain
+    /// or no idle buffers are left.
+    pub fn trim_to(&self, target: usize) {
+        for index in (0..self.entry.buckets.len()).rev() {
+            let mut bin = self.entry.bucket(index);
+            while self.bytes_in_pool() > target {
+                let Some(buf) = bin.pop() else { break };
+                self.entry
+                    .bytes_in_use
+                    .fetch_sub(buf.capacity(), Ordering::Relaxed);
+            }
+            if self.bytes_in_pool() <= target {
+                return;
+            }
+        }
+    }
+
+    /// Drops the idle buffers of every bucket holding buffers larger than `threshold`
+    /// bytes, keeping the smaller ones for reuse.
+    pub fn trim_above(&self, threshold: usize) {
+        for index in 0..self.entry.buckets.len() {
+            if self.entry.min_bucket << index <= threshold {
+                continue;
+            }
+            let mut bin = self.entry.bucket(index);
+            for buf in bin.drain(..) {
+                self.entry
+                    .bytes_in_use
+                    .fetch_sub(buf.capacity(), Ordering::Relaxed);
+            }
+        }
+    }
+
     pub fn trim(&self) {
-        for bin in self.entry.buckets.iter() {
-            let mut bin = bin.lock().unwrap();
+        for index in 0..self.entry.buckets.len() {
+            let mut bin = self.entry.bucket(index);
             for buf in bin.drain(..) {
                 self.entry
                     .bytes_in_use
@@ -133,6 +253,7 @@ impl Clone for SmartBufferPool {
 pub struct SmartPage {
     pub(crate) buf: Vec<u8>,
     cap_bucket: usize,
+    exact: Option<usize>,
     pool: Weak<SmartEntry>,
 }
 
@@ -157,11 +278,26 @@ impl SmartPage {
         self.buf.len()
     }
 
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
     #[inline(always)]
     pub fn capacity(&self) -> usize {
         self.buf.capacity()
     }
 
+    /// The number of bytes this page may hold: the requested size for pages from
+    /// `get_exact`, otherwise the full buffer capacity.
+    #[inline(always)]
+    pub fn writable_capacity(&self) -> usize {
+        match self.exact {
+            Some(exact) => exact.min(self.buf.capacity()),
+            None => self.buf.capacity(),
+        }
+    }
+
     #[inline(always)]
     pub fn clear(&mut self) {
         self.buf.clear();
@@ -176,7 +312,7 @@ impl SmartPage {
             .ok_or(CapacityError)?;
 
         // Check if the new length exceeds the current capacity
-        if new_len > self.buf.capacity() {
+        if new_len > self.writable_capacity() {
             // Return an error if capacity is insufficient
             return Err(CapacityError);
         }
@@ -221,26 +357,15 @@ impl Drop for SmartPage {
         if let Some(pool) = self.pool.upgrade() {
             let cap = self.buf.capacity();
             // Skip extremely large buffers (don’t cache).
-            if self.cap_bucket > MAX_BUCKET {
+            if self.cap_bucket > pool.max_bucket {
                 pool.bytes_in_use.fetch_sub(cap, Ordering::Relaxed);
                 return;
             }
 
-            // let idx = pool.bucket_index(cap);
-            let idx = {
-                const MIN_BUCKET_LOG2: u32 = MIN_BUCKET.trailing_zeros();
-                const MAX_BUCKET_LOG2: u32 = MAX_BUCKET.trailing_zeros();
-                const MAX_INDEX: usize = (MAX_BUCKET_LOG2 - MIN_BUCKET_LOG2) as usize;
-
-                let cap_log2 = self.cap_bucket.trailing_zeros();
-                let index = (cap_log2.saturating_sub(MIN_BUCKET_LOG2)) as usize;
-                index.min(MAX_INDEX)
-            };
+            let idx = pool.bucket_index(self.cap_bucket);
             self.buf.clear();
 
-            if let Ok(mut bin) = pool.buckets[idx].lock() {
-                bin.push(mem::take(&mut self.buf));
-            }
+            pool.bucket(idx).push(mem::take(&mut self.buf));
         }
     }
 }
@@ -251,10 +376,8 @@ mod tests {
 
     use super::*;
     fn total_buffers(pool: &SmartBufferPool) -> usize {
-        pool.entry
-            .buckets
-            .iter()
-            .map(|b| b.lock().unwrap().len())
+        (0..pool.entry.buckets.len())
+            .map(|i| pool.entry.bucket(i).len())
             .sum()
     }
 
@@ -391,6 +514,26 @@ mod tests {
         assert!(misses > 0);
     }
 
+    #[test]
+    fn test_get_exact_limits_writable_capacity() {
+        let pool = SmartBufferPool::new(8 << 20);
+        let mut page = pool.get_exact(1000);
+        assert!(page.capacity() >= 1000);
+        assert_eq!(page.writable_capacity(), 1000);
+
+        page.append_slice(&[7u8; 1000]).unwrap();
+        assert_eq!(page.len(), 1000);
+        assert!(page.append_slice(&[7u8]).is_err());
+        assert_eq!(pool.bytes_in_pool(), 1024);
+        drop(page);
+
+        // The buffer goes back to its bucket and is reused without the limit.
+        let page = pool.get(1024);
+        assert_eq!(page.writable_capacity(), 1024);
+        let (hits, _) = pool.stats();
+        assert_eq!(hits, 1);
+    }
+
     #[test]
     fn test_resize_uninit_and_clear() {
         let pool = SmartBufferPool::new(8 << 20);
@@ -514,4 +657,139 @@ mod tests {
         assert!(misses > 0);
         assert!(pool.bytes_in_pool() <= pool.entry.max_bytes * 2);
     }
+
+    /// Leaves 4 x 32 KiB and 4 x 4 KiB idle buffers in a pool with a 64 KiB budget.
+    fn over_budget_pool(policy: OverflowPolicy) -> SmartBufferPool {
+        let pool = SmartBufferPool::with_overflow_policy(64 << 10, policy);
+        // seed the buckets directly; going through `get` would already trip the policy.
+        for cap in [32 << 10, 4 << 10] {
+            let mut bin = pool.entry.buckets[pool.bucket_index(cap)].lock().unwrap();
+            for _ in 0..4 {
+                bin.push(Vec::with_capacity(cap));
+                pool.entry.bytes_in_use.fetch_add(cap, Ordering::Relaxed);
+            }
+        }
+        assert_eq!(pool.bytes_in_pool(), 4 * (32 << 10) + 4 * (4 << 10));
+        pool
+    }
+
+    #[test]
+    fn test_overflow_trim_all() {
+        let pool = over_budget_pool(OverflowPolicy::TrimAll);
+        let page = pool.get(1024);
+        assert_eq!(pool.bytes_in_pool(), 1024);
+        drop(page);
+        assert_eq!(total_buffers(&pool), 1);
+    }
+
+    #[test]
+    fn test_overflow_trim_to_max() {
+        let pool = over_budget_pool(OverflowPolicy::TrimToMax);
+        let page = pool.get(1024);
+        // three 32 KiB buffers go before the pool is back under 64 KiB.
+        assert_eq!(pool.bytes_in_pool(), (32 << 10) + 4 * (4 << 10) + 1024);
+        drop(page);
+        assert_eq!(total_buffers(&pool), 6);
+    }
+
+    #[test]
+    fn test_overflow_reject_new() {
+        let pool = over_budget_pool(OverflowPolicy::RejectNew);
+        let before = pool.bytes_in_pool();
+
+        // a miss is served but never counted or cached.
+        let page = pool.get(1024);
+        assert_eq!(pool.bytes_in_pool(), before);
+        drop(page);
+        assert_eq!(pool.bytes_in_pool(), before);
+        assert_eq!(total_buffers(&pool), 8);
+
+        // idle buffers are still handed out and taken back.
+        let (hits, _) = pool.stats();
+        drop(pool.get(4 << 10));
+        assert_eq!(pool.stats().0, hits + 1);
+        assert_eq!(pool.bytes_in_pool(), before);
+    }
+
+    #[test]
+    fn test_overflow_ignore() {
+        let pool = over_budget_pool(OverflowPolicy::Ignore);
+        let before = pool.bytes_in_pool();
+        drop(pool.get(1024));
+        assert_eq!(pool.bytes_in_pool(), before + 1024);
+        assert_eq!(total_buffers(&pool), 9);
+    }
+
+    #[test]
+    fn test_bucket_report() {
+        let pool = SmartBufferPool::new(8 << 20);
+        let pages: Vec<_> = (0..3)
+            .map(|_| pool.get(1000))
+            .chain((0..2).map(|_| pool.get(40_000)))
+            .collect();
+        assert!(pool.bucket_report().iter().all(|&(_, count)| count == 0));
+        drop(pages);
+
+        let report = pool.bucket_report();
+        assert_eq!(report.first().unwrap().0, MIN_BUCKET);
+        assert_eq!(report.last().unwrap().0, MAX_BUCKET);
+        let non_empty: Vec<_> = report.into_iter().filter(|&(_, c)| c > 0).collect();
+        assert_eq!(non_empty, vec![(1024, 3), (65536, 2)]);
+    }
+
+    #[test]
+    fn test_trim_above_keeps_small_buffers() {
+        let pool = SmartBufferPool::new(8 << 20);
+        drop(pool.get(512));
+        drop(pool.get(1 << 20));
+        assert_eq!(pool.bytes_in_pool(), 512 + (1 << 20));
+
+        pool.trim_above(4096);
+        assert_eq!(pool.bytes_in_pool(), 512);
+        let non_empty: Vec<_> = pool
+            .bucket_report()
+            .into_iter()
+            .filter(|&(_, c)| c > 0)
+            .collect();
+        assert_eq!(non_empty, vec![(512, 1)]);
+    }
+
+    #[test]
+    fn test_bucket_range_small_minimum() {
+        let pool = SmartBufferPool::with_bucket_range(1 << 20, 64, MAX_BUCKET);
+        let page = pool.get(10);
+        assert_eq!(page.capacity(), 64);
+        drop(page);
+
+        let report = pool.bucket_report();
+        assert_eq!(report.first(), Some(&(64, 1)));
+        assert_eq!(report.last().unwrap().0, MAX_BUCKET);
+        assert_eq!(pool.get(100).capacity(), 128);
+    }
+
+    #[test]
+    fn test_poisoned_bucket_keeps_serving() {
+        let pool = SmartBufferPool::new(1 << 20);
+        drop(pool.get(1024));
+        let index = pool.bucket_index(1024);
+
+        let poisoner = pool.clone();
+        thread::spawn(move || {
+            let _bin = poisoner.entry.buckets[index].lock().unwrap();
+            panic!("poisoning the bucket");
+        })
+        .join()
+        .unwrap_err();
+        assert!(pool.entry.buckets[index].is_poisoned());
+
+        // the idle buffer is still handed out and taken back.
+        let page = pool.get(1024);
+        assert_eq!(pool.stats(), (1, 1));
+        drop(page);
+        assert_eq!(pool.bucket_report()[index], (1024, 1));
+
+        pool.trim();
+        assert_eq!(pool.bytes_in_pool(), 0);
+        assert_eq!(total_buffers(&pool), 0);
+    }
 }
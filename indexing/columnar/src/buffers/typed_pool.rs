@@ -0,0 +1,193 @@
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+
+/// Pool of reusable `Vec<T>` scratch buffers, the typed counterpart of `SmartBufferPool`.
+///
+/// Buffers are pooled as real `Vec<T>` rather than reinterpreted byte pages, so there are
+/// no alignment or validity concerns: a `Vec<u8>` page is only 1-aligned and cannot back a
+/// `[u64]` without copying. Any `T` works; decode paths use it with `BitEncodable` types.
+pub struct TypedBufferPool<T> {
+    entry: Arc<TypedEntry<T>>,
+}
+
+pub struct TypedEntry<T> {
+    free: Mutex<Vec<Vec<T>>>,
+    max_buffers: usize,
+    hit_count: AtomicUsize,
+    miss_count: AtomicUsize,
+}
+
+impl<T> Default for TypedBufferPool<T> {
+    fn default() -> Self {
+        Self::new(64)
+    }
+}
+
+impl<T> TypedBufferPool<T> {
+    /// Creates a pool that keeps at most `max_buffers` idle buffers.
+    pub fn new(max_buffers: usize) -> Self {
+        Self {
+            entry: Arc::new(TypedEntry {
+                free: Mutex::new(Vec::new()),
+                max_buffers,
+                hit_count: AtomicUsize::new(0),
+                miss_count: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// Returns an empty buffer with room for at least `min_capacity` values, reusing an
+    /// idle one when possible. The buffer returns to the pool when dropped.
+    pub fn get(&self, min_capacity: usize) -> TypedBuffer<T> {
+        let reused = self.entry.free.lock().ok().and_then(|mut free| {
+            let index = free.iter().position(|b| b.capacity() >= min_capacity)?;
+            Some(free.swap_remove(index))
+        });
+
+        let buf = match reused {
+            Some(buf) => {
+                self.entry.hit_count.fetch_add(1, Ordering::Relaxed);
+                buf
+            }
+            None => {
+                self.entry.miss_count.fetch_add(1, Ordering::Relaxed);
+                Vec::with_capacity(min_capacity)
+            }
+        };
+        TypedBuffer {
+            buf,
+            pool: Arc::downgrade(&self.entry),
+        }
+    }
+
+    /// Number of idle buffers currently held.
+    pub fn idle_buffers(&self) -> usize {
+        self.entry.free.lock().map_or(0, |free| free.len())
+    }
+
+    /// Returns `(hits, misses)`.
+    pub fn stats(&self) -> (usize, usize) {
+        (
+            self.entry.hit_count.load(Ordering::Relaxed),
+            self.entry.miss_count.load(Ordering::Relaxed),
+        )
+    }
+
+    pub fn trim(&self) {
+        if let Ok(mut free) = self.entry.free.lock() {
+            free.clear();
+        }
+    }
+}
+
+impl<T> Clone for TypedBufferPool<T> {
+    fn clone(&self) -> Self {
+        Self {
+            entry: Arc::clone(&self.entry),
+        }
+    }
+}
+
+/// A pooled `Vec<T>`; derefs to the vector and goes back to its pool on drop.
+pub struct TypedBuffer<T> {
+    buf: Vec<T>,
+    pool: Weak<TypedEntry<T>>,
+}
+
+impl<T> TypedBuffer<T> {
+    /// Detaches the vector from the pool.
+    pub fn into_inner(mut self) -> Vec<T> {
+        std::mem::take(&mut self.buf)
+    }
+}
+
+impl<T> Deref for TypedBuffer<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Vec<T> {
+        &self.buf
+    }
+}
+
+impl<T> DerefMut for TypedBuffer<T> {
+    fn deref_mut(&mut self) -> &mut Vec<T> {
+        &mut self.buf
+    }
+}
+
+impl<T> Drop for TypedBuffer<T> {
+    fn drop(&mut self) {
+        let Some(pool) = self.pool.upgrade() else {
+            return;
+        };
+        if self.buf.capacity() == 0 {
+            return;
+        }
+        if let Ok(mut free) = pool.free.lock()
+            && free.len() < pool.max_buffers
+        {
+            let mut buf = std::mem::take(&mut self.buf);
+            buf.clear();
+            free.push(buf);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::bitpack::v1::reader::decode_values_into;
+    use crate::encoding::bitpack::v1::writer::BitWriter;
+
+    fn encode(values: &[u32], width: u8) -> Vec<u8> {
+        let mut encoded = (values.len() as u32).to_le_bytes().to_vec();
+        let mut writer = BitWriter::<_, u32>::new(&mut encoded, width);
+        writer.write_all_values(values.iter().copied()).unwrap();
+        writer.flush().unwrap();
+        drop(writer);
+        encoded
+    }
+
+    #[test]
+    fn test_buffer_reused_across_decodes() {
+        let pool = TypedBufferPool::<u32>::new(4);
+        let first: Vec<u32> = (0..1_000).collect();
+        let second: Vec<u32> = (0..800).map(|v| v * 3).collect();
+
+        let mut buf = pool.get(first.len());
+        decode_values_into(&encode(&first, 10), 10, &mut buf).unwrap();
+        assert_eq!(*buf, first);
+        let ptr = buf.as_ptr();
+        drop(buf);
+        assert_eq!(pool.idle_buffers(), 1);
+
+        // the second decode lands in the same allocation.
+        let mut buf = pool.get(second.len());
+        decode_values_into(&encode(&second, 12), 12, &mut buf).unwrap();
+        assert_eq!(*buf, second);
+        assert_eq!(buf.as_ptr(), ptr);
+        assert_eq!(pool.stats(), (1, 1));
+
+        // full-width pages take the copy path into the same buffer.
+        let full = [u32::MAX, 7, 0];
+        decode_values_into(&encode(&full, 32), 32, &mut buf).unwrap();
+        assert_eq!(*buf, full);
+        assert_eq!(buf.as_ptr(), ptr);
+    }
+
+    #[test]
+    fn test_pool_caps_idle_buffers() {
+        let pool = TypedBufferPool::<u64>::new(1);
+        let a = pool.get(16);
+        let b = pool.get(16);
+        drop(a);
+        drop(b);
+        assert_eq!(pool.idle_buffers(), 1);
+
+        // a buffer too small for the request is not handed out.
+        let big = pool.get(1_024);
+        assert!(big.capacity() >= 1_024);
+        assert_eq!(pool.stats(), (0, 3));
+    }
+}
@@ -8,11 +8,62 @@ pub trait Columnar: Sized {
         }
         cols
     }
+
+    /// Like `to_columns`, but every column is chunked at `chunk_size` values regardless of
+    /// the struct's `#[columnar(chunk_size = N)]` attribute.
+    fn to_columns_with_chunk_size(rows: &[Self], chunk_size: usize) -> Self::Columns {
+        let mut cols = Self::Columns::default();
+        cols.set_chunk_size(chunk_size);
+        for r in rows {
+            cols.push(r);
+        }
+        cols
+    }
+
+    /// Builds a bundle for every `batch` rows of `rows` and hands it to `sink`, e.g. to
+    /// flush it to disk, so ingesting a stream never holds more than one batch. The last
+    /// bundle holds the remaining rows; no bundle is built for an empty iterator. Stops at
+    /// the first error `sink` returns.
+    fn to_columns_batched(
+        rows: impl Iterator<Item = Self>,
+        batch: usize,
+        mut sink: impl FnMut(Self::Columns) -> std::io::Result<()>,
+    ) -> std::io::Result<()> {
+        let batch = batch.max(1);
+        let mut cols = Self::Columns::default();
+        let mut pending = 0;
+        for row in rows {
+            cols.push(&row);
+            pending += 1;
+            if pending == batch {
+                sink(std::mem::take(&mut cols))?;
+                pending = 0;
+            }
+        }
+        if pending > 0 {
+            sink(cols)?;
+        }
+        Ok(())
+    }
 }
 
 pub trait ColumnBundle<Row>: Default {
     fn push(&mut self, row: &Row);
     fn merge(&mut self, other: Self);
+    /// Merges every bundle in `others`, in order. The result matches calling `merge` on
+    /// each one in turn; derived bundles move each column's chunks across in one pass.
+    fn merge_many(&mut self, others: Vec<Self>) {
+        for other in others {
+            self.merge(other);
+        }
+    }
+    /// Empties every column while keeping its allocations for the next batch.
+    fn clear(&mut self);
+    /// Checks that every column holds the same number of values, e.g. after filling the
+    /// columns by hand. Derived bundles name the first column that disagrees.
+    fn assert_consistent(&self) -> std::io::Result<()> {
+        Ok(())
+    }
     fn set_chunk_size(&mut self, n: usize) {
         let _ = n;
     }
@@ -23,6 +74,8 @@ pub trait ColumnBundle<Row>: Default {
 pub struct Column<T> {
     pub chunks: Vec<Vec<T>>,
     pub chunk_size: usize,
+    /// Emptied chunks kept by `clear`, reused before allocating new ones.
+    spare: Vec<Vec<T>>,
 }
 
 impl<T> Default for Column<T> {
@@ -30,10 +83,40 @@ impl<T> Default for Column<T> {
         Self {
             chunks: Vec::new(),
             chunk_size: 1_000_000,
+            spare: Vec::new(),
         }
     }
 }
 
+impl<T> Column<T> {
+    /// Removes all values but keeps the chunk allocations for reuse by `push`.
+    pub fn clear(&mut self) {
+        for mut chunk in self.chunks.drain(..).rev() {
+            chunk.clear();
+            self.spare.push(chunk);
+        }
+    }
+
+    /// The first value, without walking the column. Empty chunks are skipped.
+    pub fn first(&self) -> Option<&T> {
+        self.chunks.iter().find_map(|chunk| chunk.first())
+    }
+
+    /// The last value, without walking the column. Empty chunks are skipped.
+    pub fn last(&self) -> Option<&T> {
+        self.chunks.iter().rev().find_map(|chunk| chunk.last())
+    }
+
+    /// Number of values the column can hold without allocating a new chunk.
+    pub fn capacity(&self) -> usize {
+        self.chunks
+            .iter()
+            .chain(self.spare.iter())
+            .map(|c| c.capacity())
+            .sum()
+    }
+}
+
 impl<T: Clone> Column<T> {
     pub fn with_chunk_size(mut self, n: usize) -> Self {
         self.chunk_size = n;
@@ -46,7 +129,11 @@ impl<T: Clone> Column<T> {
             .last()
             .is_none_or(|c| c.len() == self.chunk_size)
         {
-            self.chunks.push(Vec::with_capacity(self.chunk_size));
+            let chunk = self
+                .spare
+                .pop()
+                .unwrap_or_else(|| Vec::with_capacity(self.chunk_size));
+            self.chunks.push(chunk);
         }
         self.chunks.last_mut().unwrap().push(v.clone());
     }
@@ -59,6 +146,16 @@ impl<T: Clone> Column<T> {
         self.len() == 0
     }
 
+    /// Appends the chunks of every column in `others` without copying them, reserving
+    /// room for all of them up front.
+    pub fn merge_many(&mut self, others: Vec<Self>) {
+        let total = others.iter().map(|o| o.chunks.len()).sum();
+        self.chunks.reserve(total);
+        for other in others {
+            self.chunks.extend(other.chunks);
+        }
+    }
+
     pub fn extend_from(&mut self, other: &Self)
     where
         T: Clone,
@@ -68,3 +165,25 @@ impl<T: Clone> Column<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_first_last() {
+        let mut column = Column::<u32>::default().with_chunk_size(4);
+        assert_eq!((column.first(), column.last()), (None, None));
+
+        for v in 10..20 {
+            column.push(&v);
+        }
+        assert_eq!(column.chunks.len(), 3);
+        assert_eq!((column.first(), column.last()), (Some(&10), Some(&19)));
+
+        // empty chunks at either end are looked past.
+        column.chunks.insert(0, Vec::new());
+        column.chunks.push(Vec::new());
+        assert_eq!((column.first(), column.last()), (Some(&10), Some(&19)));
+    }
+}
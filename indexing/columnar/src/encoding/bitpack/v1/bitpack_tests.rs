@@ -2,9 +2,9 @@ mod tests {
     use crate::encoding::bitpack::v1::{
         common::{BitEncodable, bit_width_from_value, clamp_width_to_type},
         reader::{BitReader, BitStream},
-        reader_pair::decode_pairs,
+        reader_pair::{PairStreamDecoder, decode_pairs},
         writer::BitWriter,
-        writer_pair::encode_pairs,
+        writer_pair::{PAIR_STREAM_HEADER_SIZE, PairStream, encode_pair_stream, encode_pairs},
     };
     use std::io::Cursor;
 
@@ -82,6 +82,39 @@ mod tests {
         assert_eq!(pairs, decoded);
     }
 
+    #[test]
+    fn roundtrip_pair_stream_reads_header() {
+        let pairs: Vec<(i16, u32)> = (0..1000).map(|i| ((i % 200) as i16 - 100, i * 7)).collect();
+        let encoded = encode_pair_stream(&pairs).unwrap();
+
+        let header = PairStream::read_from(&mut &encoded[..]).unwrap();
+        assert_eq!(
+            (header.width_a, header.width_b, header.count),
+            (8, 13, 1000)
+        );
+        assert_eq!(
+            encoded.len(),
+            PAIR_STREAM_HEADER_SIZE + (1000 * 21usize).div_ceil(8)
+        );
+
+        // no widths or count passed in: the decoder takes them from the stream.
+        let decoder = PairStreamDecoder::<_, i16, u32>::new(Cursor::new(&encoded)).unwrap();
+        assert_eq!(decoder.header(), &header);
+        let decoded: Vec<_> = decoder.collect::<std::io::Result<_>>().unwrap();
+        assert_eq!(pairs, decoded);
+
+        // a truncated stream reports the missing pairs instead of stopping quietly.
+        let short = &encoded[..encoded.len() - 10];
+        let result: std::io::Result<Vec<_>> =
+            PairStreamDecoder::<_, i16, u32>::new(Cursor::new(short))
+                .unwrap()
+                .collect();
+        assert_eq!(
+            result.unwrap_err().kind(),
+            std::io::ErrorKind::UnexpectedEof
+        );
+    }
+
     #[test]
     fn bit_width_sanity() {
         assert_eq!(bit_width_from_value(0u8), 1);
@@ -91,6 +124,55 @@ mod tests {
         assert_eq!(bit_width_from_value(-1i16), 1); // ZigZag(−1) = 1
     }
 
+    #[test]
+    fn zigzag_signed_edge_cases() {
+        fn check<T: BitEncodable + PartialEq + std::fmt::Debug>(min: T, neg_one: T, one: T) {
+            let bits = T::BITS as u8;
+            // MIN takes the largest code, all ones; -1 and 1 the smallest after 0.
+            assert_eq!(min.encode(), T::mask());
+            assert_eq!((neg_one.encode(), one.encode()), (1, 2));
+            assert_eq!(T::MAX.encode(), T::mask() - 1);
+
+            assert_eq!(bit_width_from_value(min), bits);
+            assert_eq!(bit_width_from_value(T::MAX), bits);
+            assert_eq!(bit_width_from_value(neg_one), 1);
+            assert_eq!(bit_width_from_value(one), 2);
+
+            for v in [min, neg_one, one, T::MAX] {
+                assert_eq!(T::decode(v.encode()), v);
+                // bits above the type's width are ignored.
+                if bits < 64 {
+                    assert_eq!(T::decode(v.encode() | (1 << bits)), v);
+                }
+            }
+
+            // through the bit writer and reader, at full width and at the narrowest widths.
+            let cases = [
+                (vec![min, neg_one, one, T::MAX, min], bits),
+                (vec![neg_one, neg_one], 1),
+                (vec![one, neg_one, one], 2),
+            ];
+            for (values, width) in cases {
+                let mut encoded = Vec::new();
+                let mut writer = BitWriter::<_, T>::new(&mut encoded, width);
+                writer.write_all_values(values.iter().copied()).unwrap();
+                writer.flush().unwrap();
+                drop(writer);
+                let decoded: Vec<T> =
+                    BitStream::<_, T>::with_count(Cursor::new(&encoded), width, values.len())
+                        .collect::<std::io::Result<_>>()
+                        .unwrap();
+                assert_eq!(decoded, values, "width {width}");
+            }
+        }
+
+        check(i8::MIN, -1i8, 1);
+        check(i16::MIN, -1i16, 1);
+        check(i32::MIN, -1i32, 1);
+        check(i64::MIN, -1i64, 1);
+        check(isize::MIN, -1isize, 1);
+    }
+
     #[test]
     fn clamp_width_does_not_exceed_type_bits() {
         assert_eq!(clamp_width_to_type::<u8>(10), 8);
@@ -0,0 +1,188 @@
+use crate::{
+    buffers::smart_pool::SmartBufferPool,
+    encoding::bitpack::v1::{common::BitEncodable, page_reader::PageHeader, reader::BitStream},
+};
+use std::collections::VecDeque;
+use std::io::{self, Cursor, Read};
+
+struct RetainedPage<T> {
+    /// Row position of the first value in the page.
+    start: u64,
+    values: Vec<T>,
+}
+
+/// Page decoder for `Read`-only sources (pipes, sockets) that supports limited backward
+/// seeks by keeping recently decoded pages in memory.
+///
+/// The retention budget is `retain_pages` decoded pages, counting the page currently being
+/// read, so memory is bounded by `retain_pages` times the page size. Seeking backwards is
+/// possible as far as the first value of the oldest retained page; anything earlier fails.
+/// Seeking forwards is always possible and decodes (and discards) the pages in between.
+pub struct BufferedPageDecoder<R: Read, T: BitEncodable> {
+    pool: SmartBufferPool,
+    source_reader: R,
+    retain_pages: usize,
+    pages: VecDeque<RetainedPage<T>>,
+    /// Number of values decoded from the source so far.
+    loaded_end: u64,
+    /// Position of the next value returned by `next`.
+    pos: u64,
+}
+
+impl<R: Read, T: BitEncodable> BufferedPageDecoder<R, T> {
+    /// Creates a decoder that keeps at most `retain_pages` pages (minimum 1) in memory.
+    pub fn new(pool: SmartBufferPool, source_reader: R, retain_pages: usize) -> Self {
+        Self {
+            pool,
+            source_reader,
+            retain_pages: retain_pages.max(1),
+            pages: VecDeque::new(),
+            loaded_end: 0,
+            pos: 0,
+        }
+    }
+
+    /// Position of the next value that will be returned.
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Earliest position a backward seek can reach.
+    pub fn earliest_position(&self) -> u64 {
+        self.pages
+            .front()
+            .map_or(self.loaded_end, |page| page.start)
+    }
+
+    /// Moves the read position to `pos`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidInput` error if `pos` lies before the retained pages.
+    pub fn seek(&mut self, pos: u64) -> io::Result<()> {
+        let earliest = self.earliest_position();
+        if pos < earliest {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "cannot seek back to {}: earliest retained position is {}",
+                    pos, earliest
+                ),
+            ));
+        }
+        // forward seeks are resolved lazily by `next`.
+        self.pos = pos;
+        Ok(())
+    }
+
+    /// Decodes the next page into the retention window. Returns `false` on a clean EOF
+    /// or at the stream footer.
+    fn load_next_page(&mut self) -> io::Result<bool> {
+        let header = match PageHeader::<T>::read_next(&mut self.source_reader) {
+            Ok(Some(header)) => header,
+            Ok(None) => return Ok(false),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(false),
+            Err(e) => return Err(e),
+        };
+
+        let mut buffer = self.pool.get(header.data_bytes as usize);
+        buffer.resize_uninit(header.data_bytes as usize);
+        self.source_reader.read_exact(buffer.as_mut_slice())?;
+
+        let values = BitStream::with_count(Cursor::new(buffer), header.bit_width, header.count)
+            .collect::<io::Result<Vec<T>>>()?;
+        let start = self.loaded_end;
+        self.loaded_end += values.len() as u64;
+        self.pages.push_back(RetainedPage { start, values });
+        while self.pages.len() > self.retain_pages {
+            self.pages.pop_front();
+        }
+        Ok(true)
+    }
+}
+
+impl<R: Read, T: BitEncodable> Iterator for BufferedPageDecoder<R, T> {
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pos >= self.loaded_end {
+            match self.load_next_page() {
+                Ok(true) => continue,
+                Ok(false) => return None,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+
+        let pos = self.pos;
+        let page = self.pages.iter().rev().find(|page| page.start <= pos)?;
+        let value = page.values[(pos - page.start) as usize];
+        self.pos += 1;
+        Some(Ok(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::bitpack::v1::page_writer::PageEncoder;
+
+    /// A source that only implements `Read`.
+    struct ReadOnly<'a>(&'a [u8]);
+
+    impl Read for ReadOnly<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    fn encode(pool: &SmartBufferPool, values: &[u32]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for page in PageEncoder::new(pool.clone(), values.iter().copied(), 12, 256) {
+            bytes.extend_from_slice(page.unwrap().as_slice());
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_backward_seek_within_window() -> io::Result<()> {
+        let pool = SmartBufferPool::new(1 << 20);
+        let values: Vec<u32> = (0..2_000).collect();
+        let bytes = encode(&pool, &values);
+
+        let mut decoder = BufferedPageDecoder::<_, u32>::new(pool, ReadOnly(&bytes), 2);
+        let first: Vec<u32> = decoder.by_ref().take(300).collect::<io::Result<_>>()?;
+        assert_eq!(first, values[..300]);
+
+        // a short step back stays inside the retained pages.
+        decoder.seek(290)?;
+        let again: Vec<u32> = decoder.by_ref().take(20).collect::<io::Result<_>>()?;
+        assert_eq!(again, values[290..310]);
+
+        // forward seeks skip ahead, then the rest of the stream follows.
+        decoder.seek(1_500)?;
+        let rest: Vec<u32> = decoder.collect::<io::Result<_>>()?;
+        assert_eq!(rest, values[1_500..]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_backward_seek_outside_window_errors() -> io::Result<()> {
+        let pool = SmartBufferPool::new(1 << 20);
+        let values: Vec<u32> = (0..2_000).collect();
+        let bytes = encode(&pool, &values);
+
+        let mut decoder = BufferedPageDecoder::<_, u32>::new(pool, ReadOnly(&bytes), 2);
+        let _ = decoder
+            .by_ref()
+            .take(1_800)
+            .collect::<io::Result<Vec<_>>>()?;
+        assert!(decoder.earliest_position() > 0);
+
+        let err = decoder.seek(10).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        // a failed seek leaves the position untouched.
+        assert_eq!(decoder.position(), 1_800);
+        assert_eq!(decoder.next().transpose()?, Some(1_800));
+        Ok(())
+    }
+}
@@ -0,0 +1,205 @@
+use crate::{
+    buffers::smart_pool::SmartBufferPool,
+    encoding::bitpack::v1::{
+        common::{BitEncodable, PAGE_HEADER_SIZE},
+        page_reader::PageHeader,
+        reader::BitStream,
+    },
+};
+use std::collections::VecDeque;
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+
+/// Where a page sits in the stream, found by walking the headers once.
+struct PageEntry {
+    /// Row position of the first value in the page.
+    start: u64,
+    count: u64,
+    /// Stream offset of the page header, which also keys the cache.
+    offset: u64,
+}
+
+/// Random-access page decoder for point lookups that keeps the most recently used decoded
+/// pages in memory, so repeated `value_at` calls landing on the same page decode it once.
+///
+/// Pages are located by walking every header when the decoder is created, seeking over the
+/// page data. The cache holds at most `max_cached_values` decoded values, i.e. about
+/// `max_cached_values * size_of::<T>()` bytes, and evicts the least recently used page
+/// first. A page larger than the whole budget is still decoded, and kept until the next
+/// page replaces it.
+pub struct CachedPageDecoder<R: Read + Seek, T: BitEncodable> {
+    pool: SmartBufferPool,
+    source_reader: R,
+    pages: Vec<PageEntry>,
+    /// Decoded pages keyed by stream offset, least recently used first.
+    cache: VecDeque<(u64, Vec<T>)>,
+    cached_values: usize,
+    max_cached_values: usize,
+    decoded_pages: usize,
+}
+
+impl<R: Read + Seek, T: BitEncodable> CachedPageDecoder<R, T> {
+    pub fn new(
+        pool: SmartBufferPool,
+        mut source_reader: R,
+        max_cached_values: usize,
+    ) -> io::Result<Self> {
+        source_reader.seek(SeekFrom::Start(0))?;
+        let mut pages = Vec::new();
+        let (mut offset, mut start) = (0u64, 0u64);
+        loop {
+            let header = match PageHeader::<T>::read_next(&mut source_reader) {
+                Ok(Some(header)) => header,
+                Ok(None) => break,
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e),
+            };
+            pages.push(PageEntry {
+                start,
+                count: header.count as u64,
+                offset,
+            });
+            start += header.count as u64;
+            offset += PAGE_HEADER_SIZE as u64 + header.data_bytes;
+            source_reader.seek(SeekFrom::Start(offset))?;
+        }
+
+        Ok(Self {
+            pool,
+            source_reader,
+            pages,
+            cache: VecDeque::new(),
+            cached_values: 0,
+            max_cached_values,
+            decoded_pages: 0,
+        })
+    }
+
+    /// Number of values in the stream.
+    pub fn len(&self) -> u64 {
+        self.pages.last().map_or(0, |page| page.start + page.count)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// How many times a page was decoded from the source, cache misses included.
+    pub fn decoded_pages(&self) -> usize {
+        self.decoded_pages
+    }
+
+    /// The value at row `index`, or `None` past the end of the stream.
+    pub fn value_at(&mut self, index: u64) -> io::Result<Option<T>> {
+        let page = self
+            .pages
+            .partition_point(|page| page.start + page.count <= index);
+        let Some(entry) = self.pages.get(page) else {
+            return Ok(None);
+        };
+        let (start, offset) = (entry.start, entry.offset);
+        let values = self.page_values(offset)?;
+        Ok(values.get((index - start) as usize).copied())
+    }
+
+    /// Decoded values of the page at `offset`, from the cache when possible.
+    fn page_values(&mut self, offset: u64) -> io::Result<&[T]> {
+        if let Some(hit) = self.cache.iter().position(|(key, _)| *key == offset) {
+            let page = self.cache.remove(hit).unwrap();
+            self.cache.push_back(page);
+        } else {
+            let values = self.decode_page(offset)?;
+            self.cached_values += values.len();
+            self.cache.push_back((offset, values));
+            while self.cached_values > self.max_cached_values && self.cache.len() > 1 {
+                let (_, evicted) = self.cache.pop_front().unwrap();
+                self.cached_values -= evicted.len();
+            }
+        }
+        Ok(&self.cache.back().unwrap().1)
+    }
+
+    fn decode_page(&mut self, offset: u64) -> io::Result<Vec<T>> {
+        self.source_reader.seek(SeekFrom::Start(offset))?;
+        let header = PageHeader::<T>::read_from(&mut self.source_reader)?;
+
+        let mut buffer = self.pool.get(header.data_bytes as usize);
+        buffer.resize_uninit(header.data_bytes as usize);
+        self.source_reader.read_exact(buffer.as_mut_slice())?;
+        self.decoded_pages += 1;
+        BitStream::with_count(Cursor::new(buffer), header.bit_width, header.count).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::bitpack::v1::page_writer::PageEncoder;
+
+    /// Counts the bytes read through it.
+    struct Counting<R> {
+        inner: R,
+        read: usize,
+    }
+
+    impl<R: Read> Read for Counting<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.inner.read(buf)?;
+            self.read += n;
+            Ok(n)
+        }
+    }
+
+    impl<R: Seek> Seek for Counting<R> {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    fn encode(pool: &SmartBufferPool, values: &[u32]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for page in PageEncoder::new(pool.clone(), values.iter().copied(), 12, 256) {
+            bytes.extend_from_slice(page.unwrap().as_slice());
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_point_lookups_in_one_page_decode_once() -> io::Result<()> {
+        let pool = SmartBufferPool::new(1 << 20);
+        let values: Vec<u32> = (0..2_000).map(|v| v * 3 % 4096).collect();
+        let source = Counting {
+            inner: Cursor::new(encode(&pool, &values)),
+            read: 0,
+        };
+        let mut decoder = CachedPageDecoder::<_, u32>::new(pool, source, 1_000)?;
+        assert_eq!(decoder.len(), 2_000);
+
+        assert_eq!(decoder.value_at(200)?, Some(values[200]));
+        let read = decoder.source_reader.read;
+        for i in 0..100 {
+            let index = 170 + i % 50;
+            assert_eq!(decoder.value_at(index)?, Some(values[index as usize]));
+        }
+        assert_eq!(decoder.decoded_pages(), 1);
+        assert_eq!(decoder.source_reader.read, read);
+
+        assert_eq!(decoder.value_at(2_000)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() -> io::Result<()> {
+        let pool = SmartBufferPool::new(1 << 20);
+        let values: Vec<u32> = (0..2_000).collect();
+        let bytes = encode(&pool, &values);
+        // 12-bit values: 128 per 256-byte page, so the budget fits two pages.
+        let mut decoder = CachedPageDecoder::<_, u32>::new(pool, Cursor::new(bytes), 320)?;
+
+        for index in [0, 200, 0, 400, 0, 200] {
+            assert_eq!(decoder.value_at(index)?, Some(index as u32));
+        }
+        // row 0 stays hot; the page of row 200 is evicted by that of row 400 and decoded again.
+        assert_eq!(decoder.decoded_pages(), 4);
+        Ok(())
+    }
+}
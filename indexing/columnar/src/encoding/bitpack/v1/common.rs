@@ -4,6 +4,13 @@ use core::mem::size_of;
 pub const PAGE_MAGIC_BITPACK: &[u8; 6] = b"BITPK1";
 pub const PAGE_VERSION: u8 = 1;
 
+/// Header byte holding the page's feature flags. It sits past the widest min/max layout,
+/// so pages written before flags existed read as `0`.
+pub const PAGE_FLAGS_OFFSET: usize = PAGE_HEADER_SIZE - 1;
+/// Feature flags this version of the decoders understands. Pages with any other bit set
+/// need a newer reader and are rejected instead of being decoded as plain bitpacked data.
+pub const PAGE_KNOWN_FLAGS: u8 = 0;
+
 pub const PAGE_DEFAULT_SIZE: usize = 64 * 1024;
 pub const PAGE_HEADER_SIZE: usize = 64;
 
@@ -22,6 +29,22 @@ pub trait BitEncodable: LeNum + Sized + Copy + Ord {
     fn decode(payload: u64) -> Self;
     // fn to_le_bytes(self) -> Vec<u8>;
     // fn from_le_bytes(slice: &[u8]) -> Self;
+    /// Decodes a run of full-width little-endian values (`width == BITS`) straight from
+    /// `bytes` with a single copy. Returns `None` when the type has no such layout (signed
+    /// types are ZigZag encoded) or the host is big-endian, so callers fall back to bit reads.
+    #[inline(always)]
+    fn from_le_slice(bytes: &[u8]) -> Option<Vec<Self>> {
+        let mut out = vec![Self::MIN; bytes.len() / (Self::BITS as usize / 8)];
+        Self::copy_from_le_slice(bytes, &mut out).then_some(out)
+    }
+
+    /// Like `from_le_slice`, but fills `out` in place (reading `out.len()` values) so a
+    /// reused buffer avoids the allocation. Returns `false` when the fast path is unavailable.
+    #[inline(always)]
+    fn copy_from_le_slice(_bytes: &[u8], _out: &mut [Self]) -> bool {
+        false
+    }
+
     /// A mask of the lower `BITS` bits.
     #[inline(always)]
     fn mask() -> u64 {
@@ -33,12 +56,22 @@ pub trait BitEncodable: LeNum + Sized + Copy + Ord {
     }
 }
 
+/// ZigZag-encodes `n`, a `bits`-wide signed value sign-extended to `i64`, so small
+/// magnitudes of either sign get small codes: 0, -1, 1, -2, ... map to 0, 1, 2, 3, ...
+///
+/// The result always fits in `bits` bits. `n >> (bits - 1)` is an arithmetic shift of the
+/// sign-extended value, so it is 0 for non-negative `n` and all ones for negative `n`, and
+/// the xor turns `n << 1` into `2n` or `-2n - 1`. Both are below `2^bits` for any `n` in the
+/// type's range; `MIN` gets the largest code, all `bits` ones (`i64::MIN << 1` wraps to 0
+/// and the xor with all ones yields `u64::MAX`).
 #[inline(always)]
 fn zigzag_encode_width_aware(n: i64, bits: u32) -> u64 {
-    // ZigZag: (n << 1) ^ (n >> (bits-1))  // arithmetic shift for sign
     ((n << 1) ^ (n >> (bits - 1))) as u64
 }
 
+/// Inverse of `zigzag_encode_width_aware` for codes that fit the type: even codes are
+/// `code / 2`, odd ones `-(code + 1) / 2`. The caller truncates the `i64` to the type, which
+/// is lossless since the code came from a value of that type.
 #[inline(always)]
 fn zigzag_decode_u64(u: u64) -> i64 {
     // ZigZag inverse: (u >> 1) ^ -(u & 1)
@@ -65,6 +98,17 @@ macro_rules! impl_bitencodable_unsigned {
                     // Mask to the destination width and cast back
                     (payload & Self::mask()) as $t
                 }
+
+                #[inline(always)]
+                fn copy_from_le_slice(bytes: &[u8], out: &mut [Self]) -> bool {
+                    use zerocopy::IntoBytes;
+                    if cfg!(target_endian = "big") {
+                        return false;
+                    }
+                    let len = out.as_bytes().len();
+                    out.as_mut_bytes().copy_from_slice(&bytes[..len]);
+                    true
+                }
             }
         )*
     };
@@ -103,8 +147,10 @@ impl_bitencodable_signed!(i8, i16, i32, i64, isize);
 
 /* ---------- Helpers you can reuse with any BitEncodable ---------- */
 
-/// Minimal bit width needed to store `value` after encoding.
-/// (Returns 0 for 0.)
+/// Minimal bit width needed to store `value` after encoding. At least 1, even for 0.
+///
+/// Signed values are measured after ZigZag encoding, so the width follows the magnitude:
+/// -1 takes 1 bit, 1 takes 2, and both `MIN` and `MAX` take the type's full width.
 #[inline(always)]
 pub fn bit_width_from_value<T: BitEncodable>(value: T) -> u8 {
     let enc = value.encode();
@@ -0,0 +1,215 @@
+use crate::{
+    buffers::smart_pool::SmartBufferPool,
+    encoding::bitpack::v1::{
+        common::BitEncodable, page_reader::PageDecoder, page_writer::write_pages,
+    },
+};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Merged values encoded into pages at a time by `external_sort`.
+const MERGE_CHUNK: usize = 16 * 1024;
+
+/// Most runs `external_sort` merges, and so holds open, at once.
+const MERGE_FAN_IN: usize = 64;
+
+/// Sorts the values of the page streams in `input_readers` into a single ascending page
+/// stream written to `out_writer`, for columns too large to sort in memory.
+///
+/// Values are read `run_size` at a time across the inputs; each run is sorted and spilled
+/// to a temp file as a page stream and closed. The runs are then k-way merged through a
+/// heap holding the next value of every run, at most `MERGE_FAN_IN` runs per pass, so
+/// memory is bounded by one run while spilling and by one decoded page per merged run
+/// while merging, and only that many run files are open at once. The temp files need room
+/// for the whole column, packed, twice over while a pass runs.
+pub fn external_sort<T, R, W>(
+    input_readers: Vec<R>,
+    run_size: usize,
+    pool: SmartBufferPool,
+    out_writer: W,
+) -> io::Result<()>
+where
+    T: BitEncodable,
+    R: Read,
+    W: Write,
+{
+    sort_with_fan_in::<T, R, W>(input_readers, run_size, MERGE_FAN_IN, pool, out_writer)
+}
+
+fn sort_with_fan_in<T, R, W>(
+    input_readers: Vec<R>,
+    run_size: usize,
+    fan_in: usize,
+    pool: SmartBufferPool,
+    mut out_writer: W,
+) -> io::Result<()>
+where
+    T: BitEncodable,
+    R: Read,
+    W: Write,
+{
+    let temp_dir = tempfile::tempdir()?;
+    let mut spill = SpillDir {
+        dir: temp_dir.path(),
+        next: 0,
+    };
+
+    let run_size = run_size.max(1);
+    let mut runs = Vec::new();
+    let mut run = vec![T::MIN; run_size];
+    let mut filled = 0;
+    for reader in input_readers {
+        let mut decoder = PageDecoder::<_, T>::new(pool.clone(), reader);
+        loop {
+            let n = decoder.read_values(&mut run[filled..])?;
+            if n == 0 {
+                break;
+            }
+            filled += n;
+            if filled == run_size {
+                runs.push(spill.run(&mut run, &pool)?);
+                filled = 0;
+            }
+        }
+    }
+    if filled > 0 {
+        runs.push(spill.run(&mut run[..filled], &pool)?);
+    }
+    drop(run);
+
+    // merge groups of runs into longer ones until one pass can take them all.
+    let fan_in = fan_in.max(2);
+    while runs.len() > fan_in {
+        let mut merged = Vec::with_capacity(runs.len().div_ceil(fan_in));
+        for group in runs.chunks(fan_in) {
+            let path = spill.next_path();
+            let mut writer = BufWriter::new(File::create(&path)?);
+            merge_runs::<T, _>(group, &pool, &mut writer)?;
+            writer.flush()?;
+            for done in group {
+                fs::remove_file(done)?;
+            }
+            merged.push(path);
+        }
+        runs = merged;
+    }
+    merge_runs::<T, _>(&runs, &pool, &mut out_writer)?;
+    out_writer.flush()
+}
+
+/// Numbers the run files spilled under `dir`.
+struct SpillDir<'a> {
+    dir: &'a Path,
+    next: usize,
+}
+
+impl SpillDir<'_> {
+    fn next_path(&mut self) -> PathBuf {
+        self.next += 1;
+        self.dir.join(format!("run_{}.bin", self.next))
+    }
+
+    /// Sorts `run` and writes it to a new run file as a page stream.
+    fn run<T: BitEncodable>(
+        &mut self,
+        run: &mut [T],
+        pool: &SmartBufferPool,
+    ) -> io::Result<PathBuf> {
+        run.sort_unstable();
+        let path = self.next_path();
+        let mut writer = BufWriter::new(File::create(&path)?);
+        write_pages(run, pool, &mut writer)?;
+        writer.flush()?;
+        Ok(path)
+    }
+}
+
+/// K-way merges the sorted page streams in `runs` into `out`.
+fn merge_runs<T: BitEncodable, W: Write>(
+    runs: &[PathBuf],
+    pool: &SmartBufferPool,
+    out: &mut W,
+) -> io::Result<()> {
+    let mut decoders = Vec::with_capacity(runs.len());
+    let mut heap = BinaryHeap::with_capacity(runs.len());
+    for path in runs {
+        let reader = BufReader::new(File::open(path)?);
+        let mut decoder = PageDecoder::<_, T>::new(pool.clone(), reader);
+        if let Some(v) = decoder.next().transpose()? {
+            heap.push(Reverse((v, decoders.len())));
+        }
+        decoders.push(decoder);
+    }
+
+    let mut merged = Vec::with_capacity(MERGE_CHUNK);
+    while let Some(Reverse((v, run))) = heap.pop() {
+        merged.push(v);
+        if let Some(next) = decoders[run].next().transpose()? {
+            heap.push(Reverse((next, run)));
+        }
+        if merged.len() == MERGE_CHUNK {
+            write_pages(&merged, pool, out)?;
+            merged.clear();
+        }
+    }
+    write_pages(&merged, pool, out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn encode(values: &[u32], pool: &SmartBufferPool) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_pages(values, pool, &mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_external_sort_merges_runs_across_inputs() -> io::Result<()> {
+        let pool = SmartBufferPool::new(1 << 20);
+        // 7919 is coprime with 100_000, so this visits every value once, shuffled.
+        let shuffled: Vec<u32> = (0..100_000u32).map(|i| i * 7919 % 100_000).collect();
+        let inputs = [
+            &shuffled[..30_000],
+            &shuffled[30_000..30_001],
+            &shuffled[30_001..],
+        ]
+        .iter()
+        .map(|part| Cursor::new(encode(part, &pool)))
+        .collect();
+
+        let mut out = Vec::new();
+        // runs straddle the input boundaries and leave a short last run.
+        external_sort::<u32, _, _>(inputs, 8_192, pool.clone(), &mut out)?;
+
+        let sorted: Vec<u32> = PageDecoder::<_, u32>::new(pool.clone(), Cursor::new(out))
+            .collect::<io::Result<_>>()?;
+        assert_eq!(sorted, (0..100_000u32).collect::<Vec<_>>());
+
+        let mut empty = Vec::new();
+        external_sort::<u32, _, _>(vec![Cursor::new(Vec::new())], 16, pool, &mut empty)?;
+        assert!(empty.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_external_sort_merges_in_bounded_passes() -> io::Result<()> {
+        let pool = SmartBufferPool::new(1 << 20);
+        let shuffled: Vec<u32> = (0..10_000u32).map(|i| i * 7919 % 10_000).collect();
+        let inputs = vec![Cursor::new(encode(&shuffled, &pool))];
+
+        // 40 runs merged 3 at a time take three passes (14, 5, 2 runs) before the last merge.
+        let mut out = Vec::new();
+        sort_with_fan_in::<u32, _, _>(inputs, 250, 3, pool.clone(), &mut out)?;
+
+        let sorted: Vec<u32> =
+            PageDecoder::<_, u32>::new(pool, Cursor::new(out)).collect::<io::Result<_>>()?;
+        assert_eq!(sorted, (0..10_000u32).collect::<Vec<_>>());
+        Ok(())
+    }
+}
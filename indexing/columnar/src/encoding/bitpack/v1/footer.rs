@@ -0,0 +1,153 @@
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+/// Marks a stream footer. It sits where the next page header would, so readers walking
+/// pages stop there, and is repeated as the last bytes of the stream.
+pub const STREAM_FOOTER_MAGIC: &[u8; 6] = b"BPFOOT";
+
+/// Footer length (`u32`) plus the closing magic, at the very end of the stream.
+const TRAILER_SIZE: usize = 4 + STREAM_FOOTER_MAGIC.len();
+const FLAG_PAGE_INDEX: u8 = 1;
+
+/// Optional summary written after the last page of a bitpack stream:
+///
+/// `[magic][flags: u8][total_pages: u64][total_values: u64][page offsets: u64 * total_pages]`
+/// `[footer_len: u32][magic]`
+///
+/// All integers are little-endian. Page offsets are only present when bit 0 of `flags` is
+/// set and count from the start of the stream; `footer_len` covers the whole footer, both
+/// magics included, so it can be found from the end of the stream.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StreamFooter {
+    pub total_pages: u64,
+    pub total_values: u64,
+    /// Stream offset of every page header, when the writer kept an index.
+    pub page_offsets: Option<Vec<u64>>,
+}
+
+impl StreamFooter {
+    pub fn encoded_len(&self) -> usize {
+        let offsets = self.page_offsets.as_ref().map_or(0, Vec::len);
+        STREAM_FOOTER_MAGIC.len() + 1 + 16 + offsets * 8 + TRAILER_SIZE
+    }
+
+    pub fn write_to<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        let mut buf = Vec::with_capacity(self.encoded_len());
+        buf.extend_from_slice(STREAM_FOOTER_MAGIC);
+        buf.push(if self.page_offsets.is_some() {
+            FLAG_PAGE_INDEX
+        } else {
+            0
+        });
+        buf.extend_from_slice(&self.total_pages.to_le_bytes());
+        buf.extend_from_slice(&self.total_values.to_le_bytes());
+        for offset in self.page_offsets.iter().flatten() {
+            buf.extend_from_slice(&offset.to_le_bytes());
+        }
+        buf.extend_from_slice(&(self.encoded_len() as u32).to_le_bytes());
+        buf.extend_from_slice(STREAM_FOOTER_MAGIC);
+        writer.write_all(&buf)
+    }
+
+    /// Parses the rest of a footer whose leading magic was already consumed, as happens to a
+    /// reader that meets it in place of a page header.
+    pub fn read_after_magic<R: Read + ?Sized>(reader: &mut R) -> io::Result<Self> {
+        let mut fixed = [0u8; 17];
+        reader.read_exact(&mut fixed)?;
+        let total_pages = u64::from_le_bytes(fixed[1..9].try_into().unwrap());
+        let total_values = u64::from_le_bytes(fixed[9..17].try_into().unwrap());
+
+        let page_offsets = if fixed[0] & FLAG_PAGE_INDEX != 0 {
+            let mut offsets = Vec::new();
+            let mut word = [0u8; 8];
+            for _ in 0..total_pages {
+                reader.read_exact(&mut word)?;
+                offsets.push(u64::from_le_bytes(word));
+            }
+            Some(offsets)
+        } else {
+            None
+        };
+
+        let footer = Self {
+            total_pages,
+            total_values,
+            page_offsets,
+        };
+        let mut trailer = [0u8; TRAILER_SIZE];
+        reader.read_exact(&mut trailer)?;
+        let footer_len = u32::from_le_bytes(trailer[..4].try_into().unwrap()) as usize;
+        if &trailer[4..] != STREAM_FOOTER_MAGIC || footer_len != footer.encoded_len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "stream footer trailer does not match its contents",
+            ));
+        }
+        Ok(footer)
+    }
+
+    /// Reads the footer from the end of `reader`, which must end where the stream does.
+    /// Returns `None` for streams written without one. The reader's position is restored.
+    pub fn read_from_end<R: Read + Seek + ?Sized>(reader: &mut R) -> io::Result<Option<Self>> {
+        let restore = reader.stream_position()?;
+        let result = Self::read_tail(reader);
+        reader.seek(SeekFrom::Start(restore))?;
+        result
+    }
+
+    fn read_tail<R: Read + Seek + ?Sized>(reader: &mut R) -> io::Result<Option<Self>> {
+        let end = reader.seek(SeekFrom::End(0))?;
+        if end < TRAILER_SIZE as u64 {
+            return Ok(None);
+        }
+        let mut trailer = [0u8; TRAILER_SIZE];
+        reader.seek(SeekFrom::End(-(TRAILER_SIZE as i64)))?;
+        reader.read_exact(&mut trailer)?;
+        if &trailer[4..] != STREAM_FOOTER_MAGIC {
+            return Ok(None);
+        }
+
+        let footer_len = u32::from_le_bytes(trailer[..4].try_into().unwrap()) as u64;
+        if footer_len > end {
+            return Ok(None);
+        }
+        reader.seek(SeekFrom::Start(end - footer_len))?;
+        let mut magic = [0u8; STREAM_FOOTER_MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if &magic != STREAM_FOOTER_MAGIC {
+            return Ok(None);
+        }
+        Self::read_after_magic(reader).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_footer_roundtrip_from_end() {
+        for page_offsets in [None, Some(vec![0, 300, 900])] {
+            let footer = StreamFooter {
+                total_pages: 3,
+                total_values: 1234,
+                page_offsets,
+            };
+            let mut bytes = vec![7u8; 50];
+            footer.write_to(&mut bytes).unwrap();
+            assert_eq!(bytes.len(), 50 + footer.encoded_len());
+
+            let mut reader = Cursor::new(bytes);
+            reader.set_position(5);
+            let read = StreamFooter::read_from_end(&mut reader).unwrap();
+            assert_eq!(read, Some(footer));
+            assert_eq!(reader.position(), 5);
+        }
+
+        // streams without a footer, including ones too short to hold a trailer.
+        for bytes in [vec![], vec![1u8; 4], vec![9u8; 200]] {
+            let read = StreamFooter::read_from_end(&mut Cursor::new(bytes)).unwrap();
+            assert_eq!(read, None);
+        }
+    }
+}
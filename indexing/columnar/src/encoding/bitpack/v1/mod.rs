@@ -1,9 +1,18 @@
 pub mod bitpack_tests;
+pub mod buffered_page_reader;
+pub mod cached_page_reader;
 pub mod common;
+pub mod external_sort;
+pub mod footer;
+pub mod page_index;
 pub mod page_reader;
 pub mod page_writer;
+pub mod pred;
 pub mod reader;
 pub mod reader_pair;
 pub mod stream_writer;
+pub mod transcode;
+pub mod unpack;
+pub mod validate;
 pub mod writer;
 pub mod writer_pair;
@@ -0,0 +1,109 @@
+use crate::encoding::bitpack::v1::{
+    common::{BitEncodable, PAGE_HEADER_SIZE},
+    page_reader::PageHeader,
+};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+/// Marks a page index file.
+pub const PAGE_INDEX_MAGIC: &[u8; 6] = b"BPPIDX";
+
+/// Where every page of a bitpack stream starts, kept next to the stream so readers can
+/// jump to a row without walking the page headers:
+///
+/// `[magic][total_values: u64][pages: u64][(first_row: u64, offset: u64) * pages]`
+///
+/// All integers are little-endian; offsets count from the start of the stream.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PageIndex {
+    pub total_values: u64,
+    /// `(first_row, offset)` of every page, in stream order.
+    pub pages: Vec<(u64, u64)>,
+}
+
+impl PageIndex {
+    /// Walks the page headers of the stream in `reader` from its start, seeking over the
+    /// page data. Stops at the end of the stream or at its footer.
+    pub fn build<T: BitEncodable, R: Read + Seek>(reader: &mut R) -> io::Result<Self> {
+        reader.seek(SeekFrom::Start(0))?;
+        let mut index = Self::default();
+        let mut offset = 0u64;
+        loop {
+            let header = match PageHeader::<T>::read_next(reader) {
+                Ok(Some(header)) => header,
+                Ok(None) => break,
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e),
+            };
+            index.pages.push((index.total_values, offset));
+            index.total_values += header.count as u64;
+            offset += PAGE_HEADER_SIZE as u64 + header.data_bytes;
+            reader.seek(SeekFrom::Start(offset))?;
+        }
+        Ok(index)
+    }
+
+    /// Offset of the page holding `row` and the row's position within it, or `None` past
+    /// the last value.
+    pub fn locate(&self, row: u64) -> Option<(u64, usize)> {
+        if row >= self.total_values {
+            return None;
+        }
+        let page = self
+            .pages
+            .partition_point(|&(first_row, _)| first_row <= row)
+            .checked_sub(1)?;
+        let (first_row, offset) = self.pages[page];
+        Some((offset, (row - first_row) as usize))
+    }
+
+    pub fn write_to<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        let mut buf = Vec::with_capacity(PAGE_INDEX_MAGIC.len() + 16 + self.pages.len() * 16);
+        buf.extend_from_slice(PAGE_INDEX_MAGIC);
+        buf.extend_from_slice(&self.total_values.to_le_bytes());
+        buf.extend_from_slice(&(self.pages.len() as u64).to_le_bytes());
+        for (first_row, offset) in &self.pages {
+            buf.extend_from_slice(&first_row.to_le_bytes());
+            buf.extend_from_slice(&offset.to_le_bytes());
+        }
+        writer.write_all(&buf)
+    }
+
+    pub fn read_from<R: Read + ?Sized>(reader: &mut R) -> io::Result<Self> {
+        let mut fixed = [0u8; PAGE_INDEX_MAGIC.len() + 16];
+        reader.read_exact(&mut fixed)?;
+        if &fixed[..6] != PAGE_INDEX_MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a page index: bad magic",
+            ));
+        }
+        let total_values = u64::from_le_bytes(fixed[6..14].try_into().unwrap());
+        let count = u64::from_le_bytes(fixed[14..22].try_into().unwrap());
+
+        let mut pages = Vec::new();
+        let mut entry = [0u8; 16];
+        for _ in 0..count {
+            reader.read_exact(&mut entry)?;
+            pages.push((
+                u64::from_le_bytes(entry[..8].try_into().unwrap()),
+                u64::from_le_bytes(entry[8..].try_into().unwrap()),
+            ));
+        }
+        if pages
+            .first()
+            .map_or(total_values > 0, |&(first_row, _)| first_row != 0)
+            || pages
+                .windows(2)
+                .any(|w| w[1].0 < w[0].0 || w[1].1 <= w[0].1)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "page index entries are not in stream order",
+            ));
+        }
+        Ok(Self {
+            total_values,
+            pages,
+        })
+    }
+}
@@ -1,11 +1,20 @@
 use crate::{
     buffers::smart_pool::{SmartBufferPool, SmartPage},
     encoding::bitpack::v1::{
-        common::{BitEncodable, PAGE_HEADER_SIZE, PAGE_MAGIC_BITPACK, PAGE_VERSION},
+        common::{
+            BitEncodable, PAGE_FLAGS_OFFSET, PAGE_HEADER_SIZE, PAGE_KNOWN_FLAGS,
+            PAGE_MAGIC_BITPACK, PAGE_VERSION,
+        },
+        footer::{STREAM_FOOTER_MAGIC, StreamFooter},
         reader::BitStream,
     },
 };
-use std::io::{self, Cursor, Read};
+use memmap2::MmapMut;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+use toolkit::fspkg::file_slice::FileSlice;
+use zerocopy::{FromBytes, IntoBytes};
 
 pub struct PageHeader<T: BitEncodable> {
     pub min: T,
@@ -13,9 +22,24 @@ pub struct PageHeader<T: BitEncodable> {
     pub count: usize,
     pub bit_width: u8,
     pub data_bytes: u64,
+    /// Format version the page was written with, at most `PAGE_VERSION`.
+    pub version: u8,
+    /// Feature flags the page was written with, a subset of `PAGE_KNOWN_FLAGS`.
+    pub flags: u8,
 }
 
 impl<T: BitEncodable> PageHeader<T> {
+    /// Reads the next page header, or `None` when the stream footer starts here instead.
+    /// The footer's magic is consumed; a clean end of stream is still `UnexpectedEof`.
+    pub fn read_next<R: Read>(reader: &mut R) -> io::Result<Option<Self>> {
+        let mut magic = [0u8; STREAM_FOOTER_MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if &magic == STREAM_FOOTER_MAGIC {
+            return Ok(None);
+        }
+        Self::read_from(&mut magic.chain(reader)).map(Some)
+    }
+
     pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Self> {
         let mut header_buf = [0u8; PAGE_HEADER_SIZE];
         reader.read_exact(&mut header_buf)?;
@@ -31,29 +55,42 @@ impl<T: BitEncodable> PageHeader<T> {
             ));
         }
 
-        if header_buf[6] != PAGE_VERSION {
+        let version = header_buf[6];
+        if version == 0 || version > PAGE_VERSION {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidData,
-                format!("unsupported page version {}", header_buf[6]),
+                format!("unsupported page version {version}, expected 1..={PAGE_VERSION}"),
             ));
         }
 
+        let flags = header_buf[PAGE_FLAGS_OFFSET];
+        if flags & !PAGE_KNOWN_FLAGS != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "page uses unsupported feature flags {:#010b} (this reader knows {:#010b})",
+                    flags & !PAGE_KNOWN_FLAGS,
+                    PAGE_KNOWN_FLAGS
+                ),
+            ));
+        }
+
+        // Width in bytes of the stored min/max values. It must match `T` exactly; slicing
+        // below uses the on-disk width so a mismatch can never be read silently.
         let type_width = header_buf[7] as usize;
-        if (type_width * 8) != T::BITS as usize {
+        let expected_width = (T::BITS / 8) as usize;
+        if type_width != expected_width {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidData,
                 format!(
-                    "type width mismatch: expected {}, found {}",
-                    T::BITS,
-                    type_width * 8
+                    "type width mismatch: page stores {}-byte values, decoder expects {}-byte values",
+                    type_width, expected_width
                 ),
             ));
         }
 
         let bit_width = header_buf[8];
-        let count = u64::from_le_bytes(header_buf[9..17].try_into().unwrap()) as usize;
-
-        let type_width = (T::BITS / 8) as usize;
+        let count = u64::from_le_bytes(header_buf[9..17].try_into().unwrap());
 
         let start: usize = 17;
         let end = start + type_width;
@@ -67,20 +104,162 @@ impl<T: BitEncodable> PageHeader<T> {
         let end = start + 8;
 
         let data_bytes = u64::from_le_bytes(header_buf[start..end].try_into().unwrap());
+
+        // Decoders read exactly `count` values from the data section, so a count the data
+        // cannot hold is corruption. Zero-width pages store no data and cannot be checked.
+        if bit_width > 0 && count > data_bytes.saturating_mul(8) / bit_width as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "page count {count} exceeds the {} values {data_bytes} data bytes hold at {bit_width} bits",
+                    data_bytes.saturating_mul(8) / bit_width as u64
+                ),
+            ));
+        }
+        let count = usize::try_from(count).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("page count {count} does not fit in usize"),
+            )
+        })?;
+
         Ok(Self {
             min,
             max,
             count,
             bit_width,
             data_bytes,
+            version,
+            flags,
         })
     }
 }
 
+/// Decodes a bitpacked column stored in a footer file, reading pages lazily from the slice.
+///
+/// Only the page currently being decoded is buffered, so large columns can be scanned
+/// without loading the whole slice.
+///
+/// ```
+/// use columnar::SmartBufferPool;
+/// use columnar::encoding::bitpack::v1::page_reader::decode_slice;
+/// use columnar::encoding::bitpack::v1::page_writer::PageEncoder;
+/// use toolkit::footerfile::{file_decoder::FooterFileDecoder, file_encoder::FooterFileEncoder};
+///
+/// # fn main() -> std::io::Result<()> {
+/// let dir = tempfile::tempdir()?;
+/// let path = dir.path().join("columns.bin");
+/// let pool = SmartBufferPool::default();
+///
+/// // encode a column into pages and store it as column 7
+/// let mut bytes = Vec::new();
+/// for page in PageEncoder::new(pool.clone(), (0..1000u32).into_iter(), 10, 256) {
+///     bytes.extend_from_slice(page?.as_slice());
+/// }
+/// let mut encoder = FooterFileEncoder::create(path.clone())?;
+/// encoder.write(7, &mut bytes.as_slice())?;
+/// encoder.close()?;
+///
+/// // decode it back lazily
+/// let mut decoder = FooterFileDecoder::new(path)?;
+/// let values = decode_slice::<u32>(decoder.get_column(7)?, pool)
+///     .collect::<std::io::Result<Vec<_>>>()?;
+/// assert_eq!(values, (0..1000).collect::<Vec<u32>>());
+/// # Ok(())
+/// # }
+/// ```
+pub fn decode_slice<T: BitEncodable>(
+    slice: FileSlice<File>,
+    pool: SmartBufferPool,
+) -> PageDecoder<FileSlice<File>, T> {
+    PageDecoder::new(pool, slice)
+}
+
+/// Decodes the first `count` values of a page stream into a file at `out_path`, for columns
+/// too large to collect into a `Vec<T>`.
+///
+/// The file is created (or truncated) to `count * size_of::<T>()` bytes, memory-mapped and
+/// viewed as a `[T]` that the decoder writes into directly, so values are stored in native
+/// byte order. Fails with `UnexpectedEof` if the stream holds fewer than `count` values;
+/// anything after them is left unread.
+pub fn decode_to_mmap<T, R, P>(
+    src_reader: R,
+    out_path: P,
+    count: usize,
+    pool: SmartBufferPool,
+) -> io::Result<()>
+where
+    T: BitEncodable + FromBytes + IntoBytes,
+    R: Read,
+    P: AsRef<Path>,
+{
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(out_path)?;
+    file.set_len((count * size_of::<T>()) as u64)?;
+    if count == 0 {
+        return Ok(());
+    }
+
+    // SAFETY: the file was just created by us and is not shared while mapped.
+    let mut mmap = unsafe { MmapMut::map_mut(&file)? };
+    // mappings are page aligned, so the cast only fails on a broken platform.
+    let out = <[T]>::mut_from_bytes(&mut mmap[..])
+        .map_err(|_| io::Error::other("memory map is not aligned for the value type"))?;
+    let mut decoder = PageDecoder::<_, T>::new(pool, src_reader);
+    let mut filled = 0;
+    while filled < count {
+        let n = decoder.read_values(&mut out[filled..])?;
+        if n == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("stream ended after {filled} of {count} values"),
+            ));
+        }
+        filled += n;
+    }
+    mmap.flush()
+}
+
+/// Global `(min, max)` of a page stream, folded from the page headers alone: each page's
+/// data is skipped with a seek, never read or decoded. `None` for a stream without values.
+/// Stops at the stream footer or the end of the stream.
+pub fn stream_min_max<T, R>(mut reader: R) -> io::Result<Option<(T, T)>>
+where
+    T: BitEncodable,
+    R: Read + Seek,
+{
+    let mut extremes: Option<(T, T)> = None;
+    loop {
+        let header = match PageHeader::<T>::read_next(&mut reader) {
+            Ok(Some(header)) => header,
+            Ok(None) => break,
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e),
+        };
+        reader.seek(SeekFrom::Current(header.data_bytes as i64))?;
+        if header.count == 0 {
+            continue;
+        }
+        extremes = Some(match extremes {
+            Some((min, max)) => (min.min(header.min), max.max(header.max)),
+            None => (header.min, header.max),
+        });
+    }
+    Ok(extremes)
+}
+
 pub struct PageDecoder<R: Read, T: BitEncodable> {
     pool: SmartBufferPool,
     source_reader: R,
     current_stream: Option<BitStream<Cursor<SmartPage>, T>>,
+    /// Stream offset of the next page header.
+    pos: u64,
+    /// No page whose header starts at or after this offset is decoded.
+    end: Option<u64>,
 }
 
 impl<R: Read, T: BitEncodable> PageDecoder<R, T> {
@@ -89,6 +268,193 @@ impl<R: Read, T: BitEncodable> PageDecoder<R, T> {
             pool,
             source_reader,
             current_stream: None,
+            pos: 0,
+            end: None,
+        }
+    }
+
+    /// Like `new`, but reads the first page right away, so a stream written for another
+    /// element type, or one that is not a page stream at all, fails here rather than on
+    /// the first `next`. An empty stream is accepted.
+    pub fn try_new(pool: SmartBufferPool, source_reader: R) -> io::Result<Self> {
+        let mut decoder = Self::new(pool, source_reader);
+        decoder.load_next_page()?;
+        Ok(decoder)
+    }
+
+    /// Decodes up to `out.len()` values into `out`, crossing page boundaries as needed.
+    /// Returns how many values were written; `0` means the stream is exhausted.
+    pub fn read_values(&mut self, out: &mut [T]) -> io::Result<usize> {
+        let mut written = 0;
+        while written < out.len() {
+            if let Some(ref mut stream) = self.current_stream {
+                let n = stream.read_into(&mut out[written..])?;
+                written += n;
+                if n > 0 {
+                    continue;
+                }
+                self.current_stream = None;
+            }
+            if !self.load_next_page()? {
+                break;
+            }
+        }
+        Ok(written)
+    }
+
+    /// Reads the next page into `current_stream`. Returns `false` on a clean EOF, at the
+    /// stream footer, or once the end of the range given to `from_range` is reached.
+    fn load_next_page(&mut self) -> io::Result<bool> {
+        if self.end.is_some_and(|end| self.pos >= end) {
+            return Ok(false);
+        }
+        let header = match PageHeader::<T>::read_next(&mut self.source_reader) {
+            Ok(Some(header)) => header,
+            Ok(None) => return Ok(false),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        self.pos += PAGE_HEADER_SIZE as u64 + header.data_bytes;
+
+        let mut buffer = self.pool.get(header.data_bytes as usize);
+        buffer.resize_uninit(header.data_bytes as usize);
+        self.source_reader.read_exact(buffer.as_mut_slice())?;
+
+        let cursor = io::Cursor::new(buffer);
+        self.current_stream = Some(BitStream::with_count(
+            cursor,
+            header.bit_width,
+            header.count,
+        ));
+        Ok(true)
+    }
+}
+
+impl<R: Read + Seek, T: BitEncodable> PageDecoder<R, T> {
+    /// Reads the footer written by `BitpackStreamWriter::with_footer`, or `None` for streams
+    /// without one. As with `from_range`, the stream must span the whole reader. The
+    /// decoding position is left unchanged.
+    pub fn footer(&mut self) -> io::Result<Option<StreamFooter>> {
+        StreamFooter::read_from_end(&mut self.source_reader)
+    }
+
+    /// Total number of values in the stream, taken from its footer without decoding any
+    /// page. `None` for streams written without a footer.
+    pub fn len(&mut self) -> io::Result<Option<u64>> {
+        Ok(self.footer()?.map(|footer| footer.total_values))
+    }
+
+    /// Continues decoding from the first value of page `page`, located through the footer's
+    /// page index. Fails with `Unsupported` when the stream has no page index.
+    pub fn seek_to_page(&mut self, page: usize) -> io::Result<()> {
+        let offsets = self
+            .footer()?
+            .and_then(|footer| footer.page_offsets)
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::Unsupported, "stream has no page index")
+            })?;
+        let offset = *offsets.get(page).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "page {page} is past the {} pages of the stream",
+                    offsets.len()
+                ),
+            )
+        })?;
+        self.source_reader.seek(SeekFrom::Start(offset))?;
+        self.current_stream = None;
+        self.pos = offset;
+        Ok(())
+    }
+
+    /// Decodes only the pages whose header starts within `start..end` of the stream.
+    ///
+    /// A page belongs to the range holding its first byte and is always decoded whole, even
+    /// when its data runs past `end`. Splitting a stream at any byte offsets therefore gives
+    /// every page to exactly one range. Pages before `start` are found by walking their
+    /// headers from the beginning of the stream and seeking over their data.
+    pub fn from_range(
+        pool: SmartBufferPool,
+        mut source_reader: R,
+        start: u64,
+        end: u64,
+    ) -> io::Result<Self> {
+        source_reader.seek(SeekFrom::Start(0))?;
+        let mut pos = 0;
+        while pos < start {
+            let header = match PageHeader::<T>::read_next(&mut source_reader) {
+                Ok(Some(header)) => header,
+                Ok(None) => break,
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e),
+            };
+            pos += PAGE_HEADER_SIZE as u64 + header.data_bytes;
+            source_reader.seek(SeekFrom::Start(pos))?;
+        }
+
+        let mut decoder = Self::new(pool, source_reader);
+        decoder.pos = pos;
+        decoder.end = Some(end);
+        Ok(decoder)
+    }
+}
+
+impl<R: Read + Seek, T: BitEncodable> PageDecoder<R, T> {
+    /// Decodes the stream backwards: pages from last to first, and each page's values from
+    /// last to first. `page_offsets` is the stream offset of every page header in stream
+    /// order, as kept by the footer of `BitpackStreamWriter::with_footer(true)`.
+    pub fn reverse(
+        pool: SmartBufferPool,
+        source_reader: R,
+        page_offsets: Vec<u64>,
+    ) -> ReversePageDecoder<R, T> {
+        ReversePageDecoder {
+            pool,
+            source_reader,
+            page_offsets,
+            values: Vec::new(),
+        }
+    }
+}
+
+/// Backward decoder returned by `PageDecoder::reverse`. Holds one decoded page at a time.
+pub struct ReversePageDecoder<R: Read + Seek, T: BitEncodable> {
+    pool: SmartBufferPool,
+    source_reader: R,
+    /// Offsets of the pages not yet decoded; the next page is the last one.
+    page_offsets: Vec<u64>,
+    /// Values of the current page still to be returned, popped from the back.
+    values: Vec<T>,
+}
+
+impl<R: Read + Seek, T: BitEncodable> ReversePageDecoder<R, T> {
+    fn load_page(&mut self, offset: u64) -> io::Result<()> {
+        self.source_reader.seek(SeekFrom::Start(offset))?;
+        let header = PageHeader::<T>::read_from(&mut self.source_reader)?;
+        let mut buffer = self.pool.get(header.data_bytes as usize);
+        buffer.resize_uninit(header.data_bytes as usize);
+        self.source_reader.read_exact(buffer.as_mut_slice())?;
+        self.values = BitStream::with_count(Cursor::new(buffer), header.bit_width, header.count)
+            .collect::<io::Result<_>>()?;
+        Ok(())
+    }
+}
+
+impl<R: Read + Seek, T: BitEncodable> Iterator for ReversePageDecoder<R, T> {
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(v) = self.values.pop() {
+                return Some(Ok(v));
+            }
+            let offset = self.page_offsets.pop()?;
+            if let Err(e) = self.load_page(offset) {
+                // nothing sensible follows a page that cannot be read.
+                self.page_offsets.clear();
+                return Some(Err(e));
+            }
         }
     }
 }
@@ -107,33 +473,25 @@ impl<R: Read, T: BitEncodable> Iterator for PageDecoder<R, T> {
                 }
             }
 
-            match PageHeader::<T>::read_from(&mut self.source_reader) {
-                Ok(header) => {
-                    let mut buffer = self.pool.get(header.data_bytes as usize);
-                    buffer.resize_uninit(header.data_bytes as usize);
-
-                    if let Err(e) = self.source_reader.read_exact(buffer.as_mut_slice()) {
-                        return Some(Err(e));
-                    }
-                    let cursor = io::Cursor::new(buffer);
-                    let bit_stream = BitStream::with_count(cursor, header.bit_width, header.count);
-                    self.current_stream = Some(bit_stream);
-                    continue;
-                }
-                Err(e) => {
-                    if e.kind() == io::ErrorKind::UnexpectedEof {
-                        return None;
-                    } else {
-                        return Some(Err(e));
-                    }
-                }
+            match self.load_next_page() {
+                Ok(true) => continue,
+                Ok(false) => return None,
+                Err(e) => return Some(Err(e)),
             }
         }
     }
 }
 
+/// Where a `PooledPageDecoder` stands in its stream, from `checkpoint`: the next value is
+/// value `index` of the page whose header starts at `page_offset`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecoderState {
+    pub page_offset: u64,
+    pub index: usize,
+}
+
 /// An iterator that decodes values from a stream of bit-packed pages,
-/// using a BufferPool and supporting predicate-based page skipping.
+/// using a SmartBufferPool and supporting predicate-based page skipping.
 pub struct PooledPageDecoder<R, T, F>
 where
     R: Read,
@@ -144,6 +502,12 @@ where
     source_reader: R,
     current_stream: Option<BitStream<Cursor<SmartPage>, T>>,
     predicate: F,
+    /// Stream offset of the next page header.
+    pos: u64,
+    /// Stream offset of the header of the page in `current_stream`.
+    page_offset: u64,
+    /// Values already returned from the page in `current_stream`.
+    consumed: usize,
 }
 
 impl<R, T, F> PooledPageDecoder<R, T, F>
@@ -162,7 +526,121 @@ where
             source_reader: reader,
             current_stream: None,
             predicate,
+            pos: 0,
+            page_offset: 0,
+            consumed: 0,
+        }
+    }
+
+    /// The position of the next value, to continue the scan later with `resume`. Offsets
+    /// count from where the reader was when the decoder was created.
+    pub fn checkpoint(&self) -> DecoderState {
+        match self.current_stream {
+            Some(_) => DecoderState {
+                page_offset: self.page_offset,
+                index: self.consumed,
+            },
+            None => DecoderState {
+                page_offset: self.pos,
+                index: 0,
+            },
+        }
+    }
+
+    /// Decodes up to `out.len()` values from the pages accepted by the predicate.
+    /// Returns how many values were written; `0` means the stream is exhausted.
+    pub fn read_values(&mut self, out: &mut [T]) -> io::Result<usize> {
+        let mut written = 0;
+        while written < out.len() {
+            if let Some(ref mut stream) = self.current_stream {
+                let n = stream.read_into(&mut out[written..])?;
+                written += n;
+                self.consumed += n;
+                if n > 0 {
+                    continue;
+                }
+                self.current_stream = None;
+            }
+            if !self.load_next_page()? {
+                break;
+            }
+        }
+        Ok(written)
+    }
+
+    /// Reads pages until one passes the predicate and loads it into `current_stream`.
+    /// Returns `false` on a clean EOF or at the stream footer.
+    fn load_next_page(&mut self) -> io::Result<bool> {
+        loop {
+            let header = match PageHeader::<T>::read_next(&mut self.source_reader) {
+                Ok(Some(header)) => header,
+                Ok(None) => return Ok(false), // Stream footer.
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(false), // Clean EOF.
+                Err(e) => return Err(e), // Fatal error.
+            };
+            let header_offset = self.pos;
+            self.pos += PAGE_HEADER_SIZE as u64 + header.data_bytes;
+
+            if (self.predicate)(&header) {
+                // KEEP THE PAGE: Load its data into a buffer and decode.
+                let mut buffer = self.pool.get(header.data_bytes as usize);
+                buffer.resize_uninit(header.data_bytes as usize);
+                self.source_reader.read_exact(buffer.as_mut_slice())?;
+
+                let cursor = Cursor::new(buffer);
+                let stream = BitStream::with_count(cursor, header.bit_width, header.count);
+                self.current_stream = Some(stream);
+                self.page_offset = header_offset;
+                self.consumed = 0;
+                return Ok(true);
+            }
+
+            // SKIP THE PAGE: Consume and discard its data section without buffering.
+            let mut limited_reader = self.source_reader.by_ref().take(header.data_bytes);
+            io::copy(&mut limited_reader, &mut io::sink())?;
+        }
+    }
+}
+
+impl<R, T, F> PooledPageDecoder<R, T, F>
+where
+    R: Read + Seek,
+    T: BitEncodable,
+    F: FnMut(&PageHeader<T>) -> bool,
+{
+    /// Like `resume`, filtering the pages after the resumed one with `predicate`. The page
+    /// at `state` is decoded as long as the checkpoint was taken inside it.
+    pub fn resume_with_predicate(
+        pool: SmartBufferPool,
+        mut reader: R,
+        state: DecoderState,
+        predicate: F,
+    ) -> io::Result<Self> {
+        reader.seek(SeekFrom::Start(state.page_offset))?;
+        let mut decoder = Self::with_predicate(pool, reader, predicate);
+        decoder.pos = state.page_offset;
+        if state.index == 0 {
+            return Ok(decoder);
+        }
+
+        let mut skipped = 0;
+        if decoder.load_next_page()? && decoder.page_offset == state.page_offset {
+            let stream = decoder.current_stream.as_mut().unwrap();
+            while skipped < state.index && stream.next().transpose()?.is_some() {
+                skipped += 1;
+            }
         }
+        if skipped < state.index {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "no value {} in a page at offset {}",
+                    state.index, state.page_offset
+                ),
+            ));
+        }
+        decoder.consumed = skipped;
+        Ok(decoder)
     }
 }
 
@@ -178,6 +656,18 @@ where
     }
 }
 
+impl<R, T> PooledPageDecoder<R, T, fn(&PageHeader<T>) -> bool>
+where
+    R: Read + Seek,
+    T: BitEncodable,
+{
+    /// Continues a scan from a `checkpoint` of another decoder over the same stream: seeks
+    /// `reader` to the saved page and skips the values already returned from it.
+    pub fn resume(pool: SmartBufferPool, reader: R, state: DecoderState) -> io::Result<Self> {
+        Self::resume_with_predicate(pool, reader, state, |_| true)
+    }
+}
+
 impl<R, T, F> Iterator for PooledPageDecoder<R, T, F>
 where
     R: Read,
@@ -191,42 +681,20 @@ where
             // If we have an active page stream, get the next value from it.
             if let Some(ref mut stream) = self.current_stream {
                 match stream.next() {
-                    Some(item) => return Some(item),
+                    Some(item) => {
+                        self.consumed += 1;
+                        return Some(item);
+                    }
                     None => self.current_stream = None, // Page is exhausted.
                 }
             }
 
-            // We need to load a new page.
-            match PageHeader::<T>::read_from(&mut self.source_reader) {
-                Ok(header) => {
-                    // *** PREDICATE LOGIC IS HERE ***
-                    if (self.predicate)(&header) {
-                        // KEEP THE PAGE: Load its data into a buffer and decode.
-                        let mut buffer = self.pool.get(header.data_bytes as usize);
-                        buffer.resize_uninit(header.data_bytes as usize);
-                        if let Err(e) = self.source_reader.read_exact(buffer.as_mut_slice()) {
-                            return Some(Err(e));
-                        }
-
-                        let cursor = Cursor::new(buffer);
-                        let stream = BitStream::with_count(cursor, header.bit_width, header.count);
-                        self.current_stream = Some(stream);
-
-                        // Loop again to pull the first value from the new stream.
-                        continue;
-                    } else {
-                        // SKIP THE PAGE: Consume and discard its data section without buffering.
-                        let mut limited_reader =
-                            self.source_reader.by_ref().take(header.data_bytes);
-                        if let Err(e) = io::copy(&mut limited_reader, &mut io::sink()) {
-                            return Some(Err(e));
-                        }
-                        // Loop again to find the next valid page header.
-                        continue;
-                    }
-                }
-                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return None, // Clean EOF.
-                Err(e) => return Some(Err(e)),                                     // Fatal error.
+            // We need to load a new page that passes the predicate.
+            match self.load_next_page() {
+                // Loop again to pull the first value from the new stream.
+                Ok(true) => continue,
+                Ok(false) => return None,
+                Err(e) => return Some(Err(e)),
             }
         }
     }
@@ -236,7 +704,10 @@ where
 mod tests {
     use crate::buffers::smart_pool::SmartBufferPool;
     use crate::buffers::smart_pool::SmartPage;
-    use crate::encoding::bitpack::v1::page_reader::{PageHeader, PooledPageDecoder};
+    use crate::encoding::bitpack::v1::common::PAGE_VERSION;
+    use crate::encoding::bitpack::v1::page_reader::{
+        DecoderState, PageDecoder, PageHeader, PooledPageDecoder, decode_to_mmap, stream_min_max,
+    };
     use crate::encoding::bitpack::v1::page_writer::PageEncoder;
     use std::io::{self, Cursor};
 
@@ -327,6 +798,225 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_from_range_splits_cover_stream() -> io::Result<()> {
+        let pool = SmartBufferPool::new(1 << 20);
+        let values: Vec<u32> = (0..1_000).collect();
+        let mut bytes = Vec::new();
+        let mut page_starts = Vec::new();
+        for page in PageEncoder::new(pool.clone(), values.iter().copied(), 10, 128) {
+            page_starts.push(bytes.len() as u64);
+            bytes.extend_from_slice(page?.as_slice());
+        }
+        let len = bytes.len() as u64;
+        assert!(page_starts.len() > 4);
+
+        let decode = |start, end| {
+            PageDecoder::<_, u32>::from_range(pool.clone(), Cursor::new(&bytes), start, end)?
+                .collect::<io::Result<Vec<_>>>()
+        };
+
+        // page-aligned split
+        let mid = page_starts[page_starts.len() / 2];
+        let mut union = decode(0, mid)?;
+        union.extend(decode(mid, len)?);
+        assert_eq!(union, values);
+
+        // a split inside a page hands that page to the first range
+        let inside = page_starts[2] + 10;
+        let first = decode(0, inside)?;
+        let second = decode(inside, len)?;
+        assert_eq!(first.len(), 3 * 51);
+        assert_eq!([first, second].concat(), values);
+
+        assert!(decode(len, len + 10)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_header_type_width_mismatch() -> io::Result<()> {
+        let pool = SmartBufferPool::new(1 << 20);
+        let encoder = PageEncoder::new(pool.clone(), vec![1u64, 2, 3].into_iter(), 2, 1024);
+        let mut bytes = Vec::new();
+        for page in encoder {
+            bytes.extend_from_slice(page?.as_slice());
+        }
+
+        let err = match PageHeader::<u32>::read_from(&mut Cursor::new(bytes)) {
+            Ok(_) => panic!("expected a type width mismatch"),
+            Err(e) => e,
+        };
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            "type width mismatch: page stores 8-byte values, decoder expects 4-byte values"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_new_rejects_wrong_element_type() -> io::Result<()> {
+        let pool = SmartBufferPool::new(1 << 20);
+        let values: Vec<u32> = (0..100).collect();
+        let mut bytes = Vec::new();
+        for page in PageEncoder::new(pool.clone(), values.iter().copied(), 7, 1024) {
+            bytes.extend_from_slice(page?.as_slice());
+        }
+
+        let err = match PageDecoder::<_, u16>::try_new(pool.clone(), Cursor::new(&bytes)) {
+            Ok(_) => panic!("expected a type width mismatch"),
+            Err(e) => e,
+        };
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            "type width mismatch: page stores 4-byte values, decoder expects 2-byte values"
+        );
+
+        // the matching type decodes everything, the eagerly read page included.
+        let decoded = PageDecoder::<_, u32>::try_new(pool.clone(), Cursor::new(&bytes))?
+            .collect::<io::Result<Vec<_>>>()?;
+        assert_eq!(decoded, values);
+        assert!(
+            PageDecoder::<_, u16>::try_new(pool, Cursor::new(Vec::new()))?
+                .next()
+                .is_none()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_header_version_and_flags() -> io::Result<()> {
+        let pool = SmartBufferPool::new(1 << 20);
+        let page = |encoder: PageEncoder<_, u32>| -> io::Result<Vec<u8>> {
+            let mut bytes = Vec::new();
+            for page in encoder {
+                bytes.extend_from_slice(page?.as_slice());
+            }
+            Ok(bytes)
+        };
+        let values = || vec![1u32, 2, 3].into_iter();
+
+        let bytes = page(PageEncoder::new(pool.clone(), values(), 2, 1024))?;
+        let header = PageHeader::<u32>::read_from(&mut Cursor::new(&bytes))?;
+        assert_eq!((header.version, header.flags), (PAGE_VERSION, 0));
+
+        // a flag from a newer writer is refused rather than decoded as plain bitpacked data.
+        let bytes = page(PageEncoder::new(pool.clone(), values(), 2, 1024).with_flags(0b100))?;
+        let err = PageHeader::<u32>::read_from(&mut Cursor::new(&bytes))
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            "page uses unsupported feature flags 0b00000100 (this reader knows 0b00000000)"
+        );
+        let mut decoder = PageDecoder::<_, u32>::new(pool.clone(), Cursor::new(bytes));
+        assert!(decoder.next().unwrap().is_err());
+
+        let bytes = page(PageEncoder::new(pool, values(), 2, 1024).with_version(PAGE_VERSION + 1))?;
+        let err = PageHeader::<u32>::read_from(&mut Cursor::new(&bytes))
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "unsupported page version {}, expected 1..=1",
+                PAGE_VERSION + 1
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_header_count_exceeding_data_is_rejected() -> io::Result<()> {
+        let pool = SmartBufferPool::new(1 << 20);
+        let mut bytes = Vec::new();
+        for page in PageEncoder::new(pool.clone(), 0..100u32, 7, 1024) {
+            bytes.extend_from_slice(page?.as_slice());
+        }
+        // 100 values at 7 bits take 88 bytes, which hold at most 100 values.
+        assert!(PageHeader::<u32>::read_from(&mut Cursor::new(&bytes)).is_ok());
+
+        for count in [101, u64::MAX] {
+            bytes[9..17].copy_from_slice(&count.to_le_bytes());
+            let err = PageHeader::<u32>::read_from(&mut Cursor::new(&bytes))
+                .err()
+                .unwrap();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+            assert_eq!(
+                err.to_string(),
+                format!("page count {count} exceeds the 100 values 88 data bytes hold at 7 bits")
+            );
+            let mut decoder = PageDecoder::<_, u32>::new(pool.clone(), Cursor::new(&bytes));
+            assert_eq!(
+                decoder.next().unwrap().unwrap_err().kind(),
+                io::ErrorKind::InvalidData
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_values_in_chunks() -> io::Result<()> {
+        let pool = SmartBufferPool::new(1 << 20);
+        let source: Vec<u32> = (0..5_000u32).map(|v| v * 3).collect();
+        let encoder = PageEncoder::new(pool.clone(), source.clone().into_iter(), 14, 1024);
+        let mut bytes = Vec::new();
+        let mut pages = 0;
+        for page in encoder {
+            bytes.extend_from_slice(page?.as_slice());
+            pages += 1;
+        }
+        assert!(pages > 1, "expected a multi-page stream");
+
+        let mut decoder = PageDecoder::<_, u32>::new(pool.clone(), Cursor::new(bytes));
+        let mut chunk = [0u32; 256];
+        let mut decoded = Vec::new();
+        loop {
+            let n = decoder.read_values(&mut chunk)?;
+            if n == 0 {
+                break;
+            }
+            decoded.extend_from_slice(&chunk[..n]);
+        }
+
+        assert_eq!(decoded, source);
+        assert_eq!(decoder.read_values(&mut chunk)?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pooled_read_values_skips_pages() -> io::Result<()> {
+        let pool = SmartBufferPool::new(1 << 20);
+        let source: Vec<u32> = (0..2_000u32).collect();
+        let encoder = PageEncoder::new(pool.clone(), source.clone().into_iter(), 11, 512);
+        let mut bytes = Vec::new();
+        for page in encoder {
+            bytes.extend_from_slice(page?.as_slice());
+        }
+
+        let mut decoder = PooledPageDecoder::with_predicate(
+            pool.clone(),
+            Cursor::new(bytes),
+            |header: &PageHeader<u32>| header.min >= 1_000,
+        );
+        let mut chunk = [0u32; 256];
+        let mut decoded = Vec::new();
+        loop {
+            let n = decoder.read_values(&mut chunk)?;
+            if n == 0 {
+                break;
+            }
+            decoded.extend_from_slice(&chunk[..n]);
+        }
+
+        assert!(!decoded.is_empty());
+        assert!(decoded.iter().all(|v| *v >= 1_000));
+        assert_eq!(decoded.last(), source.last());
+        Ok(())
+    }
+
     #[test]
     fn test_empty_input_roundtrip() -> io::Result<()> {
         let pool = SmartBufferPool::new(2 << 20);
@@ -355,4 +1045,133 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_decode_to_mmap() {
+        let pool = SmartBufferPool::new(1 << 20);
+        let values: Vec<u32> = (0..100_000u32)
+            .map(|i| i.wrapping_mul(2_654_435_761) >> 12)
+            .collect();
+        let mut encoded = Vec::new();
+        for page in PageEncoder::new(pool.clone(), values.iter().copied(), 20, 4096) {
+            encoded.extend_from_slice(page.unwrap().as_slice());
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("values.u32");
+        decode_to_mmap::<u32, _, _>(encoded.as_slice(), &path, values.len(), pool.clone()).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(bytes.len(), values.len() * 4);
+        let decoded: Vec<u32> = bytes
+            .chunks_exact(4)
+            .map(|c| u32::from_ne_bytes(c.try_into().unwrap()))
+            .collect();
+        assert_eq!(decoded, values);
+
+        let err = decode_to_mmap::<u32, _, _>(encoded.as_slice(), &path, values.len() + 1, pool)
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_reverse_decode_matches_forward() -> io::Result<()> {
+        let pool = SmartBufferPool::new(1 << 20);
+        let values: Vec<u32> = (0..1_500u32).map(|v| v * 11 % 2048).collect();
+        let mut bytes = Vec::new();
+        let mut offsets = Vec::new();
+        for page in PageEncoder::new(pool.clone(), values.iter().copied(), 11, 256) {
+            offsets.push(bytes.len() as u64);
+            bytes.extend_from_slice(page?.as_slice());
+        }
+        assert!(offsets.len() > 2);
+
+        let forward: Vec<u32> = PageDecoder::<_, u32>::new(pool.clone(), Cursor::new(&bytes))
+            .collect::<io::Result<_>>()?;
+        let reversed: Vec<u32> = PageDecoder::<_, u32>::reverse(pool, Cursor::new(&bytes), offsets)
+            .collect::<io::Result<_>>()?;
+        assert_eq!(forward, values);
+        assert_eq!(reversed, forward.into_iter().rev().collect::<Vec<_>>());
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_min_max_from_headers() -> io::Result<()> {
+        let pool = SmartBufferPool::new(1 << 20);
+        let values: Vec<u32> = (0..2000u32).map(|i| 500 + (i * 37) % 1500).collect();
+        let mut bytes = Vec::new();
+        let mut pages = 0;
+        for page in PageEncoder::new(pool.clone(), values.iter().copied(), 11, 256) {
+            bytes.extend_from_slice(page?.as_slice());
+            pages += 1;
+        }
+        assert!(pages > 2);
+
+        let extremes = stream_min_max::<u32, _>(Cursor::new(&bytes))?;
+        let expected = (*values.iter().min().unwrap(), *values.iter().max().unwrap());
+        assert_eq!(extremes, Some(expected));
+
+        assert_eq!(stream_min_max::<u32, _>(Cursor::new(Vec::new()))?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pooled_checkpoint_resume() -> io::Result<()> {
+        let pool = SmartBufferPool::new(1 << 20);
+        let values: Vec<u32> = (0..3_000u32).map(|v| v * 7 % 2048).collect();
+        let mut bytes = Vec::new();
+        for page in PageEncoder::new(pool.clone(), values.iter().copied(), 11, 256) {
+            bytes.extend_from_slice(page?.as_slice());
+        }
+
+        // scan a bit past halfway, mixing both ways of reading.
+        let mut decoder = PooledPageDecoder::<_, u32, _>::new(pool.clone(), Cursor::new(&bytes));
+        let mut head = vec![0u32; 1_200];
+        assert_eq!(decoder.read_values(&mut head)?, head.len());
+        for _ in 0..301 {
+            head.push(decoder.next().unwrap()?);
+        }
+        let state = decoder.checkpoint();
+        assert!(state.page_offset > 0 && state.index > 0, "{state:?}");
+
+        let resumed =
+            PooledPageDecoder::<_, u32, _>::resume(pool.clone(), Cursor::new(&bytes), state)?;
+        let rest: Vec<u32> = resumed.collect::<io::Result<_>>()?;
+        assert_eq!(head, values[..head.len()]);
+        assert_eq!(rest, values[head.len()..]);
+        assert_eq!(rest, decoder.collect::<io::Result<Vec<_>>>()?);
+
+        // a fresh decoder and an exhausted one checkpoint at a page boundary.
+        let fresh = PooledPageDecoder::<_, u32, _>::new(pool.clone(), Cursor::new(&bytes));
+        assert_eq!(
+            fresh.checkpoint(),
+            DecoderState {
+                page_offset: 0,
+                index: 0
+            }
+        );
+        let mut done = PooledPageDecoder::<_, u32, _>::new(pool.clone(), Cursor::new(&bytes));
+        done.by_ref().for_each(drop);
+        let end = done.checkpoint();
+        assert_eq!(
+            end,
+            DecoderState {
+                page_offset: bytes.len() as u64,
+                index: 0
+            }
+        );
+        let resumed =
+            PooledPageDecoder::<_, u32, _>::resume(pool.clone(), Cursor::new(&bytes), end)?;
+        assert_eq!(resumed.count(), 0);
+
+        let past_page = DecoderState {
+            page_offset: 0,
+            index: 10_000,
+        };
+        let err = PooledPageDecoder::<_, u32, _>::resume(pool, Cursor::new(&bytes), past_page)
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        Ok(())
+    }
 }
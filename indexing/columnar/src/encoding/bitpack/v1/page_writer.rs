@@ -1,7 +1,8 @@
 use crate::buffers::smart_pool::{SmartBufferPool, SmartPage};
 use crate::encoding::bitpack::v1::common::BitEncodable;
 use crate::encoding::bitpack::v1::common::{
-    PAGE_DEFAULT_SIZE, PAGE_HEADER_SIZE, PAGE_MAGIC_BITPACK, PAGE_VERSION,
+    PAGE_DEFAULT_SIZE, PAGE_FLAGS_OFFSET, PAGE_HEADER_SIZE, PAGE_MAGIC_BITPACK, PAGE_VERSION,
+    bit_width_from_value, clamp_width_to_type,
 };
 use crate::encoding::bitpack::v1::writer::BitWriterRef;
 use std::io;
@@ -17,6 +18,8 @@ where
     width: u8,
     values_per_page: usize,
     page_size: usize,
+    version: u8,
+    flags: u8,
 }
 
 impl<I, T> PageEncoder<I, T>
@@ -25,19 +28,116 @@ where
     T: BitEncodable,
 {
     pub fn new(pool: SmartBufferPool, input: I, width: u8, page_size: usize) -> Self {
-        let values_per_page = if width > 0 {
-            page_size.saturating_sub(PAGE_HEADER_SIZE) * 8 / (width as usize)
-        } else {
-            PAGE_DEFAULT_SIZE
-        };
         Self {
             pool,
             input: input.peekable(),
             width,
-            values_per_page,
+            values_per_page: values_per_page(width, page_size),
             page_size,
+            version: PAGE_VERSION,
+            flags: 0,
         }
     }
+
+    /// Packs exactly `n` values into every page but the last, whatever the width, so
+    /// row `r` always lives in page `r / n`.
+    pub fn with_values_per_page(pool: SmartBufferPool, input: I, width: u8, n: usize) -> Self {
+        let n = n.max(1);
+        Self {
+            pool,
+            input: input.peekable(),
+            width,
+            values_per_page: n,
+            page_size: PAGE_HEADER_SIZE + (n * width as usize).div_ceil(8),
+            version: PAGE_VERSION,
+            flags: 0,
+        }
+    }
+
+    /// Caps every page at `max` values even when more would fit in the byte budget, so
+    /// narrow widths do not produce pages too large to seek within cheaply.
+    pub fn with_max_values_per_page(mut self, max: usize) -> Self {
+        let max = max.max(1);
+        if max < self.values_per_page {
+            self.values_per_page = max;
+            self.page_size = PAGE_HEADER_SIZE + (max * self.width as usize).div_ceil(8);
+        }
+        self
+    }
+
+    /// Format version written into every page header, `PAGE_VERSION` by default. Readers
+    /// reject pages from a version newer than their own.
+    pub fn with_version(mut self, version: u8) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Feature flags written into every page header, none by default. Readers reject
+    /// pages carrying flags outside their `PAGE_KNOWN_FLAGS`.
+    pub fn with_flags(mut self, flags: u8) -> Self {
+        self.flags = flags;
+        self
+    }
+}
+
+/// Number of `width`-bit values `PageEncoder` packs into one page of `page_size` bytes.
+pub fn values_per_page(width: u8, page_size: usize) -> usize {
+    if width > 0 {
+        page_size.saturating_sub(PAGE_HEADER_SIZE) * 8 / (width as usize)
+    } else {
+        PAGE_DEFAULT_SIZE
+    }
+}
+
+/// Bytes `PageEncoder` would produce for `values` at the widest value's bit width, without
+/// encoding anything: one header per page plus the byte-padded packed data.
+pub fn estimate_encoded_size<T: BitEncodable>(values: &[T], page_size: usize) -> usize {
+    if values.is_empty() {
+        return 0;
+    }
+    let width = widest_width(values);
+    let per_page = values_per_page(width, page_size).max(1);
+    let page_bytes = |count: usize| PAGE_HEADER_SIZE + (count * width as usize).div_ceil(8);
+
+    let full_pages = values.len() / per_page;
+    let remainder = values.len() % per_page;
+    let mut total = full_pages * page_bytes(per_page);
+    if remainder > 0 {
+        total += page_bytes(remainder);
+    }
+    total
+}
+
+/// Encodes `values` as `PageEncoder` pages packed at the widest value's bit width and
+/// writes them to `writer`. An empty slice writes nothing.
+pub fn write_pages<T: BitEncodable, W: io::Write + ?Sized>(
+    values: &[T],
+    pool: &SmartBufferPool,
+    writer: &mut W,
+) -> io::Result<()> {
+    if values.is_empty() {
+        return Ok(());
+    }
+    let width = widest_width(values);
+    for page in PageEncoder::new(
+        pool.clone(),
+        values.iter().copied(),
+        width,
+        PAGE_DEFAULT_SIZE,
+    ) {
+        writer.write_all(page?.as_slice())?;
+    }
+    Ok(())
+}
+
+/// Bit width of the widest value in `values`, clamped to `T`.
+fn widest_width<T: BitEncodable>(values: &[T]) -> u8 {
+    let width = values
+        .iter()
+        .map(|&v| bit_width_from_value(v))
+        .max()
+        .unwrap_or(1);
+    clamp_width_to_type::<T>(width)
 }
 
 impl<I, T> Iterator for PageEncoder<I, T>
@@ -83,7 +183,7 @@ where
 
         let mut header = [0u8; PAGE_HEADER_SIZE];
         header[..6].copy_from_slice(PAGE_MAGIC_BITPACK);
-        header[6] = PAGE_VERSION;
+        header[6] = self.version;
         header[7] = (T::BITS / 8) as u8;
         header[8] = self.width;
         header[9..17].copy_from_slice(&(count as u64).to_le_bytes());
@@ -102,8 +202,97 @@ where
         let data_bytes = (buffer.len() - PAGE_HEADER_SIZE) as u64;
         header[start..end].copy_from_slice(&data_bytes.to_le_bytes());
 
+        header[PAGE_FLAGS_OFFSET] = self.flags;
+
         // page.buffer.as_mut_slice()[..PAGE_DEFAULT_SIZE].copy_from_slice(&header);
         buffer.as_mut_slice()[..PAGE_HEADER_SIZE].copy_from_slice(&header);
         Some(Ok(buffer))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::bitpack::v1::page_reader::PageDecoder;
+
+    fn encoded_len<T: BitEncodable>(values: &[T], page_size: usize) -> usize {
+        let width = values
+            .iter()
+            .map(|&v| bit_width_from_value(v))
+            .max()
+            .unwrap_or(1);
+        let width = clamp_width_to_type::<T>(width);
+        let pool = SmartBufferPool::new(1 << 20);
+        PageEncoder::new(pool, values.iter().copied(), width, page_size)
+            .map(|page| page.unwrap().len())
+            .sum()
+    }
+
+    #[test]
+    fn test_fixed_values_per_page() {
+        let pool = SmartBufferPool::new(1 << 20);
+        let values: Vec<u16> = (0..250).collect();
+        let mut encoded = Vec::new();
+        let mut counts = Vec::new();
+        for page in PageEncoder::with_values_per_page(pool.clone(), values.iter().copied(), 9, 100)
+        {
+            let page = page.unwrap();
+            counts.push(u64::from_le_bytes(
+                page.as_slice()[9..17].try_into().unwrap(),
+            ));
+            encoded.extend_from_slice(page.as_slice());
+        }
+        assert_eq!(counts, [100, 100, 50]);
+
+        let decoded: Vec<u16> = PageDecoder::<_, u16>::new(pool, std::io::Cursor::new(encoded))
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(decoded, values);
+    }
+
+    #[test]
+    fn test_max_values_per_page() {
+        let pool = SmartBufferPool::new(1 << 20);
+        let values: Vec<u8> = (0..5000u32).map(|v| (v % 4) as u8).collect();
+        // at 2 bits a default page would take every value at once.
+        assert!(values_per_page(2, PAGE_DEFAULT_SIZE) > values.len());
+
+        let mut encoded = Vec::new();
+        let mut counts = Vec::new();
+        let encoder = PageEncoder::new(pool.clone(), values.iter().copied(), 2, PAGE_DEFAULT_SIZE)
+            .with_max_values_per_page(1000);
+        for page in encoder {
+            let page = page.unwrap();
+            counts.push(u64::from_le_bytes(
+                page.as_slice()[9..17].try_into().unwrap(),
+            ));
+            encoded.extend_from_slice(page.as_slice());
+        }
+        assert_eq!(counts, [1000; 5]);
+
+        let decoded: Vec<u8> = PageDecoder::<_, u8>::new(pool, std::io::Cursor::new(encoded))
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(decoded, values);
+    }
+
+    #[test]
+    fn test_estimate_matches_encoded_size() {
+        let values: Vec<u32> = (0..100_000u32).map(|v| v * 7 % 100_003).collect();
+        for page_size in [256, 4096, PAGE_DEFAULT_SIZE] {
+            for len in [0, 1, 17, 5_000, values.len()] {
+                let slice = &values[..len];
+                let estimate = estimate_encoded_size(slice, page_size);
+                let actual = encoded_len(slice, page_size);
+                assert!(
+                    estimate.abs_diff(actual) <= 8,
+                    "page size {page_size}, {len} values: estimate {estimate}, actual {actual}"
+                );
+            }
+        }
+
+        let signed: Vec<i16> = (-3000..3000).collect();
+        let estimate = estimate_encoded_size(&signed, 1024);
+        assert!(estimate.abs_diff(encoded_len(&signed, 1024)) <= 8);
+    }
+}
@@ -0,0 +1,83 @@
+//! Page predicates for `PooledPageDecoder::with_predicate`.
+//!
+//! Each combinator looks only at a page's `min`/`max` and keeps the page when it may hold a
+//! matching value, so values inside kept pages still need filtering by the caller.
+
+use crate::encoding::bitpack::v1::{common::BitEncodable, page_reader::PageHeader};
+
+/// Keeps pages that may hold a value in `lo..=hi`.
+pub fn range<T: BitEncodable>(lo: T, hi: T) -> impl FnMut(&PageHeader<T>) -> bool {
+    move |header| header.max >= lo && header.min <= hi
+}
+
+/// Keeps pages that may hold a value greater than `x`.
+pub fn greater_than<T: BitEncodable>(x: T) -> impl FnMut(&PageHeader<T>) -> bool {
+    move |header| header.max > x
+}
+
+/// Keeps pages that may hold `x`.
+pub fn equals<T: BitEncodable>(x: T) -> impl FnMut(&PageHeader<T>) -> bool {
+    range(x, x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffers::smart_pool::SmartBufferPool;
+    use crate::encoding::bitpack::v1::page_reader::PooledPageDecoder;
+    use crate::encoding::bitpack::v1::page_writer::PageEncoder;
+    use std::io::{self, Cursor};
+
+    // 128-byte pages at 10 bits hold 51 values each.
+    const PER_PAGE: u32 = 51;
+
+    /// Three pages holding 100..151, 900..951 and 400..451.
+    fn stream(pool: &SmartBufferPool) -> Vec<u8> {
+        let values = [100u32, 900, 400]
+            .into_iter()
+            .flat_map(|start| start..start + PER_PAGE);
+        let mut bytes = Vec::new();
+        for page in PageEncoder::new(pool.clone(), values, 10, 128) {
+            bytes.extend_from_slice(page.unwrap().as_slice());
+        }
+        bytes
+    }
+
+    fn decode<F>(predicate: F) -> io::Result<Vec<u32>>
+    where
+        F: FnMut(&PageHeader<u32>) -> bool,
+    {
+        let pool = SmartBufferPool::new(1 << 20);
+        let bytes = stream(&pool);
+        PooledPageDecoder::with_predicate(pool, Cursor::new(bytes), predicate).collect()
+    }
+
+    #[test]
+    fn test_range_keeps_overlapping_pages() -> io::Result<()> {
+        let decoded = decode(range(140, 420))?;
+        let expected: Vec<u32> = (100..100 + PER_PAGE).chain(400..400 + PER_PAGE).collect();
+        assert_eq!(decoded, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_greater_than_keeps_pages_above() -> io::Result<()> {
+        assert_eq!(
+            decode(greater_than(500))?,
+            (900..900 + PER_PAGE).collect::<Vec<_>>()
+        );
+        // a bound equal to a page max excludes that page.
+        assert_eq!(decode(greater_than(950))?, Vec::<u32>::new());
+        Ok(())
+    }
+
+    #[test]
+    fn test_equals_keeps_containing_page() -> io::Result<()> {
+        assert_eq!(
+            decode(equals(425))?,
+            (400..400 + PER_PAGE).collect::<Vec<_>>()
+        );
+        assert!(decode(equals(600))?.is_empty());
+        Ok(())
+    }
+}
@@ -1,8 +1,14 @@
 use crate::encoding::bitpack::v1::common::BitEncodable;
+use crate::encoding::bitpack::v1::unpack::unpack;
 use std::io::{self, Read};
 
 const BUF_SIZE: usize = 512;
 
+/// Most values a width-0 buffer may claim. Such a buffer is only its count, so nothing else
+/// bounds what a corrupt prefix makes decoders allocate; `encode_values` stores longer
+/// all-zero runs at width 1 instead.
+pub const MAX_ZERO_WIDTH_COUNT: usize = 1 << 20;
+
 /// Reads bit-packed integers from any `Read`.
 pub struct BitReader<R: Read> {
     reader: R,
@@ -99,6 +105,62 @@ impl<R: Read> BitReader<R> {
     }
 }
 
+/// Reads bit-packed integers straight from a borrowed slice, without the copy through an
+/// intermediate buffer that `BitReader` makes. Uses the same LSB-first bit order.
+pub struct SliceBitReader<'a> {
+    data: &'a [u8],
+    bit_pos: usize,
+}
+
+impl<'a> SliceBitReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, bit_pos: 0 }
+    }
+
+    /// Reads `width` bits from the slice. `width` must be <= 64.
+    #[inline]
+    pub fn read_bits(&mut self, width: u8) -> io::Result<u64> {
+        if width == 0 {
+            return Ok(0);
+        }
+        if width > 64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "bit width > 64",
+            ));
+        }
+        if self.bit_pos + width as usize > self.data.len() * 8 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "not enough bits",
+            ));
+        }
+
+        let byte = self.bit_pos / 8;
+        let shift = (self.bit_pos % 8) as u32;
+        let mut word = [0u8; 8];
+        let available = (self.data.len() - byte).min(8);
+        word[..available].copy_from_slice(&self.data[byte..byte + available]);
+        let mut value = u64::from_le_bytes(word) >> shift;
+        // a value straddling 9 bytes needs the top bits of the ninth.
+        if shift + width as u32 > 64 {
+            value |= (self.data[byte + 8] as u64) << (64 - shift);
+        }
+
+        self.bit_pos += width as usize;
+        Ok(if width == 64 {
+            value
+        } else {
+            value & ((1u64 << width) - 1)
+        })
+    }
+
+    #[inline]
+    pub fn read_value<T: BitEncodable>(&mut self, width: u8) -> io::Result<T> {
+        Ok(T::decode(self.read_bits(width)?))
+    }
+}
+
 /* -------- Iterator wrapper -------- */
 
 /// Iterator over bit-packed values of type `T`.
@@ -130,6 +192,33 @@ impl<R: Read, T: BitEncodable> BitStream<R, T> {
             _marker: std::marker::PhantomData,
         }
     }
+
+    /// Decodes up to `out.len()` values into `out` and returns how many were written.
+    /// Returns `0` once the stream is exhausted.
+    pub fn read_into(&mut self, out: &mut [T]) -> io::Result<usize> {
+        let mut written = 0;
+        for slot in out.iter_mut() {
+            if self.remaining == Some(0) {
+                break;
+            }
+            match self.reader.read_value::<T>(self.width) {
+                Ok(v) => {
+                    *slot = v;
+                    written += 1;
+                    if let Some(ref mut rem) = self.remaining {
+                        *rem -= 1;
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    // clean EOF, same as the iterator
+                    self.remaining = Some(0);
+                    break;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(written)
+    }
 }
 
 impl<R: Read, T: BitEncodable> Iterator for BitStream<R, T> {
@@ -152,10 +241,127 @@ impl<R: Read, T: BitEncodable> Iterator for BitStream<R, T> {
     }
 }
 
+/// Splits a `[count: u32][bit-packed values]` buffer into its count and data.
+///
+/// An empty buffer is what `encode_values` writes for no values and reads as a count of 0;
+/// a buffer of 1 to 3 bytes has a truncated prefix and is `UnexpectedEof`. A count the data
+/// cannot hold at `width` bits is `InvalidData`, caught before anything is sized from it;
+/// at width 0 that is any count above `MAX_ZERO_WIDTH_COUNT`.
+fn split_count(reader: &[u8], width: u8) -> io::Result<(usize, &[u8])> {
+    if reader.is_empty() {
+        return Ok((0, reader));
+    }
+    let Some((prefix, data)) = reader.split_first_chunk::<4>() else {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!(
+                "bitpacked buffer of {} bytes is shorter than its 4-byte count",
+                reader.len()
+            ),
+        ));
+    };
+    let count = u32::from_le_bytes(*prefix) as usize;
+    if width == 0 && count > MAX_ZERO_WIDTH_COUNT {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("count {count} exceeds the {MAX_ZERO_WIDTH_COUNT} values allowed at width 0"),
+        ));
+    }
+    if count as u64 * width as u64 > data.len() as u64 * 8 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "count {count} exceeds the {} values {} data bytes hold at {width} bits",
+                data.len() * 8 / width as usize,
+                data.len()
+            ),
+        ));
+    }
+    Ok((count, data))
+}
+
+/// Whether `width` is a whole number of bytes no wider than `T`, in which case every
+/// packed value is its own little-endian int.
+fn is_byte_aligned<T: BitEncodable>(width: u8) -> bool {
+    width > 0 && width.is_multiple_of(8) && width as u32 <= T::BITS
+}
+
+/// Fills `out` from byte-aligned `data`: full-width unsigned data is copied straight into
+/// `out` and other widths are read a chunk at a time. Returns `false`, leaving `out`
+/// untouched, when the width is not byte aligned or `data` is too short.
+fn decode_byte_aligned<T: BitEncodable>(data: &[u8], width: u8, out: &mut [T]) -> bool {
+    if !is_byte_aligned::<T>(width) {
+        return false;
+    }
+    let bytes = width as usize / 8;
+    let Some(data) = data.get(..out.len() * bytes) else {
+        return false;
+    };
+    if width as u32 == T::BITS && T::copy_from_le_slice(data, out) {
+        return true;
+    }
+    for (slot, chunk) in out.iter_mut().zip(data.chunks_exact(bytes)) {
+        let mut raw = [0u8; 8];
+        raw[..bytes].copy_from_slice(chunk);
+        *slot = T::decode(u64::from_le_bytes(raw));
+    }
+    true
+}
+
+/// Decodes a `[count: u32][bit-packed values]` buffer.
+///
+/// Byte-aligned widths (multiples of 8 bits, up to `T::BITS`) are decoded straight out of
+/// the buffer instead of bit by bit, see `decode_byte_aligned`. Width 0 stores no bits, so
+/// it decodes to `count` zeros. A buffer too short for its count prefix, or for `count`
+/// values at `width`, is an error, see `split_count`.
 pub fn decode_values<T: BitEncodable>(reader: &[u8], width: u8) -> io::Result<Vec<T>> {
-    let count = u32::from_le_bytes(reader[0..4].try_into().unwrap()) as usize;
-    let bit_reader = BitStream::with_count(io::Cursor::new(&reader[4..]), width, count);
-    bit_reader.collect()
+    let mut out = Vec::new();
+    decode_values_into(reader, width, &mut out)?;
+    Ok(out)
+}
+
+/// Like `decode_values`, but replaces the contents of `out` so its allocation can be reused
+/// (e.g. a buffer from `TypedBufferPool`).
+pub fn decode_values_into<T: BitEncodable>(
+    reader: &[u8],
+    width: u8,
+    out: &mut Vec<T>,
+) -> io::Result<()> {
+    let (count, data) = split_count(reader, width)?;
+    out.clear();
+    if width == 0 {
+        out.resize(count, T::decode(0));
+        return Ok(());
+    }
+    out.resize(count, T::MIN);
+    if decode_byte_aligned(data, width, out) {
+        return Ok(());
+    }
+    let mut bit_reader = BitStream::with_count(io::Cursor::new(data), width, count);
+    let written = bit_reader.read_into(out)?;
+    out.truncate(written);
+    Ok(())
+}
+
+/// Like `decode_values`, but reads bits directly from `reader` with a `SliceBitReader`,
+/// which avoids buffer copies when decoding small in-memory buffers.
+pub fn decode_values_slice<T: BitEncodable>(reader: &[u8], width: u8) -> io::Result<Vec<T>> {
+    let (count, data) = split_count(reader, width)?;
+    if width > 64 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "bit width > 64",
+        ));
+    }
+    if is_byte_aligned::<T>(width) {
+        let mut out = vec![T::MIN; count];
+        if decode_byte_aligned(data, width, &mut out) {
+            return Ok(out);
+        }
+    }
+    let mut out = Vec::with_capacity(count);
+    unpack(data, width, count, &mut out)?;
+    Ok(out)
 }
 
 #[cfg(test)]
@@ -242,4 +448,169 @@ mod tests {
             .collect();
         assert!(decoded.is_empty());
     }
+
+    #[test]
+    fn test_decode_values_slice_matches_decode_values() {
+        for width in [1u8, 3, 7, 8, 13, 31, 33, 57, 63] {
+            let max = (1u64 << width) - 1;
+            let values: Vec<u64> = (0..301u64)
+                .map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15) & max)
+                .collect();
+
+            let mut encoded = (values.len() as u32).to_le_bytes().to_vec();
+            {
+                let mut writer = BitWriter::<_, u64>::new(&mut encoded, width);
+                writer.write_all_values(values.iter().copied()).unwrap();
+                writer.flush().unwrap();
+            }
+
+            let expected = decode_values::<u64>(&encoded, width).unwrap();
+            assert_eq!(expected, values, "width {width}");
+            assert_eq!(
+                decode_values_slice::<u64>(&encoded, width).unwrap(),
+                expected
+            );
+        }
+
+        // signed values go through the same zigzag decode.
+        let signed: Vec<i16> = vec![-300, 0, 5, i16::MIN, i16::MAX];
+        let mut encoded = (signed.len() as u32).to_le_bytes().to_vec();
+        {
+            let mut writer = BitWriter::<_, i16>::new(&mut encoded, 16);
+            writer.write_all_values(signed.iter().copied()).unwrap();
+            writer.flush().unwrap();
+        }
+        assert_eq!(decode_values_slice::<i16>(&encoded, 16).unwrap(), signed);
+    }
+
+    #[test]
+    fn test_decode_values_full_width_fast_path() {
+        let values: Vec<u32> = (0..1000u32)
+            .map(|i| i.wrapping_mul(2_654_435_761))
+            .chain([u32::MAX, 0])
+            .collect();
+        let (width, encoded) =
+            crate::encoding::bitpack::v1::writer::encode_values(&values).unwrap();
+        assert_eq!(width, 32);
+        assert!(u32::from_le_slice(&encoded[4..]).is_some());
+
+        let fast = decode_values::<u32>(&encoded, width).unwrap();
+        let slow: Vec<u32> =
+            BitStream::<_, u32>::with_count(Cursor::new(&encoded[4..]), width, values.len())
+                .collect::<io::Result<_>>()
+                .unwrap();
+        assert_eq!(fast, slow);
+        assert_eq!(fast, values);
+
+        // signed values are ZigZag encoded, so they always take the bit path.
+        let signed: Vec<i32> = vec![i32::MIN, -1, 0, 1, i32::MAX];
+        let (width, encoded) =
+            crate::encoding::bitpack::v1::writer::encode_values(&signed).unwrap();
+        assert_eq!(width, 32);
+        assert_eq!(decode_values::<i32>(&encoded, width).unwrap(), signed);
+    }
+
+    #[test]
+    fn test_decode_values_byte_aligned_narrow_widths() {
+        fn check<T: BitEncodable + std::fmt::Debug>(values: &[T], width: u8) {
+            let mut encoded = (values.len() as u32).to_le_bytes().to_vec();
+            {
+                let mut writer = BitWriter::<_, T>::new(&mut encoded, width);
+                writer.write_all_values(values.iter().copied()).unwrap();
+                writer.flush().unwrap();
+            }
+            let slow: Vec<T> =
+                BitStream::<_, T>::with_count(Cursor::new(&encoded[4..]), width, values.len())
+                    .collect::<io::Result<_>>()
+                    .unwrap();
+            assert_eq!(slow, values, "width {width}");
+            assert_eq!(decode_values::<T>(&encoded, width).unwrap(), values);
+            assert_eq!(decode_values_slice::<T>(&encoded, width).unwrap(), values);
+        }
+
+        let small: Vec<u64> = (0..300u64).map(|i| i * 0x0101 % 0xff).collect();
+        check(&small, 8);
+        check(&small, 16);
+        check(&small, 32);
+        check(
+            &(0..300u32)
+                .map(|i| i.wrapping_mul(2_654_435_761) >> 8)
+                .collect::<Vec<_>>(),
+            24,
+        );
+        check(&[-300i32, 0, 5, i16::MIN as i32, i16::MAX as i32], 16);
+        check(&[1u16, 200, 255], 8);
+    }
+
+    #[test]
+    fn test_decode_values_short_buffers() {
+        // no bytes at all is how `encode_values` stores zero values.
+        let (width, encoded) =
+            crate::encoding::bitpack::v1::writer::encode_values::<u32>(&[]).unwrap();
+        assert!(encoded.is_empty());
+        assert!(decode_values::<u32>(&encoded, width).unwrap().is_empty());
+        assert!(
+            decode_values_slice::<u32>(&encoded, width)
+                .unwrap()
+                .is_empty()
+        );
+
+        // a truncated count prefix.
+        let err = decode_values::<u32>(&[1, 0], 8).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(
+            err.to_string(),
+            "bitpacked buffer of 2 bytes is shorter than its 4-byte count"
+        );
+        let err = decode_values_slice::<u32>(&[1, 0], 8).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        // a count of zero and no data.
+        let zero = 0u32.to_le_bytes();
+        assert!(decode_values::<u32>(&zero, 8).unwrap().is_empty());
+        assert!(decode_values_slice::<u32>(&zero, 8).unwrap().is_empty());
+        assert!(decode_values::<u32>(&zero, 32).unwrap().is_empty());
+
+        // a count the data cannot hold is rejected before sizing the output from it.
+        let mut huge = u32::MAX.to_le_bytes().to_vec();
+        huge.extend_from_slice(&[0xff; 4]);
+        let mut out = Vec::<u64>::new();
+        let err = decode_values_into(&huge, 8, &mut out).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            "count 4294967295 exceeds the 4 values 4 data bytes hold at 8 bits"
+        );
+        assert_eq!(out.capacity(), 0);
+        let err = decode_values_slice::<u64>(&huge, 8).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        // 5 values at 7 bits need 35 bits, one more than 4 bytes hold.
+        let mut short = 5u32.to_le_bytes().to_vec();
+        short.extend_from_slice(&[0; 4]);
+        assert!(decode_values::<u8>(&short, 7).is_err());
+        short[0] = 4;
+        assert_eq!(decode_values::<u8>(&short, 7).unwrap(), vec![0; 4]);
+    }
+
+    #[test]
+    fn test_decode_values_caps_zero_width_count() {
+        let huge = u32::MAX.to_le_bytes();
+        let mut out = Vec::<u64>::new();
+        let err = decode_values_into(&huge, 0, &mut out).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "count 4294967295 exceeds the {MAX_ZERO_WIDTH_COUNT} values allowed at width 0"
+            )
+        );
+        assert_eq!(out.capacity(), 0);
+        assert!(decode_values_slice::<u64>(&huge, 0).is_err());
+
+        let limit = (MAX_ZERO_WIDTH_COUNT as u32).to_le_bytes();
+        assert_eq!(
+            decode_values::<u8>(&limit, 0).unwrap().len(),
+            MAX_ZERO_WIDTH_COUNT
+        );
+    }
 }
@@ -1,5 +1,6 @@
 use crate::encoding::bitpack::v1::common::{BitEncodable, bit_width_from_value};
 use crate::encoding::bitpack::v1::reader::BitReader;
+use crate::encoding::bitpack::v1::writer_pair::PairStream;
 use std::io::{self, Read};
 
 pub struct PairBitReader<R: Read, A: BitEncodable, B: BitEncodable> {
@@ -73,3 +74,66 @@ pub fn decode_pairs<R: Read, A: BitEncodable, B: BitEncodable>(
 
     Ok(result)
 }
+
+/// Iterates the pairs of a stream written by `encode_pair_stream`, taking the widths and
+/// pair count from its header.
+pub struct PairStreamDecoder<R: Read, A: BitEncodable, B: BitEncodable> {
+    pairs: PairBitReader<R, A, B>,
+    header: PairStream,
+    remaining: u32,
+}
+
+impl<R: Read, A: BitEncodable, B: BitEncodable> PairStreamDecoder<R, A, B> {
+    /// Reads the header; fails if either width is zero or too wide for its type.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let header = PairStream::read_from(&mut reader)?;
+        for (width, bits) in [(header.width_a, A::BITS), (header.width_b, B::BITS)] {
+            if width == 0 || width as u32 > bits {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("pair stream width {width} does not fit a {bits}-bit type"),
+                ));
+            }
+        }
+        Ok(Self {
+            pairs: PairBitReader::new(reader, header.width_a, header.width_b),
+            header,
+            remaining: header.count,
+        })
+    }
+
+    pub fn header(&self) -> &PairStream {
+        &self.header
+    }
+}
+
+impl<R: Read, A: BitEncodable, B: BitEncodable> Iterator for PairStreamDecoder<R, A, B> {
+    type Item = io::Result<(A, B)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let index = self.header.count - self.remaining;
+        self.remaining -= 1;
+        match self.pairs.read_pair() {
+            Ok(Some(pair)) => Some(Ok(pair)),
+            Ok(None) => {
+                // a short stream ends the iteration after reporting it once.
+                self.remaining = 0;
+                Some(Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("pair stream ended at pair {index} of {}", self.header.count),
+                )))
+            }
+            Err(e) => {
+                self.remaining = 0;
+                Some(Err(e))
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining as usize))
+    }
+}
@@ -1,18 +1,51 @@
 use crate::buffers::smart_pool::{SmartBufferPool, SmartPage};
 use crate::encoding::StreamingEncoder;
 use crate::encoding::bitpack::v1::common::{BitEncodable, PAGE_DEFAULT_SIZE, bit_width_from_value};
+use crate::encoding::bitpack::v1::footer::StreamFooter;
 use crate::encoding::bitpack::v1::page_writer::PageEncoder;
 use crate::encoding::iters::num::NumReadIter;
+use std::cell::RefCell;
 use std::fs;
 use std::io::{self, Seek, Write};
+use std::path::Path;
 use std::sync::Mutex;
 
 const BUFFER_SIZE: usize = 1 << 20;
 
+/// Streams values to a temp file and bit-packs them on `end_stream`.
+///
+/// The state sits behind a `Mutex`, so the writer can be shared between threads. Column
+/// builders that only push from one thread can use `LocalBitpackStreamWriter` to skip the lock.
 pub struct BitpackStreamWriter<T: BitEncodable> {
     state: Mutex<Option<BitpackState<T>>>,
     pool: SmartBufferPool,
     bit_size: usize,
+    footer: Footer,
+}
+
+/// A `BitpackStreamWriter` whose state lives in a `RefCell`, which skips the lock on every
+/// `encode_value`. It is `!Sync`, so only one thread can drive it. Output is identical.
+pub struct LocalBitpackStreamWriter<T: BitEncodable> {
+    state: RefCell<Option<BitpackState<T>>>,
+    pool: SmartBufferPool,
+    footer: Footer,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Footer {
+    None,
+    Totals,
+    PageIndex,
+}
+
+impl Footer {
+    fn new(page_index: bool) -> Self {
+        if page_index {
+            Footer::PageIndex
+        } else {
+            Footer::Totals
+        }
+    }
 }
 
 struct BitpackState<T: BitEncodable> {
@@ -26,129 +59,247 @@ struct BitpackState<T: BitEncodable> {
 impl<T: BitEncodable> BitpackStreamWriter<T> {
     pub fn new(pool: SmartBufferPool) -> Self {
         let file = tempfile::tempfile().expect("failed to create a temp file");
-        let mut buffer = pool.get(BUFFER_SIZE);
-        buffer.clear();
-        buffer.resize_uninit(BUFFER_SIZE);
+        let state = BitpackState::new(&pool, file);
+        Self::from_state(state, pool)
+    }
 
-        let state = Mutex::new(Some(BitpackState {
-            buffer,
-            file,
-            max: T::MIN,
-            min: T::MAX,
-            count: 0,
-        }));
+    /// Like `new`, but spills values to a temp file in `dir` instead of the system temp
+    /// directory. Every value pushed is kept there unpacked until `end_stream` paginates
+    /// it, so `dir` needs room for `size_of::<T>()` bytes per value of the largest stream.
+    pub fn new_in(pool: SmartBufferPool, dir: &Path) -> io::Result<Self> {
+        let state = BitpackState::new(&pool, tempfile::tempfile_in(dir)?);
+        Ok(Self::from_state(state, pool))
+    }
+
+    fn from_state(state: BitpackState<T>, pool: SmartBufferPool) -> Self {
         let bit_size = core::mem::size_of::<T>();
         Self {
-            state,
+            state: Mutex::new(Some(state)),
             bit_size,
             pool,
+            footer: Footer::None,
         }
     }
 
-    fn flush_buffer(&self, state: &mut BitpackState<T>) -> io::Result<()> {
-        if state.buffer.len() > 0 {
-            state.file.write_all(state.buffer.as_slice())?;
-            state.buffer.clear();
-        }
-        Ok(())
+    /// Appends a `StreamFooter` after the last page, holding the page and value totals and,
+    /// with `page_index`, the offset of every page. Such streams need a footer-aware reader
+    /// like `PageDecoder`, which stops at the footer's magic; readers that predate it take
+    /// the magic for a page header and fail.
+    pub fn with_footer(mut self, page_index: bool) -> Self {
+        self.footer = Footer::new(page_index);
+        self
+    }
+
+    fn with_state<R>(&self, f: impl FnOnce(&mut BitpackState<T>) -> R) -> R {
+        f(self.state.lock().unwrap().as_mut().unwrap())
     }
 }
 
-impl<T: BitEncodable> Default for BitpackStreamWriter<T> {
-    fn default() -> Self {
+impl<T: BitEncodable> LocalBitpackStreamWriter<T> {
+    pub fn new(pool: SmartBufferPool) -> Self {
         let file = tempfile::tempfile().expect("failed to create a temp file");
-        let pool = SmartBufferPool::new(4 * 1024);
+        let state = BitpackState::new(&pool, file);
+        Self {
+            state: RefCell::new(Some(state)),
+            pool,
+            footer: Footer::None,
+        }
+    }
+
+    /// See `BitpackStreamWriter::with_footer`.
+    pub fn with_footer(mut self, page_index: bool) -> Self {
+        self.footer = Footer::new(page_index);
+        self
+    }
+
+    fn with_state<R>(&self, f: impl FnOnce(&mut BitpackState<T>) -> R) -> R {
+        f(self.state.borrow_mut().as_mut().unwrap())
+    }
+}
+
+impl<T: BitEncodable> BitpackState<T> {
+    fn new(pool: &SmartBufferPool, file: fs::File) -> Self {
         let mut buffer = pool.get(BUFFER_SIZE);
         buffer.clear();
         buffer.resize_uninit(BUFFER_SIZE);
 
-        let state = Mutex::new(Some(BitpackState {
+        BitpackState {
             buffer,
             file,
             max: T::MIN,
             min: T::MAX,
             count: 0,
-        }));
-        let bit_size = core::mem::size_of::<T>();
-        Self {
-            state,
-            bit_size,
-            pool,
         }
     }
-}
 
-impl<T> StreamingEncoder<T> for BitpackStreamWriter<T>
-where
-    T: BitEncodable,
-    T: Sync + Send + 'static,
-{
-    fn begin_stream(&self, _: &mut dyn std::io::Write) -> std::io::Result<()> {
-        let mut guard = self.state.lock().unwrap();
-        let state = guard.as_mut().unwrap();
-        state.file.set_len(0).ok(); // truncate
-        state.min = T::MAX;
-        state.max = T::MIN;
-        state.count = 0;
-        state.buffer.clear();
+    fn reset(&mut self) {
+        self.file.set_len(0).ok(); // truncate
+        self.min = T::MAX;
+        self.max = T::MIN;
+        self.count = 0;
+        self.buffer.clear();
+    }
+
+    fn flush_buffer(&mut self) -> io::Result<()> {
+        if self.buffer.len() > 0 {
+            self.file.write_all(self.buffer.as_slice())?;
+            self.buffer.clear();
+        }
         Ok(())
     }
 
-    fn encode_value(&self, v: &T, _: usize, _: &mut dyn std::io::Write) -> std::io::Result<()> {
-        let mut guard = self.state.lock().unwrap();
-        let state = guard.as_mut().unwrap();
-        state.min = state.min.min(*v);
-        state.max = state.max.max(*v);
-        state.count += 1;
-        // state.buffer.extend_from_slice(&v.to_le_bytes());
-        state
-            .buffer
+    fn push(&mut self, v: &T) -> io::Result<()> {
+        self.min = self.min.min(*v);
+        self.max = self.max.max(*v);
+        self.count += 1;
+        // self.buffer.extend_from_slice(&v.to_le_bytes());
+        self.buffer
             .append_slice(&v.to_le_bytes())
             .map_err(|_| io::Error::new(io::ErrorKind::Other, "Buffer capacity exceeded"))?;
-        if state.buffer.len() >= BUFFER_SIZE {
-            self.flush_buffer(state)?;
+        if self.buffer.len() >= BUFFER_SIZE {
+            self.flush_buffer()?;
         }
         Ok(())
     }
 
-    fn end_stream(&self, writer: &mut dyn std::io::Write) -> std::io::Result<()> {
-        let mut guard = self.state.lock().unwrap();
-        let state = guard.as_mut().unwrap();
-
+    fn finish(
+        &mut self,
+        pool: &SmartBufferPool,
+        footer: Footer,
+        writer: &mut dyn Write,
+    ) -> io::Result<()> {
         // Flush remaining buffer
-        self.flush_buffer(state)?;
-        state.file.flush()?;
+        self.flush_buffer()?;
+        self.file.flush()?;
 
         // Handle empty case
-        if state.count == 0 {
-            return Ok(());
+        if self.count == 0 {
+            return write_footer(footer, writer, 0, Vec::new());
         }
 
         // Rewind temp file
-        state.file.seek(std::io::SeekFrom::Start(0))?;
+        self.file.seek(std::io::SeekFrom::Start(0))?;
 
         // Determine bit width
         // NOTE: Decide if you're packing raw values or normalized (v - min)
-        let width = bit_width_from_value::<T>(state.max); // or (state.max - state.min)
-        let reader = io::BufReader::with_capacity(BUFFER_SIZE, &state.file);
+        let width = bit_width_from_value::<T>(self.max); // or (self.max - self.min)
+        let reader = io::BufReader::with_capacity(BUFFER_SIZE, &self.file);
         let num_reader = NumReadIter::<_, T>::new(reader).flatten();
 
-        let page_encoder =
-            PageEncoder::new(self.pool.clone(), num_reader, width, PAGE_DEFAULT_SIZE);
+        let page_encoder = PageEncoder::new(pool.clone(), num_reader, width, PAGE_DEFAULT_SIZE);
+        let mut offsets = Vec::new();
+        let mut written = 0u64;
         for page_result in page_encoder {
             let page = page_result?;
             writer.write_all(&page.buf)?;
+            offsets.push(written);
+            written += page.buf.len() as u64;
         }
+        write_footer(footer, writer, self.count, offsets)?;
         writer.flush()?;
         Ok(())
     }
 }
 
+fn write_footer(
+    footer: Footer,
+    writer: &mut dyn Write,
+    values: u64,
+    offsets: Vec<u64>,
+) -> io::Result<()> {
+    if footer == Footer::None {
+        return Ok(());
+    }
+    let stream_footer = StreamFooter {
+        total_pages: offsets.len() as u64,
+        total_values: values,
+        page_offsets: (footer == Footer::PageIndex).then_some(offsets),
+    };
+    stream_footer.write_to(writer)
+}
+
+impl<T: BitEncodable> Default for BitpackStreamWriter<T> {
+    fn default() -> Self {
+        Self::new(SmartBufferPool::new(4 * 1024))
+    }
+}
+
+impl<T> StreamingEncoder<T> for BitpackStreamWriter<T>
+where
+    T: BitEncodable,
+    T: Sync + Send + 'static,
+{
+    fn begin_stream(&self, _: &mut dyn std::io::Write) -> std::io::Result<()> {
+        self.with_state(|state| {
+            state.reset();
+            Ok(())
+        })
+    }
+
+    fn encode_value(&self, v: &T, _: usize, _: &mut dyn std::io::Write) -> std::io::Result<()> {
+        self.with_state(|state| state.push(v))
+    }
+
+    fn end_stream(&self, writer: &mut dyn std::io::Write) -> std::io::Result<()> {
+        self.with_state(|state| state.finish(&self.pool, self.footer, writer))
+    }
+
+    fn writes_bitpack_pages(&self) -> bool {
+        true
+    }
+
+    /// The spill file values wait in until `end_stream`.
+    fn open_files(&self) -> usize {
+        1
+    }
+}
+
+impl<T> StreamingEncoder<T> for LocalBitpackStreamWriter<T>
+where
+    T: BitEncodable,
+    T: Send + 'static,
+{
+    fn begin_stream(&self, _: &mut dyn std::io::Write) -> std::io::Result<()> {
+        self.with_state(|state| {
+            state.reset();
+            Ok(())
+        })
+    }
+
+    fn encode_value(&self, v: &T, _: usize, _: &mut dyn std::io::Write) -> std::io::Result<()> {
+        self.with_state(|state| state.push(v))
+    }
+
+    fn end_stream(&self, writer: &mut dyn std::io::Write) -> std::io::Result<()> {
+        self.with_state(|state| state.finish(&self.pool, self.footer, writer))
+    }
+
+    fn writes_bitpack_pages(&self) -> bool {
+        true
+    }
+
+    /// The spill file values wait in until `end_stream`.
+    fn open_files(&self) -> usize {
+        1
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::encoding::bitpack::v1::page_reader::PageDecoder;
     use std::io::Cursor;
+    use std::time::Instant;
+
+    fn encode_all(writer: &dyn StreamingEncoder<u32>, values: &[u32]) -> Vec<u8> {
+        let mut cursor = Cursor::new(Vec::new());
+        writer.begin_stream(&mut cursor).unwrap();
+        for (i, v) in values.iter().enumerate() {
+            writer.encode_value(v, i, &mut cursor).unwrap();
+        }
+        writer.end_stream(&mut cursor).unwrap();
+        cursor.into_inner()
+    }
 
     #[test]
     fn test_bitpack_stream() {
@@ -156,10 +307,10 @@ mod tests {
         let writer = BitpackStreamWriter::<u8>::new(pool.clone());
         let mut cursor = Cursor::new(Vec::new());
         writer.begin_stream(&mut cursor).unwrap();
-        writer.encode_value(&1, &mut cursor).unwrap();
-        writer.encode_value(&2, &mut cursor).unwrap();
-        writer.encode_value(&3, &mut cursor).unwrap();
-        writer.encode_value(&4, &mut cursor).unwrap();
+        writer.encode_value(&1, 0, &mut cursor).unwrap();
+        writer.encode_value(&2, 0, &mut cursor).unwrap();
+        writer.encode_value(&3, 0, &mut cursor).unwrap();
+        writer.encode_value(&4, 0, &mut cursor).unwrap();
         writer.end_stream(&mut cursor).unwrap();
 
         let mut decoder = PageDecoder::<_, u8>::new(pool.clone(), Cursor::new(cursor.into_inner()));
@@ -168,4 +319,110 @@ mod tests {
         assert_eq!(decoder.next().unwrap().unwrap(), 3);
         assert_eq!(decoder.next().unwrap().unwrap(), 4);
     }
+
+    #[test]
+    fn test_shared_writer_is_sync() {
+        fn assert_sync<S: Sync>() {}
+        assert_sync::<BitpackStreamWriter<u32>>();
+    }
+
+    #[test]
+    fn test_single_threaded_matches_mutex() {
+        let pool = SmartBufferPool::new(4 * 1024);
+        let values: Vec<u32> = (0..10_000u32)
+            .map(|i| i.wrapping_mul(2654435761) >> 12)
+            .collect();
+
+        let shared = encode_all(&BitpackStreamWriter::new(pool.clone()), &values);
+        let local = encode_all(&LocalBitpackStreamWriter::new(pool.clone()), &values);
+        assert_eq!(shared, local);
+
+        let decoded: Vec<u32> = PageDecoder::<_, u32>::new(pool, Cursor::new(local))
+            .map(|v| v.unwrap())
+            .collect();
+        assert_eq!(decoded, values);
+    }
+
+    #[test]
+    fn test_footer_reports_totals_without_decoding() {
+        let pool = SmartBufferPool::new(4 * 1024);
+        let values: Vec<u32> = (0..100_000u32).map(|i| i % 70_000).collect();
+        let writer = BitpackStreamWriter::new(pool.clone()).with_footer(true);
+        let bytes = encode_all(&writer, &values);
+
+        let mut decoder = PageDecoder::<_, u32>::new(pool.clone(), Cursor::new(bytes.clone()));
+        assert_eq!(decoder.len().unwrap(), Some(values.len() as u64));
+        let footer = decoder.footer().unwrap().unwrap();
+        let offsets = footer.page_offsets.unwrap();
+        assert!(footer.total_pages > 1);
+        assert_eq!(offsets.len() as u64, footer.total_pages);
+
+        // the footer is not mistaken for a page by a plain sequential decode...
+        let decoded: Vec<u32> = decoder.by_ref().map(|v| v.unwrap()).collect();
+        assert_eq!(decoded, values);
+
+        // ...and its page index lets the decoder jump straight to the last page.
+        decoder.seek_to_page(offsets.len() - 1).unwrap();
+        let tail: Vec<u32> = decoder.map(|v| v.unwrap()).collect();
+        assert!(!tail.is_empty());
+        assert_eq!(tail, values[values.len() - tail.len()..]);
+
+        // streams without a footer report no length.
+        let plain = encode_all(&BitpackStreamWriter::new(pool.clone()), &values);
+        assert!(bytes.starts_with(&plain));
+        let mut decoder = PageDecoder::<_, u32>::new(pool, Cursor::new(plain));
+        assert_eq!(decoder.len().unwrap(), None);
+        assert_eq!(
+            decoder.seek_to_page(0).unwrap_err().kind(),
+            io::ErrorKind::Unsupported
+        );
+    }
+
+    #[test]
+    fn test_new_in_spills_to_given_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = SmartBufferPool::new(4 * 1024);
+        let values: Vec<u32> = (0..50_000u32).map(|i| i * 3).collect();
+
+        let writer = BitpackStreamWriter::new_in(pool.clone(), dir.path()).unwrap();
+        let bytes = encode_all(&writer, &values);
+        assert_eq!(
+            bytes,
+            encode_all(&BitpackStreamWriter::new(pool.clone()), &values)
+        );
+
+        let decoded: Vec<u32> = PageDecoder::<_, u32>::new(pool.clone(), Cursor::new(bytes))
+            .map(|v| v.unwrap())
+            .collect();
+        assert_eq!(decoded, values);
+
+        let missing = dir.path().join("missing");
+        assert!(BitpackStreamWriter::<u32>::new_in(pool, &missing).is_err());
+    }
+
+    // cargo test -p columnar --release bench_single_threaded -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_single_threaded_vs_mutex() {
+        let pool = SmartBufferPool::new(4 * 1024);
+        let values: Vec<u32> = (0..5_000_000u32).collect();
+        let writers: [(&str, Box<dyn StreamingEncoder<u32>>); 2] = [
+            ("mutex", Box::new(BitpackStreamWriter::new(pool.clone()))),
+            (
+                "single",
+                Box::new(LocalBitpackStreamWriter::new(pool.clone())),
+            ),
+        ];
+        for (name, writer) in writers {
+            let mut sink = io::sink();
+            writer.begin_stream(&mut sink).unwrap();
+            let start = Instant::now();
+            for (i, v) in values.iter().enumerate() {
+                writer.encode_value(v, i, &mut sink).unwrap();
+            }
+            let elapsed = start.elapsed();
+            writer.end_stream(&mut sink).unwrap();
+            println!("{name}: {} values pushed in {elapsed:?}", values.len());
+        }
+    }
 }
@@ -0,0 +1,76 @@
+use crate::{
+    buffers::smart_pool::SmartBufferPool,
+    encoding::bitpack::v1::{
+        common::BitEncodable, page_reader::PageDecoder, page_writer::write_pages,
+    },
+};
+use std::io::{self, Read, Write};
+
+/// Values decoded, mapped and re-encoded at a time by `transcode_column`.
+const TRANSCODE_CHUNK: usize = 16 * 1024;
+
+/// Rewrites a page stream of `T` as a page stream of `U`, passing every value through `f`,
+/// e.g. to rescale a column or remap categorical codes during a migration.
+///
+/// Works on `TRANSCODE_CHUNK` values at a time, so memory stays bounded whatever the
+/// column's size. Each chunk is packed at the widest mapped value's bit width.
+pub fn transcode_column<T, U, F, R, W>(
+    src_reader: R,
+    mut dst_writer: W,
+    mut f: F,
+    pool: SmartBufferPool,
+) -> io::Result<()>
+where
+    T: BitEncodable,
+    U: BitEncodable,
+    F: FnMut(T) -> U,
+    R: Read,
+    W: Write,
+{
+    let mut decoder = PageDecoder::<_, T>::new(pool.clone(), src_reader);
+    let mut input = vec![T::MIN; TRANSCODE_CHUNK];
+    let mut output = Vec::with_capacity(TRANSCODE_CHUNK);
+    loop {
+        let n = decoder.read_values(&mut input)?;
+        if n == 0 {
+            break;
+        }
+        output.clear();
+        output.extend(input[..n].iter().map(|&v| f(v)));
+        write_pages(&output, &pool, &mut dst_writer)?;
+    }
+    dst_writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::bitpack::v1::page_writer::PageEncoder;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_transcode_u32_to_doubled_u64() -> io::Result<()> {
+        let pool = SmartBufferPool::new(1 << 20);
+        let values: Vec<u32> = (0..40_000u32)
+            .map(|i| i.wrapping_mul(2_654_435_761))
+            .collect();
+        let mut src = Vec::new();
+        for page in PageEncoder::new(pool.clone(), values.iter().copied(), 32, 4096) {
+            src.extend_from_slice(page?.as_slice());
+        }
+
+        let mut dst = Vec::new();
+        transcode_column(
+            Cursor::new(&src),
+            &mut dst,
+            |x: u32| x as u64 * 2,
+            pool.clone(),
+        )?;
+
+        let decoded =
+            PageDecoder::<_, u64>::new(pool, Cursor::new(dst)).collect::<io::Result<Vec<_>>>()?;
+        let expected: Vec<u64> = values.iter().map(|&x| x as u64 * 2).collect();
+        assert_eq!(decoded, expected);
+        Ok(())
+    }
+}
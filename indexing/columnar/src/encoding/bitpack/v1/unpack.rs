@@ -0,0 +1,201 @@
+use crate::encoding::bitpack::v1::common::BitEncodable;
+use crate::encoding::bitpack::v1::reader::SliceBitReader;
+use std::io;
+
+/// Unpacks up to `count` LSB-first values of `width` bits from `data` into `out`, stopping
+/// early if `data` runs short. Any other read error, such as a width over 64 bits, is
+/// returned. With the `simd` feature the word-at-a-time fast paths are used; otherwise
+/// values are read one at a time.
+pub fn unpack<T: BitEncodable>(
+    data: &[u8],
+    width: u8,
+    count: usize,
+    out: &mut Vec<T>,
+) -> io::Result<()> {
+    if cfg!(feature = "simd") {
+        unpack_fast(data, width, count, out)
+    } else {
+        unpack_scalar(data, width, count, out)
+    }
+}
+
+/// Reference path: one `SliceBitReader::read_bits` call per value.
+pub fn unpack_scalar<T: BitEncodable>(
+    data: &[u8],
+    width: u8,
+    count: usize,
+    out: &mut Vec<T>,
+) -> io::Result<()> {
+    let mut bits = SliceBitReader::new(data);
+    for _ in 0..count {
+        match bits.read_value(width) {
+            Ok(v) => out.push(v),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+/// Unrolled paths for byte-aligned and nibble widths, and 8-byte word loads for any other
+/// width up to 56 bits. Produces exactly what `unpack_scalar` does.
+pub fn unpack_fast<T: BitEncodable>(
+    data: &[u8],
+    width: u8,
+    count: usize,
+    out: &mut Vec<T>,
+) -> io::Result<()> {
+    if width > 64 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "bit width > 64",
+        ));
+    }
+    let available = match width {
+        0 => count,
+        w => count.min(data.len() * 8 / w as usize),
+    };
+    out.reserve(available);
+    match width {
+        0 => out.extend((0..available).map(|_| T::decode(0))),
+        4 => {
+            for &byte in &data[..available / 2] {
+                out.push(T::decode((byte & 0x0f) as u64));
+                out.push(T::decode((byte >> 4) as u64));
+            }
+            if available % 2 == 1 {
+                out.push(T::decode((data[available / 2] & 0x0f) as u64));
+            }
+        }
+        8 => out.extend(data[..available].iter().map(|&b| T::decode(b as u64))),
+        16 => out.extend(
+            data[..available * 2]
+                .chunks_exact(2)
+                .map(|c| T::decode(u16::from_le_bytes([c[0], c[1]]) as u64)),
+        ),
+        32 => out.extend(
+            data[..available * 4]
+                .chunks_exact(4)
+                .map(|c| T::decode(u32::from_le_bytes([c[0], c[1], c[2], c[3]]) as u64)),
+        ),
+        w if w <= 56 => unpack_words(data, w, available, out),
+        w => return unpack_scalar(data, w, available, out),
+    }
+    Ok(())
+}
+
+/// A value of at most 56 bits never spans more than 8 bytes, so each one is a single
+/// unaligned word load, shift and mask. The last few values, whose word would run past the
+/// end of `data`, go through the scalar reader.
+fn unpack_words<T: BitEncodable>(data: &[u8], width: u8, count: usize, out: &mut Vec<T>) {
+    let w = width as usize;
+    let mask = (1u64 << width) - 1;
+    // values whose 8-byte window still lies inside `data`.
+    let word_count = if data.len() < 8 {
+        0
+    } else {
+        (((data.len() - 8) * 8) / w + 1).min(count)
+    };
+
+    let mut i = 0;
+    while i + 4 <= word_count {
+        for k in 0..4 {
+            out.push(T::decode(load(data, (i + k) * w) & mask));
+        }
+        i += 4;
+    }
+    while i < word_count {
+        out.push(T::decode(load(data, i * w) & mask));
+        i += 1;
+    }
+
+    if word_count < count {
+        let start = word_count * w;
+        let mut bits = SliceBitReader::new(&data[start / 8..]);
+        // drop the leading bits that belong to the last word-loaded value.
+        bits.read_bits((start % 8) as u8).ok();
+        for _ in word_count..count {
+            match bits.read_value(width) {
+                Ok(v) => out.push(v),
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+#[inline(always)]
+fn load(data: &[u8], bit_pos: usize) -> u64 {
+    let byte = bit_pos / 8;
+    let word = u64::from_le_bytes(data[byte..byte + 8].try_into().unwrap());
+    word >> (bit_pos % 8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn random_bytes(len: usize, mut seed: u64) -> Vec<u8> {
+        (0..len)
+            .map(|_| {
+                seed = seed
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1_442_695_040_888_963_407);
+                (seed >> 33) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fast_matches_scalar() {
+        for width in 1..=32u8 {
+            for len in [0, 1, 7, 8, 9, 63, 1000] {
+                let data = random_bytes(len, width as u64 * 31 + len as u64);
+                // ask for more values than the data holds to cover the short tail.
+                let count = len * 8 / width as usize + 3;
+                let (mut fast, mut scalar) = (Vec::<u64>::new(), Vec::<u64>::new());
+                unpack_fast(&data, width, count, &mut fast).unwrap();
+                unpack_scalar(&data, width, count, &mut scalar).unwrap();
+                assert_eq!(fast, scalar, "width {width}, {len} bytes");
+
+                let (mut fast, mut scalar) = (Vec::<i32>::new(), Vec::<i32>::new());
+                unpack_fast(&data, width, count, &mut fast).unwrap();
+                unpack_scalar(&data, width, count, &mut scalar).unwrap();
+                assert_eq!(fast, scalar, "signed width {width}, {len} bytes");
+            }
+        }
+    }
+
+    #[test]
+    fn test_unpack_reports_bad_width_but_not_short_data() {
+        let data = random_bytes(3, 5);
+        for unpack in [unpack_scalar::<u64>, unpack_fast::<u64>] {
+            let mut out = Vec::new();
+            let err = unpack(&data, 65, 1, &mut out).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+            // running out of data only ends the values early.
+            unpack(&data, 8, 10, &mut out).unwrap();
+            assert_eq!(out.len(), 3);
+        }
+    }
+
+    // cargo test -p columnar --release --features simd bench_unpack -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_unpack() {
+        let data = random_bytes(8 << 20, 7);
+        for width in [3u8, 4, 8, 11, 16, 32] {
+            let count = data.len() * 8 / width as usize;
+            let mut out = Vec::<u64>::with_capacity(count);
+            let start = Instant::now();
+            unpack_scalar(&data, width, count, &mut out).unwrap();
+            let scalar = start.elapsed();
+            out.clear();
+            let start = Instant::now();
+            unpack_fast(&data, width, count, &mut out).unwrap();
+            let fast = start.elapsed();
+            println!("width {width:2}: {count} values, scalar {scalar:?}, fast {fast:?}");
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use crate::encoding::bitpack::v1::common::{BitEncodable, PAGE_HEADER_SIZE};
+use crate::encoding::bitpack::v1::footer::{STREAM_FOOTER_MAGIC, StreamFooter};
+use crate::encoding::bitpack::v1::page_reader::PageHeader;
+use std::io::{self, Read};
+
+/// Summary of a page stream checked by `validate_stream`.
+#[derive(Debug, Default, PartialEq)]
+pub struct StreamReport {
+    /// Pages whose header parsed, including ones with issues.
+    pub pages: usize,
+    /// Sum of the header counts of those pages.
+    pub values: u64,
+    pub issues: Vec<PageIssue>,
+}
+
+impl StreamReport {
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// A problem found in one page. `offset` is where the page header starts.
+#[derive(Debug, PartialEq)]
+pub struct PageIssue {
+    pub page: usize,
+    pub offset: u64,
+    pub reason: String,
+}
+
+/// Walks every page of a bitpack stream, checking that each header parses, that its bit
+/// width fits `T`, min <= max, and that the data length matches `count * bit_width` and is
+/// fully present. Page data is skipped, never decoded, so no value buffers are allocated.
+/// Pages carry no checksum, so flipped bits inside the packed data go unnoticed.
+///
+/// A stream footer, if present, ends the walk and is checked against the pages seen.
+///
+/// Problems are collected in the report rather than returned as errors; only I/O errors
+/// from `reader` fail the call. Scanning stops at the first page whose header or data is
+/// unreadable, since the following page boundary is then unknown.
+pub fn validate_stream<T: BitEncodable, R: Read>(mut reader: R) -> io::Result<StreamReport> {
+    let mut report = StreamReport::default();
+    let mut offset = 0u64;
+    let mut header_buf = [0u8; PAGE_HEADER_SIZE];
+
+    loop {
+        let page = report.pages;
+        let issue = |reason: String| PageIssue {
+            page,
+            offset,
+            reason,
+        };
+
+        let read = read_full(&mut reader, &mut header_buf)?;
+        if read == 0 {
+            return Ok(report);
+        }
+        if header_buf[..read].starts_with(STREAM_FOOTER_MAGIC) {
+            let rest = &header_buf[STREAM_FOOTER_MAGIC.len()..read];
+            check_footer(&mut rest.chain(&mut reader), &mut report, issue)?;
+            return Ok(report);
+        }
+        if read < PAGE_HEADER_SIZE {
+            report.issues.push(issue(format!(
+                "truncated header ({read} of {PAGE_HEADER_SIZE} bytes)"
+            )));
+            return Ok(report);
+        }
+        let header = match PageHeader::<T>::read_from(&mut &header_buf[..]) {
+            Ok(header) => header,
+            Err(e) => {
+                report.issues.push(issue(e.to_string()));
+                return Ok(report);
+            }
+        };
+        report.pages += 1;
+        report.values += header.count as u64;
+
+        if header.bit_width as u32 > T::BITS {
+            report.issues.push(issue(format!(
+                "bit width {} exceeds the {}-bit type",
+                header.bit_width,
+                T::BITS
+            )));
+        }
+        if header.count > 0 && header.min > header.max {
+            report
+                .issues
+                .push(issue("min is greater than max".to_string()));
+        }
+        let expected = (header.count as u64 * header.bit_width as u64).div_ceil(8);
+        if header.data_bytes != expected {
+            report.issues.push(issue(format!(
+                "data length {} does not match {} values at {} bits ({expected} bytes)",
+                header.data_bytes, header.count, header.bit_width
+            )));
+        }
+
+        let skipped = io::copy(&mut (&mut reader).take(header.data_bytes), &mut io::sink())?;
+        if skipped < header.data_bytes {
+            report.issues.push(issue(format!(
+                "truncated data ({skipped} of {} bytes)",
+                header.data_bytes
+            )));
+            return Ok(report);
+        }
+        offset += (PAGE_HEADER_SIZE as u64) + header.data_bytes;
+    }
+}
+
+/// Compares a stream footer with the pages walked so far.
+fn check_footer<R: Read>(
+    reader: &mut R,
+    report: &mut StreamReport,
+    issue: impl Fn(String) -> PageIssue,
+) -> io::Result<()> {
+    let footer = match StreamFooter::read_after_magic(reader) {
+        Ok(footer) => footer,
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+            report
+                .issues
+                .push(issue("truncated stream footer".to_string()));
+            return Ok(());
+        }
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+            report.issues.push(issue(e.to_string()));
+            return Ok(());
+        }
+        Err(e) => return Err(e),
+    };
+    if footer.total_pages != report.pages as u64 || footer.total_values != report.values {
+        report.issues.push(issue(format!(
+            "footer records {} pages and {} values, stream has {} and {}",
+            footer.total_pages, footer.total_values, report.pages, report.values
+        )));
+    }
+    Ok(())
+}
+
+/// Reads until `buf` is full or the reader is exhausted, returning the bytes read.
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffers::smart_pool::SmartBufferPool;
+    use crate::encoding::bitpack::v1::page_writer::{PageEncoder, values_per_page};
+
+    fn encode(count: u32) -> (Vec<u8>, usize) {
+        let pool = SmartBufferPool::new(1 << 20);
+        let mut bytes = Vec::new();
+        let mut pages = 0;
+        for page in PageEncoder::new(pool, (0..count).map(|v| v % 1000), 10, 256) {
+            bytes.extend_from_slice(page.unwrap().as_slice());
+            pages += 1;
+        }
+        (bytes, pages)
+    }
+
+    #[test]
+    fn test_validate_multi_page_stream() {
+        let (bytes, pages) = encode(1000);
+        assert!(pages > 1);
+        let report = validate_stream::<u32, _>(bytes.as_slice()).unwrap();
+        assert!(report.is_ok(), "{:?}", report.issues);
+        assert_eq!(report.pages, pages);
+        assert_eq!(report.values, 1000);
+    }
+
+    #[test]
+    fn test_validate_checks_footer() {
+        let (mut bytes, pages) = encode(1000);
+        let footer = StreamFooter {
+            total_pages: pages as u64,
+            total_values: 1000,
+            page_offsets: None,
+        };
+        let mut with_footer = bytes.clone();
+        footer.write_to(&mut with_footer).unwrap();
+        let report = validate_stream::<u32, _>(with_footer.as_slice()).unwrap();
+        assert!(report.is_ok(), "{:?}", report.issues);
+        assert_eq!(report.pages, pages);
+
+        StreamFooter {
+            total_values: 999,
+            ..footer
+        }
+        .write_to(&mut bytes)
+        .unwrap();
+        let report = validate_stream::<u32, _>(bytes.as_slice()).unwrap();
+        assert_eq!(report.issues.len(), 1);
+        assert!(report.issues[0].reason.contains("999 values"));
+    }
+
+    #[test]
+    fn test_validate_reports_corrupted_page() {
+        let (mut bytes, _) = encode(1000);
+        let page_len = PAGE_HEADER_SIZE + (values_per_page(10, 256) * 10).div_ceil(8);
+        // break the magic of the third page.
+        bytes[2 * page_len] ^= 0xff;
+
+        let report = validate_stream::<u32, _>(bytes.as_slice()).unwrap();
+        assert_eq!(report.pages, 2);
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].page, 2);
+        assert_eq!(report.issues[0].offset, 2 * page_len as u64);
+        assert!(report.issues[0].reason.contains("magic"));
+
+        // a type with a different width is rejected on the first page.
+        let report = validate_stream::<u16, _>(encode(10).0.as_slice()).unwrap();
+        assert_eq!(report.issues[0].page, 0);
+    }
+}
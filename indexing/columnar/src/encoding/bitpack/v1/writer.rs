@@ -1,6 +1,7 @@
 use crate::encoding::bitpack::v1::common::{
     BitEncodable, bit_width_from_value, clamp_width_to_type,
 };
+use crate::encoding::bitpack::v1::reader::MAX_ZERO_WIDTH_COUNT;
 use std::io::{self, Write};
 use std::marker::PhantomData;
 
@@ -11,6 +12,7 @@ pub struct BitWriter<W: Write, T: BitEncodable> {
     bits: u64,     // 64-bit buffer, mirroring BitReader
     bit_count: u8, // Number of valid bits in the buffer
     width: u8,     // Bits per value
+    finished: bool,
     _marker: std::marker::PhantomData<T>,
 }
 
@@ -23,6 +25,7 @@ impl<W: Write, T: BitEncodable> BitWriter<W, T> {
             bits: 0,
             bit_count: 0,
             width,
+            finished: false,
             _marker: std::marker::PhantomData,
         }
     }
@@ -60,7 +63,8 @@ impl<W: Write, T: BitEncodable> BitWriter<W, T> {
                 self.bits |= (encoded & mask) << self.bit_count;
 
                 self.bit_count += chunk_size;
-                encoded >>= chunk_size;
+                // a full 64-bit chunk leaves nothing; `>>= 64` would overflow.
+                encoded = encoded.checked_shr(chunk_size as u32).unwrap_or(0);
                 bits_to_write -= chunk_size;
             }
 
@@ -96,13 +100,37 @@ impl<W: Write, T: BitEncodable> BitWriter<W, T> {
     }
 }
 
+impl<W: Write, T: BitEncodable> BitWriter<W, T> {
+    /// Flushes the remaining bits and reports any error. Prefer this over relying on
+    /// `Drop`, which can only swallow a failed final flush.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.finished = true;
+        self.flush()
+    }
+}
+
 impl<W: Write, T: BitEncodable> Drop for BitWriter<W, T> {
     fn drop(&mut self) {
-        // Attempt to flush remaining bits, but ignore errors as we can't panic in drop.
-        let _ = self.flush();
+        if self.finished {
+            return;
+        }
+        let pending = self.bit_count;
+        // Errors can't be returned from drop; `finish` is the way to see them.
+        if let Err(e) = self.flush() {
+            report_lost_bits(pending, &e);
+        }
     }
 }
 
+/// Called when a writer dropped without `finish` fails its final flush. Debug builds
+/// treat losing buffered bits this way as a bug.
+fn report_lost_bits(pending: u8, err: &io::Error) {
+    debug_assert!(
+        pending == 0 || std::thread::panicking(),
+        "bit writer dropped without finish and lost {pending} buffered bits: {err}"
+    );
+}
+
 //=============================================================================
 // 2. The Borrowing Version (BitWriterRef)
 //    - Borrows its writer `&'a mut W`.
@@ -114,6 +142,7 @@ pub struct BitWriterRef<'a, W: Write, T: BitEncodable> {
     bits: u64,
     bit_count: u8,
     width: u8,
+    finished: bool,
     _marker: PhantomData<T>,
 }
 
@@ -125,6 +154,7 @@ impl<'a, W: Write, T: BitEncodable> BitWriterRef<'a, W, T> {
             bits: 0,
             bit_count: 0,
             width,
+            finished: false,
             _marker: PhantomData,
         }
     }
@@ -147,6 +177,12 @@ impl<'a, W: Write, T: BitEncodable> BitWriterRef<'a, W, T> {
         self.writer.flush()
     }
 
+    /// Flushes the remaining bits and reports any error, unlike the flush in `Drop`.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.finished = true;
+        self.flush()
+    }
+
     pub fn write_value(&mut self, value: T) -> io::Result<()> {
         let mut encoded = value.encode();
         let mut bits_to_write = self.width;
@@ -162,7 +198,8 @@ impl<'a, W: Write, T: BitEncodable> BitWriterRef<'a, W, T> {
                 };
                 self.bits |= (encoded & mask) << self.bit_count;
                 self.bit_count += chunk_size;
-                encoded >>= chunk_size;
+                // a full 64-bit chunk leaves nothing; `>>= 64` would overflow.
+                encoded = encoded.checked_shr(chunk_size as u32).unwrap_or(0);
                 bits_to_write -= chunk_size;
             }
             while self.bit_count >= 8 {
@@ -178,18 +215,34 @@ impl<'a, W: Write, T: BitEncodable> BitWriterRef<'a, W, T> {
 /// The borrowing writer automatically flushes when it goes out of scope.
 impl<'a, W: Write, T: BitEncodable> Drop for BitWriterRef<'a, W, T> {
     fn drop(&mut self) {
-        // Ignore errors in drop, as we cannot panic.
-        let _ = self.flush();
+        if self.finished {
+            return;
+        }
+        let pending = self.bit_count;
+        if let Err(e) = self.flush() {
+            report_lost_bits(pending, &e);
+        }
     }
 }
 
+/// Encodes `values` as `[count: u32][bit-packed values]` at the narrowest width that holds
+/// the largest one, and returns that width with the buffer.
+///
+/// Width 0 means no value has any bits set: an empty input is stored as no bytes at all,
+/// and an all-zero input as just its count, which decoders expand back into zeros. All-zero
+/// inputs longer than `MAX_ZERO_WIDTH_COUNT` are packed at width 1, since decoders reject
+/// longer width-0 counts.
 pub fn encode_values<T: BitEncodable>(values: &[T]) -> io::Result<(u8, Vec<u8>)> {
     if values.is_empty() {
         return Ok((0, Vec::new()));
     }
     let max_value = values.iter().cloned().max().unwrap();
     let len = values.len() as u32;
-    let width = bit_width_from_value(max_value);
+    let width = if values.len() <= MAX_ZERO_WIDTH_COUNT && values.iter().all(|v| v.encode() == 0) {
+        0
+    } else {
+        bit_width_from_value(max_value)
+    };
     let mut buffer = Vec::new();
     buffer.extend_from_slice(&len.to_le_bytes());
     {
@@ -249,7 +302,7 @@ mod tests {
     fn test_encode_values() {
         let values: Vec<u32> = vec![0, 1, 2, 3, 4, 5, 63, 64, 127, 128, 255, 256, 511, 512, 1023];
         let (width, encoded) = encode_values(&values.clone()).unwrap();
-        let decoded = decode_values(&encoded, width).unwrap();
+        let decoded: Vec<u32> = decode_values(&encoded, width).unwrap();
         assert_eq!(values, decoded);
     }
 
@@ -257,7 +310,77 @@ mod tests {
     fn test_encode_values_single() {
         let values: Vec<u32> = vec![0];
         let (width, encoded) = encode_values(&values.clone()).unwrap();
-        let decoded = decode_values(&encoded, width).unwrap();
+        let decoded: Vec<u32> = decode_values(&encoded, width).unwrap();
         assert_eq!(values, decoded);
     }
+
+    #[test]
+    fn test_encode_values_all_zero_uses_width_zero() {
+        let values = vec![0u32; 100];
+        let (width, encoded) = encode_values(&values).unwrap();
+        assert_eq!(width, 0);
+        // only the count is stored.
+        assert_eq!(encoded, 100u32.to_le_bytes());
+        assert_eq!(decode_values::<u32>(&encoded, width).unwrap(), values);
+        assert_eq!(
+            crate::encoding::bitpack::v1::reader::decode_values_slice::<u32>(&encoded, width)
+                .unwrap(),
+            values
+        );
+
+        // zero is the only signed value whose ZigZag encoding has no bits set.
+        let signed = vec![0i64; 7];
+        let (width, encoded) = encode_values(&signed).unwrap();
+        assert_eq!(width, 0);
+        assert_eq!(decode_values::<i64>(&encoded, width).unwrap(), signed);
+        assert_eq!(encode_values(&[0i64, -1]).unwrap().0, 1);
+
+        // past the width-0 cap the zeros take a bit each, and still round trip.
+        let long = vec![0u8; MAX_ZERO_WIDTH_COUNT + 1];
+        let (width, encoded) = encode_values(&long).unwrap();
+        assert_eq!(width, 1);
+        assert_eq!(decode_values::<u8>(&encoded, width).unwrap(), long);
+    }
+
+    struct FailingWriter;
+
+    impl Write for FailingWriter {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk full"))
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Err(io::Error::other("disk full"))
+        }
+    }
+
+    #[test]
+    fn test_finish_reports_flush_error() {
+        // 3 bits stay buffered, so nothing reaches the writer until the final flush.
+        let mut writer = BitWriter::<_, u8>::new(FailingWriter, 3);
+        writer.write_value(5).unwrap();
+        let err = writer.finish().unwrap_err();
+        assert_eq!(err.to_string(), "disk full");
+
+        let mut inner = FailingWriter;
+        let mut writer = BitWriterRef::<_, u8>::new(&mut inner, 3);
+        writer.write_value(5).unwrap();
+        assert!(writer.finish().is_err());
+    }
+
+    #[test]
+    fn test_finish_writes_buffered_bits() {
+        let mut out = Vec::new();
+        let mut writer = BitWriter::<_, u8>::new(&mut out, 3);
+        writer.write_all_values([1, 2, 3]).unwrap();
+        writer.finish().unwrap();
+        assert_eq!(out, [0b1101_0001, 0]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "dropped without finish")]
+    fn test_drop_with_lost_bits_asserts() {
+        let mut writer = BitWriter::<_, u8>::new(FailingWriter, 3);
+        writer.write_value(5).unwrap();
+    }
 }
@@ -1,7 +1,7 @@
 use crate::encoding::bitpack::v1::common::{
     BitEncodable, bit_width_from_value, clamp_width_to_type,
 };
-use std::io::{self, Write};
+use std::io::{self, Read, Write};
 
 /// Writes pairs of values (T, U) bit-packed with fixed widths.
 pub struct PairBitWriter<W: Write, A: BitEncodable, B: BitEncodable> {
@@ -99,3 +99,78 @@ pub fn encode_pairs<A: BitEncodable, B: BitEncodable>(
     }
     Ok(buffer)
 }
+
+/// Size in bytes of a serialized `PairStream` header.
+pub const PAIR_STREAM_HEADER_SIZE: usize = 6;
+
+/// Header of a self-describing pair stream:
+///
+/// `[width_a: u8][width_b: u8][count: u32 LE]`
+///
+/// followed by `count` pairs packed exactly as `PairBitWriter` writes them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PairStream {
+    pub width_a: u8,
+    pub width_b: u8,
+    pub count: u32,
+}
+
+impl PairStream {
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut buf = [0u8; PAIR_STREAM_HEADER_SIZE];
+        buf[0] = self.width_a;
+        buf[1] = self.width_b;
+        buf[2..6].copy_from_slice(&self.count.to_le_bytes());
+        writer.write_all(&buf)
+    }
+
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut buf = [0u8; PAIR_STREAM_HEADER_SIZE];
+        reader.read_exact(&mut buf)?;
+        Ok(Self {
+            width_a: buf[0],
+            width_b: buf[1],
+            count: u32::from_le_bytes([buf[2], buf[3], buf[4], buf[5]]),
+        })
+    }
+}
+
+/// Like `encode_pairs`, but derives the widths from the pairs themselves and writes them,
+/// with the pair count, in a `PairStream` header so `PairStreamDecoder` needs no arguments.
+pub fn encode_pair_stream<A: BitEncodable, B: BitEncodable>(
+    pairs: &[(A, B)],
+) -> io::Result<Vec<u8>> {
+    let count = u32::try_from(pairs.len()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} pairs do not fit a pair stream header", pairs.len()),
+        )
+    })?;
+    let header = PairStream {
+        width_a: pairs
+            .iter()
+            .map(|(a, _)| bit_width_from_value(*a))
+            .max()
+            .unwrap_or(1),
+        width_b: pairs
+            .iter()
+            .map(|(_, b)| bit_width_from_value(*b))
+            .max()
+            .unwrap_or(1),
+        count,
+    };
+
+    let mut buffer = Vec::with_capacity(
+        PAIR_STREAM_HEADER_SIZE
+            + (pairs.len() * (header.width_a + header.width_b) as usize).div_ceil(8),
+    );
+    header.write_to(&mut buffer)?;
+    {
+        let mut writer = PairBitWriter::new(&mut buffer, header.width_a, header.width_b);
+        for &(a, b) in pairs {
+            writer.write_pair(a, b)?;
+        }
+        writer.flush()?;
+    }
+    Ok(buffer)
+}
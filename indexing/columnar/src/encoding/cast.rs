@@ -0,0 +1,88 @@
+use crate::encoding::streaming::StreamingEncoder;
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::marker::PhantomData;
+
+/// Narrows each value to `U` before handing it to the inner encoder.
+/// Values that do not fit in `U` fail with `InvalidData` instead of wrapping.
+pub struct CheckedCastEncoder<T, U> {
+    inner: Box<dyn StreamingEncoder<U>>,
+    _marker: PhantomData<fn(T)>,
+}
+
+impl<T, U> CheckedCastEncoder<T, U> {
+    pub fn new(inner: Box<dyn StreamingEncoder<U>>) -> Self {
+        Self {
+            inner,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, U> StreamingEncoder<T> for CheckedCastEncoder<T, U>
+where
+    T: Copy + Display + TryInto<U> + 'static,
+    U: 'static,
+{
+    fn begin_stream(&self, writer: &mut dyn Write) -> io::Result<()> {
+        self.inner.begin_stream(writer)
+    }
+
+    fn encode_value(&self, v: &T, row_pos: usize, writer: &mut dyn Write) -> io::Result<()> {
+        let cast: U = (*v).try_into().map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "value {} at row {} does not fit in {}",
+                    v,
+                    row_pos,
+                    std::any::type_name::<U>()
+                ),
+            )
+        })?;
+        self.inner.encode_value(&cast, row_pos, writer)
+    }
+
+    fn end_stream(&self, writer: &mut dyn Write) -> io::Result<()> {
+        self.inner.end_stream(writer)
+    }
+
+    fn open_files(&self) -> usize {
+        self.inner.open_files()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::FixedWidthStreamEncoder;
+
+    #[test]
+    fn test_in_range_downcast() {
+        let encoder = CheckedCastEncoder::<i64, i32>::new(Box::new(FixedWidthStreamEncoder));
+        let mut out = Vec::new();
+        encoder.begin_stream(&mut out).unwrap();
+        encoder.encode_value(&-7, 0, &mut out).unwrap();
+        encoder
+            .encode_value(&(i32::MAX as i64), 1, &mut out)
+            .unwrap();
+        encoder.end_stream(&mut out).unwrap();
+
+        let mut expected = Vec::new();
+        expected.extend_from_slice(&(-7i32).to_ne_bytes());
+        expected.extend_from_slice(&i32::MAX.to_ne_bytes());
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_out_of_range_downcast_errors() {
+        let encoder = CheckedCastEncoder::<i64, i32>::new(Box::new(FixedWidthStreamEncoder));
+        let mut out = Vec::new();
+        let err = encoder
+            .encode_value(&(i32::MAX as i64 + 1), 3, &mut out)
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("row 3"));
+        assert!(out.is_empty(), "nothing should be written on a failed cast");
+    }
+}
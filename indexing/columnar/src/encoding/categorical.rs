@@ -0,0 +1,227 @@
+use crate::encoding::bitpack::v1::common::{BitEncodable, bit_width_from_value};
+use crate::encoding::bitpack::v1::unpack::unpack;
+use crate::encoding::bitpack::v1::writer::BitWriter;
+use crate::encoding::streaming::{StreamingDecoder, StreamingEncoder, read_declared};
+use std::io::{self, Read, Write};
+use std::sync::Mutex;
+
+/// Dictionary encoding for low-cardinality columns.
+///
+/// Values are buffered until `end_stream`, which writes the sorted distinct values once
+/// followed by one bit-packed dictionary code per row:
+///
+/// `[cardinality: u32][width: u8][count: u32][dictionary][codes]`
+///
+/// Dictionary entries are little-endian `T`s, and codes take `ceil(log2(cardinality))`
+/// bits (at least 1), LSB first.
+pub struct CategoricalEncoder<T: BitEncodable> {
+    values: Mutex<Vec<T>>,
+}
+
+impl<T: BitEncodable> Default for CategoricalEncoder<T> {
+    fn default() -> Self {
+        Self {
+            values: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl<T: BitEncodable> CategoricalEncoder<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Bits needed to address `cardinality` dictionary entries.
+pub fn code_width(cardinality: usize) -> u8 {
+    bit_width_from_value(cardinality.saturating_sub(1) as u64)
+}
+
+impl<T: BitEncodable + Send + 'static> StreamingEncoder<T> for CategoricalEncoder<T> {
+    fn begin_stream(&self, _writer: &mut dyn Write) -> io::Result<()> {
+        self.values.lock().unwrap().clear();
+        Ok(())
+    }
+
+    fn encode_value(&self, v: &T, _: usize, _writer: &mut dyn Write) -> io::Result<()> {
+        self.values.lock().unwrap().push(*v);
+        Ok(())
+    }
+
+    fn end_stream(&self, writer: &mut dyn Write) -> io::Result<()> {
+        let values = std::mem::take(&mut *self.values.lock().unwrap());
+        let mut dictionary = values.clone();
+        dictionary.sort_unstable();
+        dictionary.dedup();
+        let width = code_width(dictionary.len());
+        // the dictionary is never longer than the values, so one check covers both counts.
+        let count = u32::try_from(values.len()).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "categorical stream holds {} values, more than its u32 count can record",
+                    values.len()
+                ),
+            )
+        })?;
+
+        writer.write_all(&(dictionary.len() as u32).to_le_bytes())?;
+        writer.write_all(&[width])?;
+        writer.write_all(&count.to_le_bytes())?;
+        for v in &dictionary {
+            writer.write_all(&v.to_le_bytes())?;
+        }
+
+        let mut codes = BitWriter::<_, u64>::new(&mut *writer, width);
+        for v in &values {
+            // every value is in the dictionary, so the search always hits.
+            let code = dictionary.binary_search(v).unwrap_or_default();
+            codes.write_value(code as u64)?;
+        }
+        codes.flush()
+    }
+}
+
+/// Reads a stream written by `CategoricalEncoder`, mapping each code back to its value.
+pub struct CategoricalDecoder<T: BitEncodable> {
+    dictionary: Vec<T>,
+    codes: Vec<u64>,
+    next: usize,
+}
+
+impl<T: BitEncodable> Default for CategoricalDecoder<T> {
+    fn default() -> Self {
+        Self {
+            dictionary: Vec::new(),
+            codes: Vec::new(),
+            next: 0,
+        }
+    }
+}
+
+impl<T: BitEncodable> CategoricalDecoder<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn dictionary(&self) -> &[T] {
+        &self.dictionary
+    }
+}
+
+impl<T: BitEncodable + Send> StreamingDecoder<T> for CategoricalDecoder<T> {
+    fn begin_stream(&mut self, reader: &mut dyn Read) -> io::Result<()> {
+        let mut header = [0u8; 9];
+        reader.read_exact(&mut header)?;
+        let cardinality = u32::from_le_bytes(header[0..4].try_into().unwrap()) as usize;
+        let width = header[4];
+        let count = u32::from_le_bytes(header[5..9].try_into().unwrap()) as usize;
+        if width == 0 || width > 32 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid categorical code width {width}"),
+            ));
+        }
+
+        let value_size = T::BITS as u64 / 8;
+        let dictionary = read_declared(reader, cardinality as u64 * value_size)?;
+        self.dictionary = dictionary
+            .chunks_exact(value_size as usize)
+            .map(T::from_le_bytes)
+            .collect();
+
+        let packed = read_declared(reader, (count as u64 * width as u64).div_ceil(8))?;
+        self.codes.clear();
+        unpack(&packed, width, count, &mut self.codes)?;
+        self.next = 0;
+        Ok(())
+    }
+
+    fn decode_next(&mut self, _reader: &mut dyn Read) -> io::Result<Option<T>> {
+        let Some(&code) = self.codes.get(self.next) else {
+            return Ok(None);
+        };
+        self.next += 1;
+        self.dictionary
+            .get(code as usize)
+            .copied()
+            .map(Some)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "row {}: code {code} outside a dictionary of {}",
+                        self.next - 1,
+                        self.dictionary.len()
+                    ),
+                )
+            })
+    }
+
+    fn end_stream(&mut self, _reader: &mut dyn Read) -> io::Result<()> {
+        self.codes.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_categorical_roundtrip() {
+        let levels = [700u16, 3, 41, 9000, 12];
+        let values: Vec<u16> = (0..1000).map(|i| levels[(i * 7 + i / 3) % 5]).collect();
+
+        let encoder = CategoricalEncoder::<u16>::new();
+        let mut out = Vec::new();
+        encoder.begin_stream(&mut out).unwrap();
+        for (i, v) in values.iter().enumerate() {
+            encoder.encode_value(v, i, &mut out).unwrap();
+        }
+        encoder.end_stream(&mut out).unwrap();
+
+        // 5 distinct values need 3-bit codes.
+        assert_eq!(out[4], 3);
+        assert_eq!(out.len(), 9 + 5 * 2 + (1000 * 3usize).div_ceil(8));
+
+        let mut reader = io::Cursor::new(out);
+        let mut decoder = CategoricalDecoder::<u16>::new();
+        decoder.begin_stream(&mut reader).unwrap();
+        assert_eq!(decoder.dictionary(), &[3, 12, 41, 700, 9000]);
+        let mut decoded = Vec::new();
+        while let Some(v) = decoder.decode_next(&mut reader).unwrap() {
+            decoded.push(v);
+        }
+        decoder.end_stream(&mut reader).unwrap();
+        assert_eq!(decoded, values);
+    }
+
+    #[test]
+    fn test_decoder_rejects_counts_past_the_stream() {
+        let header = |cardinality: u32, count: u32| {
+            let mut out = cardinality.to_le_bytes().to_vec();
+            out.push(8);
+            out.extend_from_slice(&count.to_le_bytes());
+            out.extend_from_slice(&[1, 0, 2, 0]);
+            out
+        };
+        for stream in [header(u32::MAX, 2), header(2, u32::MAX)] {
+            let mut decoder = CategoricalDecoder::<u16>::new();
+            let err = decoder
+                .begin_stream(&mut io::Cursor::new(stream))
+                .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        }
+    }
+
+    #[test]
+    fn test_code_width() {
+        assert_eq!(code_width(0), 1);
+        assert_eq!(code_width(1), 1);
+        assert_eq!(code_width(2), 1);
+        assert_eq!(code_width(5), 3);
+        assert_eq!(code_width(256), 8);
+        assert_eq!(code_width(257), 9);
+    }
+}
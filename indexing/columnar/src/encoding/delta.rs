@@ -30,7 +30,7 @@ impl StreamingEncoder<i64> for DeltaStreamEncoder {
         let mut guard = self.prev.lock().unwrap();
         let delta = match *guard {
             None => *v,
-            Some(prev) => *v - prev,
+            Some(prev) => v.wrapping_sub(prev),
         };
         writer.write_all(&delta.to_le_bytes())?;
         *guard = Some(*v);
@@ -0,0 +1,211 @@
+use crate::buffers::smart_pool::SmartBufferPool;
+use crate::encoding::streaming::StreamingEncoder;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+
+type Constructor<T> = Box<dyn Fn(SmartBufferPool) -> Box<dyn StreamingEncoder<T>> + Send + Sync>;
+
+struct Entry {
+    /// Encoder kind recorded in the manifest, e.g. `categorical`.
+    name: &'static str,
+    /// A `Constructor<T>` for the `TypeId` this entry is stored under.
+    constructor: Box<dyn Any + Send + Sync>,
+}
+
+impl Entry {
+    fn new<T, F>(name: &'static str, constructor: F) -> Self
+    where
+        T: 'static,
+        F: Fn(SmartBufferPool) -> Box<dyn StreamingEncoder<T>> + Send + Sync + 'static,
+    {
+        let constructor: Constructor<T> = Box::new(constructor);
+        Self {
+            name,
+            constructor: Box::new(constructor),
+        }
+    }
+
+    fn build<T: 'static>(
+        &self,
+        pool: SmartBufferPool,
+    ) -> Option<(&'static str, Box<dyn StreamingEncoder<T>>)> {
+        let constructor = self.constructor.downcast_ref::<Constructor<T>>()?;
+        Some((self.name, constructor(pool)))
+    }
+}
+
+/// Picks streaming encoders by column name or element type at runtime.
+///
+/// Streaming bundles built with `with_factory` ask the factory for each field's encoder:
+/// an encoder registered for that column wins, then one registered for its type, and
+/// otherwise the one named in `#[columnar(encoder = ...)]` is used, so the encoding can
+/// change without recompiling the row type.
+#[derive(Default)]
+pub struct EncoderFactory {
+    entries: HashMap<TypeId, Entry>,
+    /// Per-column overrides, keyed by column name and element type.
+    columns: HashMap<(String, TypeId), Entry>,
+}
+
+impl fmt::Debug for EncoderFactory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncoderFactory")
+            .field(
+                "types",
+                &self
+                    .entries
+                    .values()
+                    .map(|entry| entry.name)
+                    .collect::<Vec<_>>(),
+            )
+            .field(
+                "columns",
+                &self
+                    .columns
+                    .iter()
+                    .map(|((column, _), entry)| (column.as_str(), entry.name))
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl EncoderFactory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the encoder for every `T` column, replacing any earlier registration.
+    /// `name` is what the bundle's manifest records as the column's encoder.
+    pub fn register<T, F>(&mut self, name: &'static str, constructor: F) -> &mut Self
+    where
+        T: 'static,
+        F: Fn(SmartBufferPool) -> Box<dyn StreamingEncoder<T>> + Send + Sync + 'static,
+    {
+        self.entries
+            .insert(TypeId::of::<T>(), Entry::new(name, constructor));
+        self
+    }
+
+    /// Registers the encoder for the `T` column named `column` only, replacing any earlier
+    /// registration for it. Takes precedence over the type-level encoder from `register`.
+    pub fn register_for<T, F>(
+        &mut self,
+        column: &str,
+        name: &'static str,
+        constructor: F,
+    ) -> &mut Self
+    where
+        T: 'static,
+        F: Fn(SmartBufferPool) -> Box<dyn StreamingEncoder<T>> + Send + Sync + 'static,
+    {
+        self.columns.insert(
+            (column.to_string(), TypeId::of::<T>()),
+            Entry::new(name, constructor),
+        );
+        self
+    }
+
+    pub fn with_encoder<T, F>(mut self, name: &'static str, constructor: F) -> Self
+    where
+        T: 'static,
+        F: Fn(SmartBufferPool) -> Box<dyn StreamingEncoder<T>> + Send + Sync + 'static,
+    {
+        self.register(name, constructor);
+        self
+    }
+
+    pub fn with_column_encoder<T, F>(
+        mut self,
+        column: &str,
+        name: &'static str,
+        constructor: F,
+    ) -> Self
+    where
+        T: 'static,
+        F: Fn(SmartBufferPool) -> Box<dyn StreamingEncoder<T>> + Send + Sync + 'static,
+    {
+        self.register_for(column, name, constructor);
+        self
+    }
+
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.entries.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Builds the registered encoder for `T` with its manifest name, or `None` when no
+    /// encoder was registered for `T`.
+    pub fn build<T: 'static>(
+        &self,
+        pool: SmartBufferPool,
+    ) -> Option<(&'static str, Box<dyn StreamingEncoder<T>>)> {
+        self.entries.get(&TypeId::of::<T>())?.build(pool)
+    }
+
+    /// Builds the encoder for the `T` column named `column`: the one registered for that
+    /// column if any, else the one registered for `T`, else `None`.
+    pub fn build_for<T: 'static>(
+        &self,
+        column: &str,
+        pool: SmartBufferPool,
+    ) -> Option<(&'static str, Box<dyn StreamingEncoder<T>>)> {
+        match self.columns.get(&(column.to_string(), TypeId::of::<T>())) {
+            Some(entry) => entry.build(pool),
+            None => self.build(pool),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::{BitpackStreamWriter, CategoricalEncoder};
+
+    #[test]
+    fn test_factory_builds_by_type() {
+        let factory = EncoderFactory::new()
+            .with_encoder::<u32, _>("categorical", |_| Box::new(CategoricalEncoder::new()))
+            .with_encoder::<u64, _>("bitpack", |pool| {
+                Box::new(BitpackStreamWriter::<u64>::new(pool))
+            });
+        assert!(factory.contains::<u32>());
+        assert!(!factory.contains::<i32>());
+
+        let pool = SmartBufferPool::default();
+        let (name, encoder) = factory.build::<u32>(pool.clone()).unwrap();
+        assert_eq!(name, "categorical");
+        let mut out = Vec::new();
+        encoder.begin_stream(&mut out).unwrap();
+        encoder.encode_value(&7, 0, &mut out).unwrap();
+        encoder.end_stream(&mut out).unwrap();
+        // one dictionary entry.
+        assert_eq!(out[..4], 1u32.to_le_bytes());
+
+        assert_eq!(factory.build::<u64>(pool.clone()).unwrap().0, "bitpack");
+        assert!(factory.build::<i32>(pool).is_none());
+    }
+
+    #[test]
+    fn test_factory_column_override_falls_back_to_type() {
+        let factory = EncoderFactory::new()
+            .with_encoder::<u32, _>("bitpack", |pool| {
+                Box::new(BitpackStreamWriter::<u32>::new(pool))
+            })
+            .with_column_encoder::<u32, _>("zone", "categorical", |_| {
+                Box::new(CategoricalEncoder::new())
+            });
+
+        let pool = SmartBufferPool::default();
+        assert_eq!(
+            factory.build_for::<u32>("zone", pool.clone()).unwrap().0,
+            "categorical"
+        );
+        assert_eq!(
+            factory.build_for::<u32>("id", pool.clone()).unwrap().0,
+            "bitpack"
+        );
+        // the override is for a u32 column, so a u64 column of the same name ignores it.
+        assert!(factory.build_for::<u64>("zone", pool).is_none());
+    }
+}
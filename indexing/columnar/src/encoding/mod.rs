@@ -1,12 +1,21 @@
 pub mod bitpack;
+pub mod cast;
+pub mod categorical;
 pub mod delta;
+pub mod factory;
 pub mod fixed_width;
 pub mod iters;
+pub mod sparse;
 pub mod streaming;
 pub mod strings;
 
 pub use bitpack::v1::stream_writer::BitpackStreamWriter;
+pub use cast::CheckedCastEncoder;
+pub use categorical::{CategoricalDecoder, CategoricalEncoder};
 pub use delta::DeltaStreamEncoder;
+pub use factory::EncoderFactory;
 pub use fixed_width::FixedWidthStreamEncoder;
+pub use sparse::{SparseDecoder, SparseStreamEncoder};
 pub use streaming::{StreamingDecoder, StreamingEncoder};
+pub use strings::reader::StringReader;
 pub use strings::writer::StringWriter;
@@ -0,0 +1,232 @@
+use crate::encoding::bitpack::v1::common::BitEncodable;
+use crate::encoding::streaming::{StreamingDecoder, StreamingEncoder, read_declared};
+use std::io::{self, Read, Write};
+use std::sync::Mutex;
+
+/// Rows seen so far: one presence bit per row (LSB first) and the non-default values.
+struct SparseState<T> {
+    count: usize,
+    bitmap: Vec<u8>,
+    values: Vec<T>,
+}
+
+/// Encoding for mostly-default columns, e.g. sentinel indices that are almost always 0.
+///
+/// Only rows that differ from `default` are stored. The stream is written by `end_stream`:
+///
+/// `[count: u32][present: u32][default][bitmap][values]`
+///
+/// The bitmap holds `ceil(count / 8)` bytes with one bit per row, set (LSB first) when the
+/// row differs from the default, and `values` the `present` differing values in row order.
+/// `default` and the values are little-endian `T`s.
+pub struct SparseStreamEncoder<T: BitEncodable> {
+    default: T,
+    state: Mutex<SparseState<T>>,
+}
+
+impl<T: BitEncodable> SparseStreamEncoder<T> {
+    pub fn new(default: T) -> Self {
+        Self {
+            default,
+            state: Mutex::new(SparseState {
+                count: 0,
+                bitmap: Vec::new(),
+                values: Vec::new(),
+            }),
+        }
+    }
+}
+
+/// Bytes a sparse stream of `count` rows with `present` non-default values takes.
+pub fn sparse_encoded_len<T: BitEncodable>(count: usize, present: usize) -> usize {
+    let value_size = T::BITS as usize / 8;
+    8 + value_size + count.div_ceil(8) + present * value_size
+}
+
+impl<T: BitEncodable + Send + 'static> StreamingEncoder<T> for SparseStreamEncoder<T> {
+    fn begin_stream(&self, _writer: &mut dyn Write) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.count = 0;
+        state.bitmap.clear();
+        state.values.clear();
+        Ok(())
+    }
+
+    fn encode_value(&self, v: &T, _: usize, _writer: &mut dyn Write) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let row = state.count;
+        if row.is_multiple_of(8) {
+            state.bitmap.push(0);
+        }
+        if *v != self.default {
+            state.bitmap[row / 8] |= 1 << (row % 8);
+            state.values.push(*v);
+        }
+        state.count += 1;
+        Ok(())
+    }
+
+    fn end_stream(&self, writer: &mut dyn Write) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        // there are never more values than rows, so one check covers both counts.
+        let count = u32::try_from(state.count).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "sparse stream holds {} rows, more than its u32 count can record",
+                    state.count
+                ),
+            )
+        })?;
+        writer.write_all(&count.to_le_bytes())?;
+        writer.write_all(&(state.values.len() as u32).to_le_bytes())?;
+        writer.write_all(&self.default.to_le_bytes())?;
+        writer.write_all(&state.bitmap)?;
+        for v in &state.values {
+            writer.write_all(&v.to_le_bytes())?;
+        }
+        state.count = 0;
+        state.bitmap.clear();
+        state.values.clear();
+        Ok(())
+    }
+}
+
+/// Reads a stream written by `SparseStreamEncoder`, filling absent rows with its default.
+pub struct SparseDecoder<T: BitEncodable> {
+    default: Option<T>,
+    count: usize,
+    bitmap: Vec<u8>,
+    values: Vec<T>,
+    next_row: usize,
+    next_value: usize,
+}
+
+impl<T: BitEncodable> Default for SparseDecoder<T> {
+    fn default() -> Self {
+        Self {
+            default: None,
+            count: 0,
+            bitmap: Vec::new(),
+            values: Vec::new(),
+            next_row: 0,
+            next_value: 0,
+        }
+    }
+}
+
+impl<T: BitEncodable> SparseDecoder<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T: BitEncodable + Send> StreamingDecoder<T> for SparseDecoder<T> {
+    fn begin_stream(&mut self, reader: &mut dyn Read) -> io::Result<()> {
+        let value_size = T::BITS as usize / 8;
+        let mut header = vec![0u8; 8 + value_size];
+        reader.read_exact(&mut header)?;
+        let count = u32::from_le_bytes(header[0..4].try_into().unwrap()) as usize;
+        let present = u32::from_le_bytes(header[4..8].try_into().unwrap()) as usize;
+        if present > count {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("sparse stream has {present} values for {count} rows"),
+            ));
+        }
+
+        let bitmap = read_declared(reader, count.div_ceil(8) as u64)?;
+        let values = read_declared(reader, (present * value_size) as u64)?;
+
+        self.default = Some(T::from_le_bytes(&header[8..]));
+        self.count = count;
+        self.bitmap = bitmap;
+        self.values = values
+            .chunks_exact(value_size)
+            .map(T::from_le_bytes)
+            .collect();
+        self.next_row = 0;
+        self.next_value = 0;
+        Ok(())
+    }
+
+    fn decode_next(&mut self, _reader: &mut dyn Read) -> io::Result<Option<T>> {
+        let (Some(default), true) = (self.default, self.next_row < self.count) else {
+            return Ok(None);
+        };
+        let row = self.next_row;
+        self.next_row += 1;
+        if self.bitmap[row / 8] & (1 << (row % 8)) == 0 {
+            return Ok(Some(default));
+        }
+        let v = self.values.get(self.next_value).copied().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("row {row}: bitmap marks more rows than the stream has values"),
+            )
+        })?;
+        self.next_value += 1;
+        Ok(Some(v))
+    }
+
+    fn end_stream(&mut self, _reader: &mut dyn Read) -> io::Result<()> {
+        self.bitmap.clear();
+        self.values.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparse_roundtrip_mostly_default() {
+        // every 20th row differs from the default, 5% of the column.
+        let values: Vec<u32> = (0..10_000u32)
+            .map(|i| if i % 20 == 7 { i * 3 + 1 } else { 0 })
+            .collect();
+        let present = values.iter().filter(|&&v| v != 0).count();
+        assert_eq!(present, 500);
+
+        let encoder = SparseStreamEncoder::new(0u32);
+        let mut out = Vec::new();
+        encoder.begin_stream(&mut out).unwrap();
+        for (i, v) in values.iter().enumerate() {
+            encoder.encode_value(v, i, &mut out).unwrap();
+        }
+        encoder.end_stream(&mut out).unwrap();
+
+        // the 500 values and a 1250-byte bitmap, instead of 40 000 bytes of u32s.
+        assert_eq!(out.len(), sparse_encoded_len::<u32>(values.len(), present));
+        assert_eq!(out.len(), 12 + 1250 + 500 * 4);
+
+        let mut reader = io::Cursor::new(out);
+        let mut decoder = SparseDecoder::<u32>::new();
+        decoder.begin_stream(&mut reader).unwrap();
+        let mut decoded = Vec::new();
+        while let Some(v) = decoder.decode_next(&mut reader).unwrap() {
+            decoded.push(v);
+        }
+        decoder.end_stream(&mut reader).unwrap();
+        assert_eq!(decoded, values);
+    }
+
+    #[test]
+    fn test_decoder_rejects_counts_past_the_stream() {
+        let header = |count: u32, present: u32| {
+            let mut out = count.to_le_bytes().to_vec();
+            out.extend_from_slice(&present.to_le_bytes());
+            out.extend_from_slice(&0u16.to_le_bytes());
+            out.extend_from_slice(&[0b101, 7, 0, 9, 0]);
+            out
+        };
+        for stream in [header(u32::MAX, 2), header(u32::MAX, u32::MAX)] {
+            let mut decoder = SparseDecoder::<u16>::new();
+            let err = decoder
+                .begin_stream(&mut io::Cursor::new(stream))
+                .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        }
+    }
+}
@@ -6,6 +6,16 @@ pub trait StreamingEncoder<T>: Send + 'static {
     fn begin_stream(&self, writer: &mut dyn Write) -> io::Result<()>;
     fn encode_value(&self, v: &T, row_pos: usize, writer: &mut dyn Write) -> io::Result<()>;
     fn end_stream(&self, writer: &mut dyn Write) -> io::Result<()>;
+
+    /// Whether the output is bitpack pages of `T`, which a `PageIndex` can be built over.
+    fn writes_bitpack_pages(&self) -> bool {
+        false
+    }
+
+    /// Files the encoder keeps open until it is dropped, such as a spill file.
+    fn open_files(&self) -> usize {
+        0
+    }
 }
 
 pub trait StreamingDecoder<T>: Send {
@@ -13,3 +23,21 @@ pub trait StreamingDecoder<T>: Send {
     fn decode_next(&mut self, reader: &mut dyn Read) -> io::Result<Option<T>>;
     fn end_stream(&mut self, reader: &mut dyn Read) -> io::Result<()>;
 }
+
+/// Reads the `len` bytes a stream header declares. The buffer grows as bytes arrive instead
+/// of being allocated up front, so a corrupt length ends in `UnexpectedEof` rather than a
+/// huge allocation.
+pub fn read_declared(reader: &mut dyn Read, len: u64) -> io::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    reader.take(len).read_to_end(&mut buf)?;
+    if (buf.len() as u64) < len {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!(
+                "stream ends after {} of the {len} bytes its header declares",
+                buf.len()
+            ),
+        ));
+    }
+    Ok(buf)
+}
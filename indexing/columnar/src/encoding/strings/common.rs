@@ -3,13 +3,21 @@ use xxhash_rust::xxh3;
 pub const DOC_MAGIC: &[u8; 6] = b"MIDOC1";
 pub const DOC_HEADER_SIZE: usize = 32; // magic (6) + total_data_size (8) + entry_count (4)
 pub const DOC_VERSION: u8 = 1;
+/// Header flag (byte 15): the document ends with its exact terms, see `DocWriter::write_with_terms`.
+pub const DOC_FLAG_TERMS: u8 = 1;
 
 pub fn hash_string(s: &str) -> u64 {
     xxh3::xxh3_64(s.as_bytes())
 }
 
+/// Term placed between fields by `DocStreamWriter::encode_fields`. Tokenizers split on
+/// whitespace, so no text ever produces it and phrases cannot match across a boundary.
+pub const FIELD_BOUNDARY_TERM: &str = " ";
+
 pub(crate) fn process_string(s: &str) -> Vec<u64> {
-    s.split(" ").map(|s| xxh3::xxh3_64(s.as_bytes())).collect()
+    s.split_whitespace()
+        .map(|s| xxh3::xxh3_64(s.as_bytes()))
+        .collect()
 }
 
 pub fn sliding_ngram_hash(tokens: &[u64], win_sz: u8, max_end_win_sz: u8) -> Vec<u64> {
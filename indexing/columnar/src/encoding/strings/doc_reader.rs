@@ -4,9 +4,9 @@ use std::{
 };
 
 use crate::{
-    buffers::smart_pool::SmartBufferPool,
+    buffers::smart_pool::{SmartBufferPool, SmartPage},
     encoding::{
-        bitpack::v1::reader::decode_values,
+        bitpack::v1::reader::decode_values_slice,
         strings::common::{self, DOC_HEADER_SIZE},
     },
 };
@@ -18,6 +18,10 @@ pub struct DocReader {
 pub struct DocHeader {
     data_size: usize,
     entry_count: usize,
+    /// Size of the trailing term section, `0` when the document has none.
+    terms_size: usize,
+    /// Whether the document was written with its terms; an empty one has a section of size 0.
+    has_terms: bool,
 }
 
 impl DocHeader {
@@ -44,9 +48,17 @@ impl DocHeader {
         }
         let data_size = u32::from_le_bytes(buffer[7..11].try_into().unwrap()) as usize;
         let entry_count = u32::from_le_bytes(buffer[11..15].try_into().unwrap()) as usize;
+        let has_terms = buffer[15] & common::DOC_FLAG_TERMS != 0;
+        let terms_size = if has_terms {
+            u32::from_le_bytes(buffer[16..20].try_into().unwrap()) as usize
+        } else {
+            0
+        };
         Ok(DocHeader {
             data_size,
             entry_count,
+            terms_size,
+            has_terms,
         })
     }
 }
@@ -56,60 +68,193 @@ impl DocReader {
         DocReader { pool }
     }
 
+    /// Reads the next document and reports whether `tokens` occur in it consecutively.
+    /// The document is always consumed; an empty `tokens` or an empty document (no entries)
+    /// never matches.
     pub fn search<R: io::Read>(&self, reader: &mut R, tokens: &[u64]) -> io::Result<bool> {
+        let (header, buffer) = self.read_doc(reader)?;
         if tokens.is_empty() {
             return Ok(false);
         }
-        // Implementation goes here
+        let starts = match_starts(&header, buffer.as_slice(), tokens)?;
+        Ok(!starts.is_empty())
+    }
+
+    /// Like `search`, but checks every hash match against the stored terms, so distinct
+    /// words whose hashes collide never produce a false match. `terms` are the query terms
+    /// behind `tokens`, in the same order.
+    ///
+    /// The document must have been written with `DocWriter::write_with_terms`.
+    pub fn search_exact<R: io::Read, S: AsRef<str>>(
+        &self,
+        reader: &mut R,
+        tokens: &[u64],
+        terms: &[S],
+    ) -> io::Result<bool> {
+        if tokens.len() != terms.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} terms for {} tokens", terms.len(), tokens.len()),
+            ));
+        }
+        let (header, buffer) = self.read_doc(reader)?;
+        if !header.has_terms {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "document was written without exact terms",
+            ));
+        }
+        if tokens.is_empty() {
+            return Ok(false);
+        }
+        let starts = match_starts(&header, buffer.as_slice(), tokens)?;
+        if starts.is_empty() {
+            return Ok(false);
+        }
+
+        let section_start = header.entry_count * 16 + header.data_size;
+        let doc_terms = decode_terms(&buffer.as_slice()[section_start..])?;
+        let found = starts.iter().any(|&start| {
+            terms
+                .iter()
+                .enumerate()
+                .all(|(i, term)| doc_terms.get(start as usize + i).copied() == Some(term.as_ref()))
+        });
+        Ok(found)
+    }
+
+    /// Reads the next document and reports whether `tokens` occur in it in order, with at
+    /// most `max_gap` other tokens between neighbours. A `max_gap` of 0 is an exact phrase,
+    /// like `search`. The document is always consumed, and an empty `tokens` never matches.
+    ///
+    /// Uses the position list `DocWriter` stores for every distinct token.
+    pub fn phrase_search<R: io::Read>(
+        &self,
+        reader: &mut R,
+        tokens: &[u64],
+        max_gap: u32,
+    ) -> io::Result<bool> {
+        let (header, buffer) = self.read_doc(reader)?;
+        if tokens.is_empty() {
+            return Ok(false);
+        }
+        let Some(lists) = token_positions(&header, buffer.as_slice(), tokens)? else {
+            return Ok(false);
+        };
+        Ok(positions_within_gap(&lists, max_gap))
+    }
+
+    /// Reads one document (header, entries, position data and any term section).
+    fn read_doc<R: io::Read>(&self, reader: &mut R) -> io::Result<(DocHeader, SmartPage)> {
         let header = DocHeader::from_reader(reader)?;
-        let entry_size = header.entry_count * 16;
-        let total_size = entry_size + header.data_size;
+        let total_size = header.entry_count * 16 + header.data_size + header.terms_size;
         let mut buffer = self.pool.get(total_size); // assumming we got the values.
         buffer.resize_uninit(total_size);
         reader.read_exact(&mut buffer.buf)?;
+        Ok((header, buffer))
+    }
+}
 
-        let mut table = HashMap::with_capacity(header.entry_count);
-        for i in 0..header.entry_count {
-            let start = i * 16;
-            let key = u64::from_le_bytes(buffer.buf[start..start + 8].try_into().unwrap());
-            let offset = u64::from_le_bytes(buffer.buf[start + 8..start + 16].try_into().unwrap());
-            table.insert(key, offset);
-        }
-        for &token in tokens {
-            if !table.contains_key(&token) {
-                return Ok(false);
+/// Position lists of `tokens` in the document, in query order, or `None` when one of them
+/// does not occur at all. Lists are ascending, as `DocWriter` records them in token order.
+fn token_positions(
+    header: &DocHeader,
+    buffer: &[u8],
+    tokens: &[u64],
+) -> io::Result<Option<Vec<Vec<u32>>>> {
+    let entry_size = header.entry_count * 16;
+    let mut table = HashMap::with_capacity(header.entry_count);
+    for i in 0..header.entry_count {
+        let start = i * 16;
+        let key = u64::from_le_bytes(buffer[start..start + 8].try_into().unwrap());
+        let offset = u64::from_le_bytes(buffer[start + 8..start + 16].try_into().unwrap());
+        table.insert(key, offset);
+    }
+    let mut lists = Vec::with_capacity(tokens.len());
+    for token in tokens {
+        let Some(&offset) = table.get(token) else {
+            return Ok(None);
+        };
+        let size_start = entry_size + offset as usize;
+        let size_end = size_start + 4;
+        let buff_len = u32::from_le_bytes(buffer[size_start..size_end].try_into().unwrap());
+        let width = buffer[size_end];
+        let buff_start = size_end + 1;
+        let buff_end = buff_start + buff_len as usize;
+        lists.push(decode_values_slice::<u32>(
+            &buffer[buff_start..buff_end],
+            width,
+        )?);
+    }
+    Ok(Some(lists))
+}
+
+/// Returns every position where `tokens` occur consecutively in the document.
+fn match_starts(header: &DocHeader, buffer: &[u8], tokens: &[u64]) -> io::Result<Vec<u32>> {
+    let Some(lists) = token_positions(header, buffer, tokens)? else {
+        return Ok(Vec::new());
+    };
+    let sets = lists
+        .into_iter()
+        .map(|list| list.into_iter().collect::<HashSet<_>>())
+        .collect::<Vec<_>>();
+
+    let mut starts = sets[0]
+        .iter()
+        .copied()
+        .filter(|val| (1..sets.len()).all(|i| sets[i].contains(&(val + i as u32))))
+        .collect::<Vec<_>>();
+    starts.sort_unstable();
+    Ok(starts)
+}
+
+/// Whether some choice of one position per list is increasing with at most `max_gap`
+/// positions between neighbours.
+///
+/// Keeps, per list, every position reachable from a chain over the lists before it; taking
+/// the earliest position greedily is not enough, since a later one can reach further.
+fn positions_within_gap(lists: &[Vec<u32>], max_gap: u32) -> bool {
+    let Some((first, rest)) = lists.split_first() else {
+        return false;
+    };
+    let mut reachable = first.clone();
+    for list in rest {
+        let mut next = Vec::new();
+        // latest reachable position before `pos`; both lists ascend, so it only moves forward.
+        let (mut j, mut latest) = (0, None);
+        for &pos in list {
+            while j < reachable.len() && reachable[j] < pos {
+                latest = Some(reachable[j]);
+                j += 1;
             }
+            if latest.is_some_and(|prev| pos - prev - 1 <= max_gap) {
+                next.push(pos);
+            }
+        }
+        if next.is_empty() {
+            return false;
         }
+        reachable = next;
+    }
+    true
+}
 
-        let sets = tokens
-            .iter()
-            .map(|value| -> io::Result<HashSet<u32>> {
-                let offset = table
-                    .get(value)
-                    .ok_or(io::Error::new(io::ErrorKind::NotFound, "Not Found"))?;
-                let size_start = entry_size + (*offset as usize);
-                let size_end = size_start + 4;
-                let buff_len =
-                    u32::from_le_bytes(buffer.buf[size_start..size_end].try_into().unwrap());
-                let width = buffer.buf[size_end];
-                let buff_start = size_end + 1;
-                let buff_end = buff_start + buff_len as usize;
-                println!(
-                    "Decoding token {} at offset {}: size {}, width {}, buffer [{}..{}]",
-                    value, offset, buff_len, width, buff_start, buff_end
-                );
-                let decoded_values =
-                    decode_values::<u32>(&buffer.buf[buff_start..buff_end], width)?;
-                let result = decoded_values.into_iter().collect::<HashSet<_>>();
-                Ok(result)
-            })
-            .collect::<io::Result<Vec<_>>>()?;
-
-        let result = sets[0]
-            .iter()
-            .any(|val| (1..sets.len()).all(|i| sets[i].contains(&(val + i as u32))));
-        Ok(result)
+/// Splits a `[len: u32][utf-8 bytes]` term section into its terms.
+fn decode_terms(mut section: &[u8]) -> io::Result<Vec<&str>> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let mut terms = Vec::new();
+    while !section.is_empty() {
+        if section.len() < 4 {
+            return Err(invalid("truncated term length"));
+        }
+        let len = u32::from_le_bytes(section[0..4].try_into().unwrap()) as usize;
+        let bytes = section
+            .get(4..4 + len)
+            .ok_or_else(|| invalid("truncated term"))?;
+        terms.push(std::str::from_utf8(bytes).map_err(|_| invalid("term is not utf-8"))?);
+        section = &section[4 + len..];
     }
+    Ok(terms)
 }
 
 #[cfg(test)]
@@ -207,6 +352,39 @@ mod tests {
         run_search_test(&[10, 20, 10, 30], &[10, 30], true);
     }
 
+    fn phrase(doc_tokens: &[u64], search_tokens: &[u64], max_gap: u32) -> bool {
+        let mut buffer = Vec::new();
+        DocWriter.write(doc_tokens, &mut buffer).unwrap();
+        let reader = DocReader::new(SmartBufferPool::new(1 << 20));
+        reader
+            .phrase_search(&mut Cursor::new(buffer), search_tokens, max_gap)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_phrase_search_exact_phrase() {
+        assert!(phrase(&[10, 20, 30, 40], &[20, 30], 0));
+        assert!(!phrase(&[10, 20, 30, 40], &[20, 40], 0));
+        assert!(!phrase(&[10, 20, 30, 40], &[30, 20], 0));
+        assert!(!phrase(&[10, 20, 30], &[], 0));
+    }
+
+    #[test]
+    fn test_phrase_search_within_gap() {
+        // 10 .. 40 has two tokens between them.
+        assert!(phrase(&[10, 20, 30, 40], &[10, 40], 2));
+        assert!(phrase(&[10, 20, 30, 40], &[10, 30, 40], 2));
+        // only the later 20 leaves 30 within reach.
+        assert!(phrase(&[10, 20, 1, 20, 2, 3, 30], &[10, 20, 30], 2));
+    }
+
+    #[test]
+    fn test_phrase_search_beyond_gap() {
+        assert!(!phrase(&[10, 20, 30, 40], &[10, 40], 1));
+        assert!(!phrase(&[10, 1, 2, 3, 20], &[10, 20], 2));
+        assert!(!phrase(&[10, 20, 30, 40], &[10, 99], 5));
+    }
+
     // --- Edge Case Tests ---
     #[test]
     fn test_empty_document_is_an_error() {
@@ -232,7 +410,7 @@ mod tests {
 
     #[test]
     fn test_search_with_empty_tokens() {
-        // Searching for nothing should trivially be true.
+        // Searching for nothing never matches.
         run_search_test(&[10, 20, 30], &[], false);
     }
 
@@ -241,6 +419,44 @@ mod tests {
         run_search_test(&[], &[], false);
     }
 
+    #[test]
+    fn test_empty_document_is_valid() {
+        // an empty document is a bare header, and reading it leaves the next one intact.
+        let mut buffer = Vec::new();
+        assert_eq!(DocWriter.write(&[], &mut buffer).unwrap(), DOC_HEADER_SIZE);
+        DocWriter.write(&[10, 20], &mut buffer).unwrap();
+        assert_eq!(&buffer[..6], DOC_MAGIC);
+
+        let reader = DocReader::new(SmartBufferPool::new(1 << 20));
+        let mut cursor = Cursor::new(buffer);
+        assert!(!reader.search(&mut cursor, &[10]).unwrap());
+        assert!(reader.search(&mut cursor, &[10, 20]).unwrap());
+    }
+
+    #[test]
+    fn test_empty_string_token() {
+        use crate::encoding::strings::common::hash_string;
+        let empty = hash_string("");
+        let mut buffer = Vec::new();
+        DocWriter
+            .write_with_terms(&[empty], &[""], &mut buffer)
+            .unwrap();
+        DocWriter
+            .write_with_terms::<&str>(&[], &[], &mut buffer)
+            .unwrap();
+
+        let reader = DocReader::new(SmartBufferPool::new(1 << 20));
+        let mut cursor = Cursor::new(&buffer);
+        assert!(reader.search_exact(&mut cursor, &[empty], &[""]).unwrap());
+        // the empty document still counts as written with (zero) terms.
+        assert!(!reader.search_exact(&mut cursor, &[empty], &[""]).unwrap());
+
+        let mut cursor = Cursor::new(&buffer);
+        assert!(!reader.search(&mut cursor, &[]).unwrap());
+        assert!(!reader.search(&mut cursor, &[empty]).unwrap());
+        assert_eq!(cursor.position() as usize, buffer.len());
+    }
+
     #[test]
     fn test_single_token_document_found() {
         run_search_test(&[100], &[100], true);
@@ -298,4 +514,80 @@ mod tests {
         assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
         assert_eq!(err.to_string(), "unsupported version: 99");
     }
+
+    // --- Exact Term Tests ---
+
+    /// Hashes every term to the same value, so any two words collide.
+    struct CollidingTokenizer;
+
+    impl crate::encoding::strings::tokenizer::Tokenizer for CollidingTokenizer {
+        fn tokenize(&self, text: &str) -> Vec<u64> {
+            self.terms(text).iter().map(|_| 7).collect()
+        }
+
+        fn terms(&self, text: &str) -> Vec<String> {
+            text.split_whitespace().map(str::to_string).collect()
+        }
+    }
+
+    fn write_exact(text: &str) -> Vec<u8> {
+        use crate::encoding::strings::tokenizer::Tokenizer;
+        let tokenizer = CollidingTokenizer;
+        let mut buffer = Vec::new();
+        DocWriter
+            .write_with_terms(
+                &tokenizer.tokenize(text),
+                &tokenizer.terms(text),
+                &mut buffer,
+            )
+            .unwrap();
+        buffer
+    }
+
+    fn search_both(doc: &[u8], query: &str) -> (bool, bool) {
+        use crate::encoding::strings::tokenizer::Tokenizer;
+        let tokenizer = CollidingTokenizer;
+        let reader = DocReader::new(SmartBufferPool::new(1 << 20));
+        let tokens = tokenizer.tokenize(query);
+        let terms = tokenizer.terms(query);
+        let hashed = reader.search(&mut Cursor::new(doc), &tokens).unwrap();
+        let exact = reader
+            .search_exact(&mut Cursor::new(doc), &tokens, &terms)
+            .unwrap();
+        (hashed, exact)
+    }
+
+    #[test]
+    fn test_exact_search_rejects_collisions() {
+        let doc = write_exact("apple pie with cream");
+
+        // "banana" and "apple" collide, so the hash-only search is fooled.
+        assert_eq!(search_both(&doc, "banana pie"), (true, false));
+        assert_eq!(search_both(&doc, "apple tart"), (true, false));
+        assert_eq!(search_both(&doc, "apple pie"), (true, true));
+        assert_eq!(search_both(&doc, "with cream"), (true, true));
+        assert_eq!(search_both(&doc, "cream with"), (true, false));
+    }
+
+    #[test]
+    fn test_exact_search_requires_terms() {
+        let mut buffer = Vec::new();
+        DocWriter.write(&[7, 7], &mut buffer).unwrap();
+        let reader = DocReader::new(SmartBufferPool::new(1 << 20));
+        let err = reader
+            .search_exact(&mut Cursor::new(buffer), &[7], &["apple"])
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_terms_section_is_consumed() {
+        // a document with terms followed by a plain one must stay readable in sequence.
+        let mut buffer = write_exact("apple pie");
+        DocWriter.write(&[1, 2], &mut buffer).unwrap();
+        let reader = DocReader::new(SmartBufferPool::new(1 << 20));
+        let mut cursor = Cursor::new(buffer);
+        assert!(reader.search(&mut cursor, &[7, 7]).unwrap());
+        assert!(reader.search(&mut cursor, &[1, 2]).unwrap());
+    }
 }
@@ -1,6 +1,80 @@
-use crate::encoding::{StreamingEncoder, strings::doc_writer::DocWriter};
-use fastbloom::BloomFilter;
-use std::cell::RefCell;
-use std::io;
-use xxhash_rust::xxh3;
-use zerocopy_derive::{FromBytes, Immutable, IntoBytes, KnownLayout};
+use crate::buffers::smart_pool::SmartBufferPool;
+use crate::encoding::strings::doc_reader::DocReader;
+use crate::encoding::strings::doc_stream_writer::{DOC_STREAM_MAGIC, HEADER_SIZE};
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// Trailer `DocStreamWriter::end_stream` writes after the documents:
+///
+/// `[doc offsets][bloom filter: u64 words][header]`
+///
+/// The 32-byte header holds the magic, the filter length in words at bytes 6..10 and the
+/// offset table size in bytes at 14..18. A filter length of 0 means the writer was built
+/// `with_bloom_filter(false)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DocStreamTrailer {
+    pub filter_words: usize,
+    pub doc_offsets_bytes: usize,
+    /// Bytes of documents before the trailer.
+    pub docs_len: u64,
+}
+
+impl DocStreamTrailer {
+    /// Reads the trailer from the end of `reader`, which must end where the stream does.
+    pub fn read_from_end<R: Read + Seek>(reader: &mut R) -> io::Result<Self> {
+        let end = reader.seek(SeekFrom::End(0))?;
+        if end < HEADER_SIZE as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("doc stream of {end} bytes is shorter than its header"),
+            ));
+        }
+        let mut header = [0u8; HEADER_SIZE];
+        reader.seek(SeekFrom::End(-(HEADER_SIZE as i64)))?;
+        reader.read_exact(&mut header)?;
+        if &header[0..6] != DOC_STREAM_MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a doc stream: bad trailer magic",
+            ));
+        }
+
+        let filter_words = u32::from_le_bytes(header[6..10].try_into().unwrap()) as usize;
+        let doc_offsets_bytes = u32::from_le_bytes(header[14..18].try_into().unwrap()) as usize;
+        let trailer_len = (HEADER_SIZE + doc_offsets_bytes + filter_words * 8) as u64;
+        let docs_len = end.checked_sub(trailer_len).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("doc stream trailer of {trailer_len} bytes is longer than the stream"),
+            )
+        })?;
+        Ok(Self {
+            filter_words,
+            doc_offsets_bytes,
+            docs_len,
+        })
+    }
+
+    pub fn has_filter(&self) -> bool {
+        self.filter_words > 0
+    }
+}
+
+/// Reports whether any document of a stream written by `DocStreamWriter` contains
+/// `tokens` consecutively. Documents are searched in order, so this works with or without
+/// a bloom filter; the filter, if any, is not consulted.
+pub fn stream_contains<R: Read + Seek>(
+    reader: &mut R,
+    pool: SmartBufferPool,
+    tokens: &[u64],
+) -> io::Result<bool> {
+    let trailer = DocStreamTrailer::read_from_end(reader)?;
+    reader.seek(SeekFrom::Start(0))?;
+    let mut docs = io::BufReader::new(reader.take(trailer.docs_len));
+    let doc_reader = DocReader::new(pool);
+    while !io::BufRead::fill_buf(&mut docs)?.is_empty() {
+        if doc_reader.search(&mut docs, tokens)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
@@ -1,6 +1,10 @@
 use crate::encoding::{
     StreamingEncoder,
-    strings::{common::process_string, doc_writer::DocWriter},
+    strings::{
+        common::{FIELD_BOUNDARY_TERM, hash_string},
+        doc_writer::DocWriter,
+        tokenizer::{Tokenizer, WhitespaceTokenizer},
+    },
 };
 use fastbloom::BloomFilter;
 use std::cell::RefCell;
@@ -8,8 +12,8 @@ use std::io;
 use xxhash_rust::xxh3;
 use zerocopy_derive::{FromBytes, Immutable, IntoBytes, KnownLayout};
 
-const HEADER_SIZE: usize = 32;
-const DOC_STREAM_MAGIC: &[u8; 6] = b"DOCST1";
+pub(crate) const HEADER_SIZE: usize = 32;
+pub(crate) const DOC_STREAM_MAGIC: &[u8; 6] = b"DOCST1";
 const SIZE_DOC_OFFSET: usize = core::mem::size_of::<DocOffset>();
 
 #[derive(Debug, Clone, Copy)]
@@ -33,12 +37,15 @@ struct DocOffset {
 
 struct DocState {
     doc_offsets: Vec<DocOffset>,
-    filter: BloomFilter,
+    /// `None` when the writer was built `with_bloom_filter(false)`.
+    filter: Option<BloomFilter>,
 }
 
 pub struct DocStreamWriter {
     state: RefCell<DocState>,
     doc_writer: DocWriter,
+    tokenizer: Box<dyn Tokenizer + Send>,
+    exact_terms: bool,
 }
 
 impl Default for DocStreamWriter {
@@ -46,14 +53,99 @@ impl Default for DocStreamWriter {
         let filter = BloomFilter::with_num_bits(1 << 20).expected_items(2 << 20);
         Self {
             state: RefCell::new(DocState {
-                filter,
+                filter: Some(filter),
                 doc_offsets: vec![],
             }),
             doc_writer: DocWriter,
+            tokenizer: Box::new(WhitespaceTokenizer),
+            exact_terms: false,
         }
     }
 }
 
+impl DocStreamWriter {
+    /// Replaces the tokenizer used to split each document before hashing.
+    pub fn with_tokenizer(mut self, tokenizer: Box<dyn Tokenizer + Send>) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    /// Adds a document's tokens to the bloom filter under a single borrow.
+    ///
+    /// Repeated tokens (common in large documents) are collapsed first, so each distinct
+    /// token is hashed and inserted once. Membership is the same as inserting one by one.
+    fn insert_tokens(&self, tokens: &[u64]) {
+        let mut unique = tokens.to_vec();
+        unique.sort_unstable();
+        unique.dedup();
+
+        let Some(filter) = &mut self.state.borrow_mut().filter else {
+            return;
+        };
+        for token in &unique {
+            let hash = filter.source_hash(token);
+            filter.insert_hash(hash);
+        }
+    }
+
+    /// Indexes several fields as a single document.
+    ///
+    /// Fields are joined with `FIELD_BOUNDARY_TERM`, which no tokenizer produces, so a
+    /// phrase can match inside any one field but never across two of them.
+    pub fn encode_fields(&self, fields: &[&str], writer: &mut dyn io::Write) -> io::Result<()> {
+        let mut tokens = Vec::new();
+        let mut terms = Vec::new();
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                tokens.push(hash_string(FIELD_BOUNDARY_TERM));
+                if self.exact_terms {
+                    terms.push(FIELD_BOUNDARY_TERM.to_string());
+                }
+            }
+            tokens.extend(self.tokenizer.tokenize(field));
+            if self.exact_terms {
+                terms.extend(self.tokenizer.terms(field));
+            }
+        }
+        self.write_doc(&tokens, &terms, writer)
+    }
+
+    /// Writes one document and records its tokens in the bloom filter. `terms` is only
+    /// used when exact terms are enabled.
+    fn write_doc(
+        &self,
+        tokens: &[u64],
+        terms: &[String],
+        writer: &mut dyn io::Write,
+    ) -> io::Result<()> {
+        if self.exact_terms {
+            self.doc_writer.write_with_terms(tokens, terms, writer)?;
+        } else {
+            self.doc_writer.write_dyn(tokens, writer)?;
+        }
+        self.insert_tokens(tokens);
+        Ok(())
+    }
+
+    /// Whether to keep the bloom filter over every document's tokens, on by default. The
+    /// filter is a fixed 128 KiB however little was written, so streams of a few small
+    /// documents are better off without it: the trailer then records a filter length of 0
+    /// and readers search the documents themselves.
+    pub fn with_bloom_filter(self, enabled: bool) -> Self {
+        if !enabled {
+            self.state.borrow_mut().filter = None;
+        }
+        self
+    }
+
+    /// Stores each document's original terms next to the token hashes so
+    /// `DocReader::search_exact` can reject hash collisions. Costs the term bytes per document.
+    pub fn with_exact_terms(mut self, enabled: bool) -> Self {
+        self.exact_terms = enabled;
+        self
+    }
+}
+
 impl StreamingEncoder<String> for DocStreamWriter {
     fn begin_stream(&self, writer: &mut dyn std::io::Write) -> std::io::Result<()> {
         Ok(())
@@ -65,13 +157,13 @@ impl StreamingEncoder<String> for DocStreamWriter {
         _: usize,
         writer: &mut dyn std::io::Write,
     ) -> std::io::Result<()> {
-        let tokens = process_string(v);
-        self.doc_writer.write_dyn(&tokens, writer)?;
-        let mut state = self.state.borrow_mut();
-        tokens.iter().for_each(|val| {
-            state.filter.insert(val);
-        });
-        Ok(())
+        let tokens = self.tokenizer.tokenize(v);
+        let terms = if self.exact_terms {
+            self.tokenizer.terms(v)
+        } else {
+            Vec::new()
+        };
+        self.write_doc(&tokens, &terms, writer)
     }
 
     fn end_stream(&self, writer: &mut dyn std::io::Write) -> std::io::Result<()> {
@@ -80,7 +172,7 @@ impl StreamingEncoder<String> for DocStreamWriter {
         let offset_size = (state.doc_offsets.len() * SIZE_DOC_OFFSET) as u32;
         encode_doc_offset(writer, &state.doc_offsets)?;
 
-        let filter_slice = state.filter.as_slice();
+        let filter_slice = state.filter.as_ref().map_or(&[][..], |f| f.as_slice());
         let filter_len = filter_slice.len() as u32;
         encode_vec_64(writer, filter_slice)?;
 
@@ -179,6 +271,8 @@ fn decode_doc_offset(buffer: &[u8]) -> io::Result<Vec<DocOffset>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::buffers::smart_pool::SmartBufferPool;
+    use crate::encoding::strings::doc_reader::DocReader;
 
     #[test]
     fn test_endian_encoding() {
@@ -208,6 +302,63 @@ mod tests {
         assert_eq!(cursor1.get_ref(), cursor2.get_ref());
     }
 
+    fn search(doc: &[u8], phrase: &str) -> bool {
+        let reader = DocReader::new(SmartBufferPool::new(1 << 20));
+        let tokens = WhitespaceTokenizer.tokenize(phrase);
+        reader.search(&mut io::Cursor::new(doc), &tokens).unwrap()
+    }
+
+    #[test]
+    fn test_encode_value_splits_on_any_whitespace() {
+        let writer = DocStreamWriter::default();
+        let mut doc = Vec::new();
+        writer
+            .encode_value(&"a\tb\nc".to_string(), 0, &mut doc)
+            .unwrap();
+        assert!(search(&doc, "a b c"));
+        assert!(search(&doc, "b c"));
+    }
+
+    #[test]
+    fn test_phrase_does_not_span_fields() {
+        let writer = DocStreamWriter::default();
+        let mut doc = Vec::new();
+        writer
+            .encode_fields(&["senior data engineer", "remote friendly"], &mut doc)
+            .unwrap();
+        assert!(search(&doc, "data engineer"));
+        assert!(search(&doc, "remote friendly"));
+        assert!(!search(&doc, "engineer remote"));
+    }
+
+    #[test]
+    fn test_batch_insert_matches_per_token_insert() {
+        let tokenizer = WhitespaceTokenizer;
+        let docs = [
+            "the quick brown fox jumps over the lazy dog the end",
+            "a a a a b b c",
+            "",
+        ];
+
+        let batched = DocStreamWriter::default();
+        // start from a copy of the empty filter so both share the same hasher seed.
+        let mut per_token = batched.state.borrow().filter.clone().unwrap();
+        for doc in docs {
+            let tokens = tokenizer.tokenize(doc);
+            batched.insert_tokens(&tokens);
+            tokens.iter().for_each(|token| {
+                per_token.insert(token);
+            });
+        }
+
+        let state = batched.state.borrow();
+        let filter = state.filter.as_ref().unwrap();
+        assert_eq!(filter.as_slice(), per_token.as_slice());
+        for token in tokenizer.tokenize("quick dog c missing") {
+            assert_eq!(filter.contains(&token), per_token.contains(&token));
+        }
+    }
+
     #[test]
     fn test_encode_decode_values() {
         let doc_offsets = vec![
@@ -240,4 +391,36 @@ mod tests {
             assert_eq!(decoded[i].size, doc_offsets[i].size);
         }
     }
+
+    #[test]
+    fn test_stream_without_bloom_filter() {
+        use crate::encoding::strings::doc_stream_reader::{DocStreamTrailer, stream_contains};
+
+        let encode = |writer: DocStreamWriter| {
+            let mut out = Vec::new();
+            writer.begin_stream(&mut out).unwrap();
+            writer
+                .encode_value(&"red green blue".to_string(), 0, &mut out)
+                .unwrap();
+            writer.end_stream(&mut out).unwrap();
+            out
+        };
+        let with_filter = encode(DocStreamWriter::default());
+        let without = encode(DocStreamWriter::default().with_bloom_filter(false));
+        assert!(with_filter.len() > 128 * 1024);
+        assert!(without.len() < 1024, "{} bytes", without.len());
+
+        let mut cursor = io::Cursor::new(without);
+        let trailer = DocStreamTrailer::read_from_end(&mut cursor).unwrap();
+        assert!(!trailer.has_filter());
+
+        let contains = |cursor: &mut io::Cursor<Vec<u8>>, phrase: &str| {
+            let tokens = WhitespaceTokenizer.tokenize(phrase);
+            stream_contains(cursor, SmartBufferPool::new(1 << 20), &tokens).unwrap()
+        };
+        assert!(contains(&mut cursor, "green blue"));
+        assert!(contains(&mut cursor, "red"));
+        assert!(!contains(&mut cursor, "blue red"));
+        assert!(!contains(&mut cursor, "yellow"));
+    }
 }
@@ -1,4 +1,5 @@
 use crate::encoding::bitpack::v1::writer::encode_values;
+use crate::encoding::strings::common::DOC_FLAG_TERMS;
 use crate::encoding::strings::common::DOC_HEADER_SIZE;
 use crate::encoding::strings::common::DOC_MAGIC;
 use crate::encoding::strings::common::DOC_VERSION;
@@ -15,11 +16,44 @@ impl Default for DocWriter {
 }
 
 impl DocWriter {
+    /// Writes one document. Empty `tokens` (e.g. an empty string) still get a header, with
+    /// no entries and no data, so every row has a document a reader can step over.
     pub fn write_dyn(&self, tokens: &[u64], writer: &mut dyn Write) -> io::Result<usize> {
-        if tokens.is_empty() {
-            return Ok(0);
+        self.write_doc(tokens, None, writer)
+    }
+
+    /// Like `write_dyn`, but also stores the original term of every token so readers can
+    /// rule out hash collisions with `DocReader::search_exact`.
+    ///
+    /// The terms follow the position data as `[len: u32][utf-8 bytes]` per token, in token
+    /// order. Header byte 15 carries `DOC_FLAG_TERMS` and bytes 16..20 the section size.
+    pub fn write_with_terms<S: AsRef<str>>(
+        &self,
+        tokens: &[u64],
+        terms: &[S],
+        writer: &mut dyn Write,
+    ) -> io::Result<usize> {
+        if tokens.len() != terms.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} terms for {} tokens", terms.len(), tokens.len()),
+            ));
         }
+        let mut section = Vec::new();
+        for term in terms {
+            let term = term.as_ref().as_bytes();
+            section.extend_from_slice(&(term.len() as u32).to_le_bytes());
+            section.extend_from_slice(term);
+        }
+        self.write_doc(tokens, Some(&section), writer)
+    }
 
+    fn write_doc(
+        &self,
+        tokens: &[u64],
+        terms: Option<&[u8]>,
+        writer: &mut dyn Write,
+    ) -> io::Result<usize> {
         // Step 1: Collect positions
         let mut table = HashMap::<u64, Vec<u32>>::new();
         for (pos, token) in tokens.iter().enumerate() {
@@ -57,6 +91,11 @@ impl DocWriter {
         header[6] = DOC_VERSION;
         header[7..11].copy_from_slice(&(data_size as u32).to_le_bytes()); // data size
         header[11..15].copy_from_slice(&(entry_count as u32).to_le_bytes()); // entry count
+        let terms_size = terms.map_or(0, |t| t.len());
+        if terms.is_some() {
+            header[15] = DOC_FLAG_TERMS;
+            header[16..20].copy_from_slice(&(terms_size as u32).to_le_bytes()); // terms size
+        }
         writer.write_all(&header)?;
 
         let mut current_offset = 0u64; // offset relative to after entry_count  
@@ -70,7 +109,11 @@ impl DocWriter {
             writer.write_all(&data)?;
         }
 
-        Ok(data_size + entries_size + DOC_HEADER_SIZE)
+        if let Some(terms) = terms {
+            writer.write_all(terms)?;
+        }
+
+        Ok(data_size + entries_size + terms_size + DOC_HEADER_SIZE)
     }
 
     pub fn write<W>(&self, tokens: &[u64], writer: &mut W) -> io::Result<usize>
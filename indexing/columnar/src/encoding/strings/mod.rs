@@ -4,5 +4,6 @@ pub mod doc_reader;
 pub mod doc_stream_reader;
 pub mod doc_stream_writer;
 pub mod doc_writer;
+pub mod reader;
 pub mod tokenizer;
 pub mod writer;
@@ -0,0 +1,132 @@
+use crate::encoding::streaming::StreamingDecoder;
+use std::io::{self, Read};
+
+/// Reads the `[len: u32][utf-8 bytes]` records written by `StringWriter`.
+///
+/// Every record is validated as UTF-8; corrupt or truncated records surface as
+/// `InvalidData` errors instead of panicking.
+#[derive(Default)]
+pub struct StringReader {
+    /// Index of the next record, used in error messages.
+    record: usize,
+}
+
+impl StreamingDecoder<String> for StringReader {
+    fn begin_stream(&mut self, _reader: &mut dyn Read) -> io::Result<()> {
+        self.record = 0;
+        Ok(())
+    }
+
+    fn decode_next(&mut self, reader: &mut dyn Read) -> io::Result<Option<String>> {
+        let mut len_buf = [0u8; 4];
+        let read = read_full(reader, &mut len_buf)?;
+        if read == 0 {
+            return Ok(None);
+        }
+        if read < len_buf.len() {
+            return Err(self.invalid(format!("truncated length ({read} of 4 bytes)")));
+        }
+
+        let len = u32::from_le_bytes(len_buf) as usize;
+        let mut bytes = Vec::new();
+        reader.take(len as u64).read_to_end(&mut bytes)?;
+        if bytes.len() < len {
+            return Err(self.invalid(format!("truncated body ({} of {len} bytes)", bytes.len())));
+        }
+
+        let value = String::from_utf8(bytes).map_err(|e| self.invalid(e.to_string()))?;
+        self.record += 1;
+        Ok(Some(value))
+    }
+
+    fn end_stream(&mut self, _reader: &mut dyn Read) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl StringReader {
+    fn invalid(&self, reason: String) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("string record {}: {reason}", self.record),
+        )
+    }
+}
+
+/// Reads until `buf` is full or the reader is exhausted, returning the bytes read.
+fn read_full(reader: &mut dyn Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::streaming::StreamingEncoder;
+    use crate::encoding::strings::writer::StringWriter;
+
+    fn encode(values: &[&str]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        StringWriter.begin_stream(&mut bytes).unwrap();
+        for (row, v) in values.iter().enumerate() {
+            StringWriter
+                .encode_value(&v.to_string(), row, &mut bytes)
+                .unwrap();
+        }
+        StringWriter.end_stream(&mut bytes).unwrap();
+        bytes
+    }
+
+    fn decode_all(bytes: &[u8]) -> io::Result<Vec<String>> {
+        let mut reader = bytes;
+        let mut decoder = StringReader::default();
+        decoder.begin_stream(&mut reader)?;
+        let mut out = Vec::new();
+        while let Some(v) = decoder.decode_next(&mut reader)? {
+            out.push(v);
+        }
+        decoder.end_stream(&mut reader)?;
+        Ok(out)
+    }
+
+    #[test]
+    fn test_roundtrip() -> io::Result<()> {
+        let values = ["hello", "", "héllo wörld", "日本語"];
+        assert_eq!(decode_all(&encode(&values))?, values);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_utf8_is_reported() {
+        let mut bytes = encode(&["ok"]);
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+        bytes.extend_from_slice(&[0xC3, 0x28]);
+
+        let err = decode_all(&bytes).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let msg = err.to_string();
+        assert!(msg.contains("string record 1"), "{msg}");
+        assert!(msg.contains("invalid utf-8"), "{msg}");
+    }
+
+    #[test]
+    fn test_truncated_record_is_reported() {
+        let mut bytes = encode(&["complete", "cut short"]);
+        bytes.truncate(bytes.len() - 3);
+        let err = decode_all(&bytes).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("truncated body (6 of 9 bytes)"));
+
+        let err = decode_all(&[1, 0]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("truncated length"));
+    }
+}
@@ -1,3 +1,203 @@
+use crate::encoding::strings::common::{hash_string, process_string};
+use unicode_normalization::UnicodeNormalization;
+
 pub trait Tokenizer {
     fn tokenize(&self, text: &str) -> Vec<u64>;
+    /// The terms behind `tokenize`, one per token and in the same order. Stored alongside
+    /// the hashes when a document keeps its exact terms.
+    ///
+    /// Returns no terms by default. Tokenizers used with `DocStreamWriter::with_exact_terms`
+    /// (and so `DocReader::search_exact`) must override it; writing exact terms with the
+    /// default fails with a term/token count mismatch.
+    fn terms(&self, _text: &str) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// Splits on runs of whitespace (spaces, tabs, newlines) without any normalization.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WhitespaceTokenizer;
+
+impl Tokenizer for WhitespaceTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<u64> {
+        process_string(text)
+    }
+
+    fn terms(&self, text: &str) -> Vec<String> {
+        text.split_whitespace().map(str::to_string).collect()
+    }
+}
+
+/// Tokenizer for free text: optionally NFC-normalizes, lowercases and treats
+/// punctuation as a separator. Empty tokens are always dropped.
+#[derive(Debug, Clone, Copy)]
+pub struct TextTokenizer {
+    lowercase: bool,
+    strip_punctuation: bool,
+    normalize_unicode: bool,
+}
+
+impl Default for TextTokenizer {
+    fn default() -> Self {
+        Self {
+            lowercase: true,
+            strip_punctuation: true,
+            normalize_unicode: false,
+        }
+    }
+}
+
+impl TextTokenizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn lowercase(mut self, enabled: bool) -> Self {
+        self.lowercase = enabled;
+        self
+    }
+
+    pub fn strip_punctuation(mut self, enabled: bool) -> Self {
+        self.strip_punctuation = enabled;
+        self
+    }
+
+    pub fn normalize_unicode(mut self, enabled: bool) -> Self {
+        self.normalize_unicode = enabled;
+        self
+    }
+
+    /// Returns the normalized terms before hashing.
+    pub fn terms(&self, text: &str) -> Vec<String> {
+        let mut text = if self.normalize_unicode {
+            text.nfc().collect::<String>()
+        } else {
+            text.to_string()
+        };
+        if self.lowercase {
+            text = text.to_lowercase();
+        }
+
+        let strip = self.strip_punctuation;
+        text.split(|c: char| c.is_whitespace() || (strip && !c.is_alphanumeric()))
+            .filter(|term| !term.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+impl Tokenizer for TextTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<u64> {
+        TextTokenizer::terms(self, text)
+            .iter()
+            .map(|t| hash_string(t))
+            .collect()
+    }
+
+    fn terms(&self, text: &str) -> Vec<String> {
+        TextTokenizer::terms(self, text)
+    }
+}
+
+/// Character n-grams of every whitespace-separated word, so short fields such as titles
+/// match on fragments of a word. Words of at most `n` characters are kept whole.
+#[derive(Debug, Clone, Copy)]
+pub struct NgramTokenizer {
+    n: usize,
+}
+
+impl NgramTokenizer {
+    pub fn new(n: usize) -> Self {
+        Self { n: n.max(1) }
+    }
+
+    pub fn n(&self) -> usize {
+        self.n
+    }
+}
+
+impl Tokenizer for NgramTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<u64> {
+        self.terms(text).iter().map(|t| hash_string(t)).collect()
+    }
+
+    fn terms(&self, text: &str) -> Vec<String> {
+        let mut terms = Vec::new();
+        for word in text.split_whitespace() {
+            let chars: Vec<char> = word.chars().collect();
+            if chars.len() <= self.n {
+                terms.push(word.to_string());
+            } else {
+                terms.extend(chars.windows(self.n).map(|w| w.iter().collect::<String>()));
+            }
+        }
+        terms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_whitespace_runs() {
+        let tokenizer = WhitespaceTokenizer;
+        assert_eq!(tokenizer.terms("a\tb\nc"), vec!["a", "b", "c"]);
+        assert_eq!(tokenizer.tokenize("a\tb\nc").len(), 3);
+        assert_eq!(tokenizer.tokenize("  a   b "), tokenizer.tokenize("a b"));
+    }
+
+    #[test]
+    fn test_punctuation_and_case() {
+        let tokenizer = TextTokenizer::new();
+        assert_eq!(tokenizer.terms("Hello, World!!"), vec!["hello", "world"]);
+        assert_eq!(
+            tokenizer.terms("...leading,,, and trailing?!"),
+            vec!["leading", "and", "trailing"]
+        );
+    }
+
+    #[test]
+    fn test_extra_whitespace_is_dropped() {
+        let tokenizer = TextTokenizer::new();
+        assert_eq!(tokenizer.terms("  spaced   out  "), vec!["spaced", "out"]);
+        assert!(tokenizer.terms("  !! ,, ").is_empty());
+    }
+
+    #[test]
+    fn test_options_disabled() {
+        let tokenizer = TextTokenizer::new()
+            .lowercase(false)
+            .strip_punctuation(false);
+        assert_eq!(tokenizer.terms("Hello, World!!"), vec!["Hello,", "World!!"]);
+    }
+
+    #[test]
+    fn test_ngrams() {
+        let tokenizer = NgramTokenizer::new(3);
+        assert_eq!(tokenizer.terms("café ox"), vec!["caf", "afé", "ox"]);
+        assert_eq!(tokenizer.tokenize("café ox")[0], hash_string("caf"));
+        assert!(tokenizer.terms("   ").is_empty());
+    }
+
+    #[test]
+    fn test_terms_default_to_empty() {
+        struct HashOnly;
+        impl Tokenizer for HashOnly {
+            fn tokenize(&self, text: &str) -> Vec<u64> {
+                process_string(text)
+            }
+        }
+        assert_eq!(HashOnly.tokenize("a b").len(), 2);
+        assert!(HashOnly.terms("a b").is_empty());
+    }
+
+    #[test]
+    fn test_unicode_normalization() {
+        let tokenizer = TextTokenizer::new().normalize_unicode(true);
+        let composed = tokenizer.tokenize("caf\u{e9}");
+        let decomposed = tokenizer.tokenize("cafe\u{301}");
+        assert_eq!(composed, decomposed);
+        assert_eq!(composed, vec![hash_string("caf\u{e9}")]);
+    }
 }
@@ -1,15 +1,24 @@
 use crate::encoding::streaming::StreamingEncoder;
 use std::io::{self, Write};
+
+/// Writes each string as a `[len: u32][utf-8 bytes]` record, little endian.
 pub struct StringWriter;
 
-impl<String> StreamingEncoder<String> for StringWriter {
-    fn begin_stream(&self, writer: &mut dyn Write) -> io::Result<()> {
+impl StreamingEncoder<String> for StringWriter {
+    fn begin_stream(&self, _writer: &mut dyn Write) -> io::Result<()> {
         Ok(())
     }
     fn encode_value(&self, v: &String, _: usize, writer: &mut dyn Write) -> io::Result<()> {
-        Ok(())
+        let len = u32::try_from(v.len()).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("string of {} bytes exceeds the u32 record limit", v.len()),
+            )
+        })?;
+        writer.write_all(&len.to_le_bytes())?;
+        writer.write_all(v.as_bytes())
     }
-    fn end_stream(&self, writer: &mut dyn Write) -> io::Result<()> {
+    fn end_stream(&self, _writer: &mut dyn Write) -> io::Result<()> {
         Ok(())
     }
 }
@@ -0,0 +1,264 @@
+use std::fmt;
+use std::io;
+use std::sync::{Arc, Condvar, Mutex};
+
+/// Caps how many files a group of `StreamColumn`s may hold open at once.
+///
+/// Clones share the same budget. A column takes one permit for every file it may hold open
+/// at once (see `StreamColumn::with_fd_limiter`) before creating its file and gives them
+/// back when closed or dropped; when not enough are free, the open fails with `WouldBlock`
+/// until other columns release theirs. Columns hold their permits until they close, so
+/// waiting instead could deadlock a thread that opens more of them than the cap allows,
+/// and a thread opening several columns needs a cap of at least their total.
+#[derive(Clone)]
+pub struct FdLimiter {
+    inner: Arc<LimiterState>,
+}
+
+struct LimiterState {
+    max_open: usize,
+    available: Mutex<usize>,
+    released: Condvar,
+}
+
+impl FdLimiter {
+    /// Creates a limiter allowing at most `max_open` (minimum 1) open files.
+    pub fn new(max_open: usize) -> Self {
+        Self {
+            inner: Arc::new(LimiterState {
+                max_open: max_open.max(1),
+                available: Mutex::new(max_open.max(1)),
+                released: Condvar::new(),
+            }),
+        }
+    }
+
+    /// Blocks until a file may be opened. Only safe when the permits are released by other
+    /// threads; a thread still holding the last permit waits forever.
+    pub fn acquire(&self) -> FdPermit {
+        let mut available = self.inner.available.lock().unwrap();
+        while *available == 0 {
+            available = self.inner.released.wait(available).unwrap();
+        }
+        *available -= 1;
+        FdPermit {
+            inner: Arc::clone(&self.inner),
+            count: 1,
+        }
+    }
+
+    /// Returns a permit if one is free, without blocking.
+    pub fn try_acquire(&self) -> Option<FdPermit> {
+        self.try_acquire_many(1)
+    }
+
+    /// Returns one permit covering `count` files if that many are free, without blocking.
+    /// Takes all of them or none.
+    pub fn try_acquire_many(&self, count: usize) -> Option<FdPermit> {
+        let mut available = self.inner.available.lock().unwrap();
+        if *available < count {
+            return None;
+        }
+        *available -= count;
+        Some(FdPermit {
+            inner: Arc::clone(&self.inner),
+            count,
+        })
+    }
+
+    /// Like `try_acquire`, but reports a full limiter as a `WouldBlock` error.
+    pub fn try_permit(&self) -> io::Result<FdPermit> {
+        self.try_permits(1)
+    }
+
+    /// Like `try_acquire_many`, but reports too few free permits as a `WouldBlock` error.
+    pub fn try_permits(&self, count: usize) -> io::Result<FdPermit> {
+        self.try_acquire_many(count).ok_or_else(|| {
+            let message = match count {
+                1 => format!("all {} file permits are in use", self.inner.max_open),
+                _ => format!(
+                    "{count} file permits needed, but only {} of {} are free",
+                    self.available(),
+                    self.inner.max_open
+                ),
+            };
+            io::Error::new(io::ErrorKind::WouldBlock, message)
+        })
+    }
+
+    /// Number of files that may still be opened without blocking.
+    pub fn available(&self) -> usize {
+        *self.inner.available.lock().unwrap()
+    }
+}
+
+/// Some open files' share of an `FdLimiter`, released on drop.
+pub struct FdPermit {
+    inner: Arc<LimiterState>,
+    count: usize,
+}
+
+impl fmt::Debug for FdPermit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FdPermit")
+            .field("count", &self.count)
+            .finish()
+    }
+}
+
+impl FdPermit {
+    /// Number of files the permit covers.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl Drop for FdPermit {
+    fn drop(&mut self) {
+        let mut available = self.inner.available.lock().unwrap();
+        *available += self.count;
+        self.inner.released.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffers::smart_pool::SmartBufferPool;
+    use crate::encoding::{BitpackStreamWriter, FixedWidthStreamEncoder};
+    use crate::stream::StreamColumn;
+    use std::thread;
+
+    #[test]
+    fn test_try_acquire_respects_cap() {
+        let limiter = FdLimiter::new(2);
+        let a = limiter.try_acquire().unwrap();
+        let _b = limiter.try_acquire().unwrap();
+        assert!(limiter.try_acquire().is_none());
+        drop(a);
+        assert_eq!(limiter.available(), 1);
+        assert!(limiter.try_acquire().is_some());
+    }
+
+    #[test]
+    fn test_try_acquire_many_takes_all_or_none() {
+        let limiter = FdLimiter::new(3);
+        let _one = limiter.try_acquire().unwrap();
+        assert!(limiter.try_acquire_many(3).is_none());
+        assert_eq!(limiter.available(), 2);
+        let two = limiter.try_acquire_many(2).unwrap();
+        assert_eq!(two.count(), 2);
+        drop(two);
+        assert_eq!(limiter.available(), 2);
+    }
+
+    // callers that open one column per thread may wait on `acquire` instead; the columns
+    // here hold one file each, so a cap of 2 lets all 8 write in turn.
+    #[test]
+    fn test_acquire_waits_for_other_threads() {
+        let dir = tempfile::tempdir().unwrap();
+        let limiter = FdLimiter::new(2);
+        let pool = SmartBufferPool::default();
+
+        let handles = (0..8u64)
+            .map(|i| {
+                let path = dir.path().join(format!("col_{i}.bin"));
+                let (limiter, pool) = (limiter.clone(), pool.clone());
+                let temp_dir = dir.path().to_path_buf();
+                thread::spawn(move || -> std::io::Result<()> {
+                    // each thread holds one file, so waiting for another thread's permit is safe.
+                    let permit = limiter.acquire();
+                    let mut column = StreamColumn::<u64>::new(
+                        path,
+                        pool,
+                        Box::new(FixedWidthStreamEncoder),
+                        None,
+                        temp_dir,
+                    )?;
+                    for v in 0..100 {
+                        column.push(&(v * i))?;
+                    }
+                    column.close()?;
+                    drop(permit);
+                    Ok(())
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.join().unwrap().unwrap();
+        }
+
+        assert_eq!(limiter.available(), 2);
+        for i in 0..8u64 {
+            let bytes = std::fs::read(dir.path().join(format!("col_{i}.bin"))).unwrap();
+            assert_eq!(bytes.len(), 100 * 8);
+            assert_eq!(bytes[8..16], i.to_le_bytes());
+        }
+    }
+
+    #[test]
+    fn test_open_past_cap_fails_instead_of_blocking() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let limiter = FdLimiter::new(2);
+        let pool = SmartBufferPool::default();
+        let open = |i: u32| {
+            StreamColumn::<u64>::with_fd_limiter(
+                dir.path().join(format!("col_{i}.bin")),
+                pool.clone(),
+                Box::new(FixedWidthStreamEncoder),
+                None,
+                dir.path().to_path_buf(),
+                &limiter,
+            )
+        };
+
+        let first = open(0)?;
+        let _second = open(1)?;
+        let err = open(2).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::WouldBlock);
+        assert_eq!(err.to_string(), "all 2 file permits are in use");
+        // nothing was created for the column that was refused.
+        assert!(!dir.path().join("col_2.bin").exists());
+
+        first.close()?;
+        open(2)?.close()
+    }
+
+    #[test]
+    fn test_bitpack_column_counts_spill_and_index_files() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let pool = SmartBufferPool::default();
+        let open = |limiter: &FdLimiter| {
+            StreamColumn::<u32>::with_fd_limiter(
+                dir.path().join("col.bin"),
+                pool.clone(),
+                Box::new(BitpackStreamWriter::<u32>::new_in(
+                    pool.clone(),
+                    dir.path(),
+                )?),
+                None,
+                dir.path().to_path_buf(),
+                limiter,
+            )
+        };
+
+        let err = open(&FdLimiter::new(2)).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::WouldBlock);
+        assert_eq!(
+            err.to_string(),
+            "3 file permits needed, but only 2 of 2 are free"
+        );
+
+        // the column file, the spill file, and the index written while reading it back.
+        let limiter = FdLimiter::new(3);
+        let mut column = open(&limiter)?;
+        assert_eq!(limiter.available(), 0);
+        for v in 0..1_000 {
+            column.push(&v)?;
+        }
+        column.close_with_index()?;
+        assert_eq!(limiter.available(), 3);
+        assert!(dir.path().join("col.pidx").exists());
+        Ok(())
+    }
+}
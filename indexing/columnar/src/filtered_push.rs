@@ -2,7 +2,8 @@ use std::{collections::HashSet, io};
 
 #[derive(Debug, Clone)]
 pub struct PushConfig {
-    allowed_fields: HashSet<String>,
+    /// `None` allows every field.
+    allowed_fields: Option<HashSet<String>>,
 }
 
 impl PushConfig {
@@ -17,14 +18,38 @@ impl PushConfig {
         S: AsRef<str>,
     {
         let allowed_fields = fields.into_iter().map(|s| s.as_ref().to_string()).collect();
-        Self { allowed_fields }
+        Self {
+            allowed_fields: Some(allowed_fields),
+        }
+    }
+
+    /// A config that allows every field, so `push_with_config` stores the whole row.
+    pub fn all() -> Self {
+        Self {
+            allowed_fields: None,
+        }
     }
 
     pub fn is_allowed(&self, field: &str) -> bool {
-        self.allowed_fields.contains(field)
+        self.allowed_fields
+            .as_ref()
+            .is_none_or(|fields| fields.contains(field))
     }
 }
 
 pub trait FilteredPush<Row> {
     fn push_with_config(&mut self, row: &Row, cfg: &crate::PushConfig) -> io::Result<()>;
+
+    /// Pushes `row` into every column only when `pred` accepts it, so dropped rows never
+    /// leave the columns at different lengths. Returns whether the row was stored.
+    fn push_if(&mut self, row: &Row, pred: impl Fn(&Row) -> bool) -> io::Result<bool>
+    where
+        Self: Sized,
+    {
+        if !pred(row) {
+            return Ok(false);
+        }
+        self.push_with_config(row, &PushConfig::all())?;
+        Ok(true)
+    }
 }
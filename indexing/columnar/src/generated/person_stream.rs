@@ -1,28 +1,139 @@
-use std::path::PathBuf;
-
-use tempfile::TempDir;
-use toolkit::temp;
-
-use crate::{StreamingColumnBundle, models::person::Person};
-
+// generated by columnar_codegen, hash 97f36ff5c1c0f06a
 #[derive(Debug)]
 pub struct PersonStreamColumn {
     pub id: crate::StreamColumn<u64>,
+    __encoders: Vec<&'static str>,
+    __dir: std::path::PathBuf,
+    __permit: Option<crate::FdPermit>,
 }
 impl PersonStreamColumn {
+    #[doc = r" Column names in declaration order, after `rename` and without skipped fields."]
+    #[doc = r" Columns are stored and pushed in this order."]
+    pub const FIELD_ORDER: &'static [&'static str] = &["id"];
+}
+impl PersonStreamColumn {
+    #[doc = r" `(column name, id)` of every column: the field's declaration index unless"]
+    #[doc = r" `#[columnar(id = N)]` sets it. Use these ids for footer file sections."]
+    pub const COLUMN_IDS: &'static [(&'static str, u32)] = &[("id", 0u32)];
+    pub fn column_id(name: &str) -> Option<u32> {
+        Self::COLUMN_IDS
+            .iter()
+            .find(|(column, _)| *column == name)
+            .map(|&(_, id)| id)
+    }
+    pub fn column_name(id: u32) -> Option<&'static str> {
+        Self::COLUMN_IDS
+            .iter()
+            .find(|(_, column_id)| *column_id == id)
+            .map(|&(name, _)| name)
+    }
+}
+impl PersonStreamColumn {
+    #[doc = r" Opens a bundle that writes under `base_path` instead of the struct's"]
+    #[doc = r" `base_path` attribute, with the default pool and encoders and temp files in"]
+    #[doc = r" the system temp directory."]
+    pub fn create_in<P: AsRef<std::path::Path>>(base_path: P) -> std::io::Result<Self> {
+        Self::with_options(
+            &crate::encoding::EncoderFactory::default(),
+            crate::SmartBufferPool::default(),
+            std::env::temp_dir(),
+            &crate::StreamBundleOptions::default().with_base_path(base_path.as_ref()),
+        )
+    }
     fn with_pool(pool: crate::SmartBufferPool, temp_dir: std::path::PathBuf) -> Self {
-        Self {
-            id: crate::StreamColumn::new(
-                "data/out/Person/id.bin",
-                pool.clone(),
-                Box::new(crate::encoding::BitpackStreamWriter::<u64>::new(
-                    pool.clone(),
-                )),
-                None,
-                temp_dir.clone(),
-            )
-            .unwrap(),
-        }
+        Self::with_factory(&crate::encoding::EncoderFactory::default(), pool, temp_dir)
+    }
+    #[doc = r" Like `with_pool`, but takes each column's encoder from `factory` when it has"]
+    #[doc = r" one for the column or the field type."]
+    pub fn with_factory(
+        factory: &crate::encoding::EncoderFactory,
+        pool: crate::SmartBufferPool,
+        temp_dir: std::path::PathBuf,
+    ) -> Self {
+        Self::with_options(
+            factory,
+            pool,
+            temp_dir,
+            &crate::StreamBundleOptions::default(),
+        )
+        .unwrap()
+    }
+    #[doc = r" Like `with_factory`, but returns an error instead of panicking when a column"]
+    #[doc = r" cannot be opened, writes under `options.base_path` when set and opens every"]
+    #[doc = r" column through `options.fd_limiter` when set."]
+    pub fn with_options(
+        factory: &crate::encoding::EncoderFactory,
+        pool: crate::SmartBufferPool,
+        temp_dir: std::path::PathBuf,
+        options: &crate::StreamBundleOptions,
+    ) -> std::io::Result<Self> {
+        let __dir = match &options.base_path {
+            Some(base) => base.join("Person"),
+            None => std::path::PathBuf::from("data/out/Person"),
+        };
+        let __permit = None;
+        let mut __encoders = Vec::new();
+        Ok(Self {
+            id: {
+                let (__name, __encoder) = factory
+                    .build_for::<u64>("id", pool.clone())
+                    .unwrap_or_else(|| {
+                        let __encoder: Box<dyn crate::encoding::StreamingEncoder<u64>> = Box::new(
+                            crate::encoding::BitpackStreamWriter::<u64>::new(pool.clone()),
+                        );
+                        ("bitpack", __encoder)
+                    });
+                __encoders.push(__name);
+                match &options.fd_limiter {
+                    Some(__limiter) => crate::StreamColumn::with_fd_limiter(
+                        __dir.join("id.bin"),
+                        pool.clone(),
+                        __encoder,
+                        None,
+                        temp_dir.clone(),
+                        __limiter,
+                    )?,
+                    None => crate::StreamColumn::new(
+                        __dir.join("id.bin"),
+                        pool.clone(),
+                        __encoder,
+                        None,
+                        temp_dir.clone(),
+                    )?,
+                }
+            },
+            __encoders,
+            __dir,
+            __permit,
+        })
+    }
+    #[doc = r" Closes the bundle like `close` and reports, per column, the raw bytes pushed"]
+    #[doc = r" against the encoded bytes written."]
+    pub fn close_with_report(self) -> std::io::Result<crate::CompressionReport> {
+        std::fs::create_dir_all(&self.__dir)?;
+        let mut __report = crate::CompressionReport::default();
+        __report
+            .columns
+            .push(("id".to_string(), self.id.close_with_stats()?));
+        let manifest = crate::StreamManifest {
+            columns: vec![crate::ManifestColumn {
+                name: "id".to_string(),
+                file: "id.bin".to_string(),
+                encoder: self.__encoders[0usize].to_string(),
+                type_tag: "u64".to_string(),
+                section: None,
+                values: Some(__report.columns[0usize].1.values),
+            }],
+        };
+        manifest.write_to(&self.__dir)?;
+        Ok(__report)
+    }
+}
+impl Default for PersonStreamColumn {
+    #[doc = r" Writes under the struct's `base_path` attribute, or the working directory"]
+    #[doc = r" when it has none."]
+    fn default() -> Self {
+        Self::with_pool(crate::SmartBufferPool::default(), std::env::temp_dir())
     }
 }
 impl crate::StreamingColumnBundle<crate::models::person::Person> for PersonStreamColumn {
@@ -30,6 +141,9 @@ impl crate::StreamingColumnBundle<crate::models::person::Person> for PersonStrea
         self.id.push(&row.id.clone())?;
         Ok(())
     }
+    fn close(self) -> std::io::Result<()> {
+        self.close_with_report().map(|_| ())
+    }
 }
 impl crate::StreamingColumnar for crate::models::person::Person {
     type Columns = PersonStreamColumn;
@@ -46,14 +160,53 @@ impl crate::FilteredPush<crate::models::person::Person> for PersonStreamColumn {
         Ok(())
     }
 }
-
-#[test]
-fn test_columns() {
-    let pool = crate::SmartBufferPool::new(4 * 1024);
-    let mut temp = TempDir::new().unwrap();
-    let path = temp.path().clone();
-    let mut person_columns = PersonStreamColumn::with_pool(pool, PathBuf::from(path));
-
-    let person = crate::models::person::Person { id: 1 };
-    person_columns.push(&person).unwrap();
+#[doc = r" Reads back the columns a bundle wrote."]
+pub struct PersonReader {
+    dir: crate::StreamDirReader,
+}
+impl PersonStreamColumn {
+    #[doc = r" Opens the bundle `create_in(base_path)` wrote, from its directory under"]
+    #[doc = r" `base_path`. There is no fallback: the struct's `base_path` attribute and the"]
+    #[doc = r" working directory are not consulted."]
+    pub fn open<P: AsRef<std::path::Path>>(base_path: P) -> std::io::Result<PersonReader> {
+        Ok(PersonReader {
+            dir: crate::StreamDirReader::open(base_path.as_ref().join("Person"))?,
+        })
+    }
+}
+impl PersonReader {
+    #[doc = r" Streams the rows in the order they were pushed, decoding one value per column"]
+    #[doc = r" at a time. Yields `InvalidData` once, and then stops, when a column runs out"]
+    #[doc = r" before the others; decode errors are passed on the same way."]
+    pub fn iter_rows(
+        &self,
+    ) -> std::io::Result<impl Iterator<Item = std::io::Result<crate::models::person::Person>>> {
+        let mut __id = self.dir.values::<u64>("id")?;
+        let mut __row = 0usize;
+        let mut __failed = false;
+        Ok(std::iter::from_fn(move || {
+            if __failed {
+                return None;
+            }
+            let __id = __id.next();
+            let __all_ended: [bool; 1usize] = [__id.is_none()];
+            if __all_ended.iter().all(|ended| *ended) {
+                return None;
+            }
+            let __ended = |name: &str| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("column {name} ends after {__row} values, before the others"),
+                )
+            };
+            let __built = (|| -> std::io::Result<crate::models::person::Person> {
+                Ok(crate::models::person::Person {
+                    id: __id.ok_or_else(|| __ended("id"))??,
+                })
+            })();
+            __row += 1;
+            __failed = __built.is_err();
+            Some(__built)
+        }))
+    }
 }
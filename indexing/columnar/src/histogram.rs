@@ -0,0 +1,86 @@
+/// Equi-width histogram over the range `[min, max]` of a numeric column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    pub min: f64,
+    pub max: f64,
+    pub counts: Vec<u64>,
+}
+
+impl Histogram {
+    /// Builds a histogram with `buckets` equal-width buckets between the smallest and
+    /// largest value. When every value is equal they all land in the first bucket.
+    pub fn from_values<I>(values: I, buckets: usize) -> Self
+    where
+        I: IntoIterator<Item = f64>,
+        I::IntoIter: Clone,
+    {
+        let buckets = buckets.max(1);
+        let values = values.into_iter();
+        let (min, max) = values
+            .clone()
+            .filter(|v| !v.is_nan())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+                (lo.min(v), hi.max(v))
+            });
+
+        let mut counts = vec![0u64; buckets];
+        if min > max {
+            // no (non NaN) values
+            return Self {
+                min: 0.0,
+                max: 0.0,
+                counts,
+            };
+        }
+
+        let width = (max - min) / buckets as f64;
+        for v in values.filter(|v| !v.is_nan()) {
+            let index = if width > 0.0 {
+                (((v - min) / width) as usize).min(buckets - 1)
+            } else {
+                0
+            };
+            counts[index] += 1;
+        }
+        Self { min, max, counts }
+    }
+
+    /// Total number of values counted.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Lower and upper bound of bucket `index`.
+    pub fn bucket_range(&self, index: usize) -> (f64, f64) {
+        let width = (self.max - self.min) / self.counts.len() as f64;
+        let lo = self.min + width * index as f64;
+        (lo, lo + width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_uniform() {
+        let values: Vec<f64> = (0..100).map(|v| v as f64).collect();
+        let hist = Histogram::from_values(values.iter().copied(), 10);
+        assert_eq!(hist.min, 0.0);
+        assert_eq!(hist.max, 99.0);
+        assert_eq!(hist.counts.len(), 10);
+        assert_eq!(hist.total(), 100);
+        assert!(hist.counts.iter().all(|c| (9..=11).contains(c)));
+        assert_eq!(hist.bucket_range(0), (0.0, 9.9));
+    }
+
+    #[test]
+    fn test_histogram_constant_and_empty() {
+        let hist = Histogram::from_values([5.0; 4], 3);
+        assert_eq!(hist.counts, vec![4, 0, 0]);
+
+        let hist = Histogram::from_values(std::iter::empty(), 3);
+        assert_eq!(hist.counts, vec![0, 0, 0]);
+        assert_eq!(hist.total(), 0);
+    }
+}
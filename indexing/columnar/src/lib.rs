@@ -1,17 +1,26 @@
 pub mod buffers;
 pub mod columnar;
 pub mod encoding;
+pub mod fd_limiter;
 pub mod filtered_push;
 pub mod generated;
+pub mod histogram;
 pub mod indexing;
 pub mod models;
+pub mod quantile;
 pub mod simple;
 pub mod stream;
+pub mod stream_dir;
 
 pub use buffers::smart_pool::*;
+pub use buffers::typed_pool::*;
 pub use columnar::*;
 pub use columnar_derive::{Columnar, ColumnarAttrs, SimpleColumnar};
+pub use fd_limiter::*;
 pub use filtered_push::*;
+pub use histogram::*;
+pub use quantile::*;
 pub use simple::*;
 pub use stream::*;
+pub use stream_dir::*;
 pub use tempfile::TempDir;
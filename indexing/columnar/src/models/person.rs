@@ -6,3 +6,19 @@ pub struct Person {
     #[columnar(encoder = "bitpack")]
     pub id: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StreamingColumnBundle;
+    use crate::generated::person_stream::PersonStreamColumn;
+
+    #[test]
+    fn test_columns() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut person_columns = PersonStreamColumn::create_in(dir.path()).unwrap();
+        person_columns.push(&Person { id: 1 }).unwrap();
+        person_columns.close().unwrap();
+        assert!(dir.path().join("Person/id.bin").exists());
+    }
+}
@@ -1,10 +1,11 @@
 //! Positions
+use columnar_derive::SimpleColumnar;
 use serde::de::Error as SerdeError;
 use serde::{self, Deserialize, Deserializer, Serialize};
 use std::fmt::Display;
 use std::str::FromStr;
 
-#[derive(Debug, Serialize, Deserialize, Default, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, SimpleColumnar)]
 pub struct Position {
     // // 16 bytes
     // #[serde(skip_deserializing, default)]
@@ -71,81 +72,101 @@ pub struct Position {
     )]
     pub prev_end_index: i16,
 
-    #[serde(rename = "weight", default, deserialize_with = "empty_to_default")]
+    #[serde(rename = "weight", default, deserialize_with = "parse_float_lenient")]
     pub weight: f32,
     #[serde(
         rename = "sample_weight",
         default,
-        deserialize_with = "empty_to_default"
+        deserialize_with = "parse_float_lenient"
     )]
     pub sample_weight: f32,
     #[serde(
         rename = "inflow_weight",
         default,
-        deserialize_with = "empty_to_default"
+        deserialize_with = "parse_float_lenient"
     )]
     pub inflow_weight: f32,
     #[serde(
         rename = "outflow_weight",
         default,
-        deserialize_with = "empty_to_default"
+        deserialize_with = "parse_float_lenient"
     )]
     pub outflow_weight: f32,
     #[serde(
         rename = "fulltime_prob",
         default,
-        deserialize_with = "empty_to_default"
+        deserialize_with = "parse_float_lenient"
     )]
     pub full_time_prob: f32,
 
     #[serde(
         rename = "multiplicator",
         default,
-        deserialize_with = "empty_to_default"
+        deserialize_with = "parse_float_lenient"
     )]
     pub multiplicator: f32,
-    #[serde(rename = "inflation", default, deserialize_with = "empty_to_default")]
+    #[serde(
+        rename = "inflation",
+        default,
+        deserialize_with = "parse_float_lenient"
+    )]
     pub inflation: f32,
 
-    #[serde(rename = "comp_ratio", default, deserialize_with = "empty_to_default")]
+    #[serde(
+        rename = "comp_ratio",
+        default,
+        deserialize_with = "parse_float_lenient"
+    )]
     pub total_compensation_ratio: f32,
     #[serde(
         rename = "work_hours_per_year",
         default,
-        deserialize_with = "empty_to_default"
+        deserialize_with = "parse_float_lenient"
     )]
     pub full_time_hours: f32,
 
     #[serde(
         rename = "estimated_us_log_salary",
         default,
-        deserialize_with = "empty_to_default"
+        deserialize_with = "parse_float_lenient"
     )]
     pub estimated_us_log_salary: f32,
-    #[serde(rename = "f_prob", default, deserialize_with = "empty_to_default")]
+    #[serde(rename = "f_prob", default, deserialize_with = "parse_float_lenient")]
     pub f_prob: f32,
 
-    #[serde(rename = "white_prob", default, deserialize_with = "empty_to_default")]
+    #[serde(
+        rename = "white_prob",
+        default,
+        deserialize_with = "parse_float_lenient"
+    )]
     pub white_prob: f32,
     #[serde(
         rename = "multiple_prob",
         default,
-        deserialize_with = "empty_to_default"
+        deserialize_with = "parse_float_lenient"
     )]
     pub multiple_prob: f32,
 
-    #[serde(rename = "black_prob", default, deserialize_with = "empty_to_default")]
+    #[serde(
+        rename = "black_prob",
+        default,
+        deserialize_with = "parse_float_lenient"
+    )]
     pub black_prob: f32,
-    #[serde(rename = "api_prob", default, deserialize_with = "empty_to_default")]
+    #[serde(rename = "api_prob", default, deserialize_with = "parse_float_lenient")]
     pub api_prob: f32,
 
     #[serde(
         rename = "hispanic_prob",
         default,
-        deserialize_with = "empty_to_default"
+        deserialize_with = "parse_float_lenient"
     )]
     pub hispanic_prob: f32,
-    #[serde(rename = "native_prob", default, deserialize_with = "empty_to_default")]
+    #[serde(
+        rename = "native_prob",
+        default,
+        deserialize_with = "parse_float_lenient"
+    )]
     pub native_prob: f32,
 
     #[serde(rename = "role_v3_id", default, deserialize_with = "empty_to_default")]
@@ -186,6 +207,7 @@ pub struct Position {
         deserialize_with = "deserialize_skill_list",
         default
     )]
+    #[columnar(list)]
     pub mapped_skills_v3: Vec<u16>,
 
     #[serde(rename = "description", default, deserialize_with = "empty_to_default")]
@@ -208,6 +230,37 @@ where
     }
 }
 
+/// Parses a float, tolerating `_` and `,` digit separators. Empty input yields the
+/// default value. Scientific notation is accepted as-is by `FromStr`.
+pub fn parse_float_lenient<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + FromStr,
+    T::Err: Display,
+{
+    let opt = Option::<String>::deserialize(deserializer)?;
+    match opt {
+        Some(raw) => parse_lenient::<T>(&raw).map_err(SerdeError::custom),
+        None => Ok(T::default()),
+    }
+}
+
+/// String form of [`parse_float_lenient`].
+pub fn parse_lenient<T>(raw: &str) -> Result<T, T::Err>
+where
+    T: Default + FromStr,
+{
+    let cleaned: String = raw
+        .trim()
+        .chars()
+        .filter(|c| *c != '_' && *c != ',')
+        .collect();
+    if cleaned.is_empty() {
+        return Ok(T::default());
+    }
+    cleaned.parse::<T>()
+}
+
 pub fn deserialize_skill_list<'de, D>(deserializer: D) -> Result<Vec<u16>, D::Error>
 where
     D: Deserializer<'de>,
@@ -226,3 +279,27 @@ where
         .filter_map(|num| num.trim().parse::<T>().ok())
         .collect::<Vec<T>>()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::parse_lenient;
+
+    #[test]
+    fn test_parse_lenient_separators() {
+        assert_eq!(parse_lenient::<f32>("1_000.5").unwrap(), 1000.5);
+        assert_eq!(parse_lenient::<f32>("1,000.5").unwrap(), 1000.5);
+    }
+
+    #[test]
+    fn test_parse_lenient_scientific() {
+        assert_eq!(parse_lenient::<f32>("1.5e3").unwrap(), 1500.0);
+        assert_eq!(parse_lenient::<f32>(" -2.5E-1 ").unwrap(), -0.25);
+    }
+
+    #[test]
+    fn test_parse_lenient_empty_and_invalid() {
+        assert_eq!(parse_lenient::<f32>("").unwrap(), 0.0);
+        assert_eq!(parse_lenient::<f32>("  ").unwrap(), 0.0);
+        assert!(parse_lenient::<f32>("abc").is_err());
+    }
+}
@@ -0,0 +1,193 @@
+use std::cmp::Ordering;
+
+/// Samples kept by a default `QuantileSketch`.
+pub const DEFAULT_QUANTILE_SAMPLES: usize = 2048;
+
+/// Approximate quantiles of a numeric column from a fixed-size uniform sample of its values
+/// (reservoir sampling), so memory stays at `capacity` values however many are inserted.
+///
+/// With the default 2048 samples the rank of an estimated median is typically within about
+/// 1% of the true one. Sampling is seeded, so the same inserts give the same estimates.
+/// Values that do not compare equal to themselves (NaN) are ignored.
+#[derive(Debug, Clone)]
+pub struct QuantileSketch<T> {
+    samples: Vec<T>,
+    capacity: usize,
+    /// Values inserted so far, including ones not kept in `samples`.
+    seen: u64,
+    rng: u64,
+}
+
+impl<T> Default for QuantileSketch<T> {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_QUANTILE_SAMPLES)
+    }
+}
+
+impl<T> QuantileSketch<T> {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            samples: Vec::new(),
+            capacity: capacity.max(1),
+            seen: 0,
+            rng: 0x9e37_79b9_7f4a_7c15,
+        }
+    }
+
+    /// Number of values inserted, not the number sampled.
+    pub fn len(&self) -> u64 {
+        self.seen
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seen == 0
+    }
+
+    /// True while every inserted value is still in the sample.
+    fn is_exact(&self) -> bool {
+        self.seen == self.samples.len() as u64
+    }
+
+    /// Uniform index below `n` from a xorshift64* generator.
+    fn next_index(&mut self, n: u64) -> u64 {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        self.rng.wrapping_mul(0x2545_f491_4f6c_dd1d) % n
+    }
+}
+
+impl<T: Copy + PartialOrd> QuantileSketch<T> {
+    pub fn insert(&mut self, v: T) {
+        if v.partial_cmp(&v).is_none() {
+            return;
+        }
+        self.seen += 1;
+        if self.samples.len() < self.capacity {
+            self.samples.push(v);
+        } else {
+            let slot = self.next_index(self.seen) as usize;
+            if slot < self.capacity {
+                self.samples[slot] = v;
+            }
+        }
+    }
+
+    /// Combines two sketches as if every value of `other` had been inserted into `self`.
+    ///
+    /// When either side still holds all of its values they are simply re-inserted; otherwise
+    /// each kept sample is drawn from one side or the other in proportion to how many values
+    /// that side has seen.
+    pub fn merge(&mut self, mut other: Self) {
+        if self.is_exact() && !other.is_exact() {
+            // keep the sampled side and re-insert our values into it below.
+            std::mem::swap(&mut self.samples, &mut other.samples);
+            std::mem::swap(&mut self.seen, &mut other.seen);
+            while self.samples.len() > self.capacity {
+                let drop = self.next_index(self.samples.len() as u64) as usize;
+                self.samples.swap_remove(drop);
+            }
+        } else if !self.is_exact() && !other.is_exact() {
+            let total = self.seen + other.seen;
+            let mut ours = std::mem::take(&mut self.samples);
+            let want = self.capacity.min(ours.len() + other.samples.len());
+            while self.samples.len() < want {
+                let from_ours = other.samples.is_empty()
+                    || (!ours.is_empty() && self.next_index(total) < self.seen);
+                let source = if from_ours {
+                    &mut ours
+                } else {
+                    &mut other.samples
+                };
+                let pick = self.next_index(source.len() as u64) as usize;
+                let v = source.swap_remove(pick);
+                self.samples.push(v);
+            }
+            self.seen = total;
+            return;
+        }
+        // `other` now holds every value it has seen.
+        for v in other.samples {
+            self.insert(v);
+        }
+    }
+
+    /// Estimated value at quantile `q` (clamped to `0.0..=1.0`), or `None` before any
+    /// value was inserted.
+    pub fn quantile(&self, q: f64) -> Option<T> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mut sorted = self.samples.clone();
+        sorted.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        let q = if q.is_nan() { 0.5 } else { q.clamp(0.0, 1.0) };
+        let index = (q * (sorted.len() - 1) as f64).round() as usize;
+        Some(sorted[index])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shuffled(n: u32) -> Vec<u32> {
+        // multiplying by an odd constant permutes 0..2^k; keep the ones below n.
+        (0..n.next_power_of_two())
+            .map(|i| i.wrapping_mul(2_654_435_761) & (n.next_power_of_two() - 1))
+            .filter(|&v| v < n)
+            .collect()
+    }
+
+    #[test]
+    fn test_sketch_is_bounded_and_close() {
+        let mut sketch = QuantileSketch::with_capacity(1024);
+        for v in shuffled(200_000) {
+            sketch.insert(v);
+        }
+        assert_eq!(sketch.len(), 200_000);
+        assert_eq!(sketch.samples.len(), 1024);
+        for (q, truth) in [(0.5, 100_000.0), (0.95, 190_000.0), (0.05, 10_000.0)] {
+            let got = sketch.quantile(q).unwrap() as f64;
+            assert!((got - truth).abs() < 200_000.0 * 0.04, "q{q}: {got}");
+        }
+    }
+
+    #[test]
+    fn test_sketch_small_inputs_are_exact() {
+        let mut sketch = QuantileSketch::default();
+        assert_eq!(sketch.quantile(0.5), None::<f64>);
+        for v in [3.0, f64::NAN, 1.0, 2.0] {
+            sketch.insert(v);
+        }
+        assert_eq!(sketch.len(), 3);
+        assert_eq!(sketch.quantile(0.0), Some(1.0));
+        assert_eq!(sketch.quantile(0.5), Some(2.0));
+        assert_eq!(sketch.quantile(7.0), Some(3.0));
+    }
+
+    #[test]
+    fn test_sketch_merge_weights_by_count() {
+        // 9 in 10 values are small, so the merged median must come from the first sketch.
+        let (mut small, mut large) = (
+            QuantileSketch::with_capacity(512),
+            QuantileSketch::with_capacity(512),
+        );
+        for v in shuffled(90_000) {
+            small.insert(v);
+        }
+        for v in 0..10_000u32 {
+            large.insert(1_000_000 + v);
+        }
+        small.merge(large);
+        assert_eq!(small.len(), 100_000);
+        assert_eq!(small.samples.len(), 512);
+        let median = small.quantile(0.5).unwrap();
+        assert!((40_000..60_000).contains(&median), "{median}");
+
+        let mut exact = QuantileSketch::with_capacity(512);
+        exact.insert(5u32);
+        exact.merge(small.clone());
+        assert_eq!(exact.len(), 100_001);
+        assert_eq!(exact.samples.len(), 512);
+    }
+}
@@ -1,6 +1,45 @@
+use crate::buffers::smart_pool::SmartBufferPool;
+use crate::encoding::bitpack::v1::common::{
+    BitEncodable, PAGE_DEFAULT_SIZE, bit_width_from_value, clamp_width_to_type,
+};
+use crate::encoding::bitpack::v1::page_writer::PageEncoder;
+use std::io::{self, Write};
+
 pub trait SimpleColumnBundle<Row>: Default {
     fn push(&mut self, row: &Row);
     fn merge(&mut self, other: Self);
+    /// Checks that every column holds the same number of rows. Derived bundles name the
+    /// first column that disagrees.
+    fn assert_consistent(&self) -> io::Result<()> {
+        Ok(())
+    }
+    /// Rebuilds the row at `index`, or `None` past the longest column. A column left empty
+    /// while others hold values, e.g. one a `PushConfig` excluded, contributes that field
+    /// of `defaults`; skipped fields get their `Default` as in `row`.
+    ///
+    /// Only `#[derive(SimpleColumnar)]` bundles rebuild rows; the chunked `Columnar` bundles
+    /// have no equivalent, and a bundle that does not override this always returns `None`.
+    fn row_with_defaults(&self, index: usize, defaults: &Row) -> Option<Row> {
+        let _ = (index, defaults);
+        None
+    }
+
+    /// Like `row_with_defaults`, filling empty columns from `Row::default()`.
+    fn row_or_default(&self, index: usize) -> Option<Row>
+    where
+        Row: Default,
+    {
+        self.row_with_defaults(index, &Row::default())
+    }
+
+    /// Rebuilds every row like `row_or_default`, as many as the longest column holds.
+    fn iter_rows_or_default(&self) -> impl Iterator<Item = Row>
+    where
+        Row: Default,
+    {
+        let defaults = Row::default();
+        (0..).map_while(move |index| self.row_with_defaults(index, &defaults))
+    }
 }
 
 pub trait SimpleColumnar: Sized {
@@ -13,6 +52,30 @@ pub trait SimpleColumnar: Sized {
         }
         cols
     }
+
+    /// Builds the columns on up to `shards` threads, one contiguous slice of `rows` each.
+    ///
+    /// Shard bundles are merged in ascending shard index, never in completion order, so
+    /// the result is always identical to `to_simple_columns(rows)`.
+    fn to_simple_columns_parallel(rows: &[Self], shards: usize) -> Self::Columns
+    where
+        Self: Sync,
+        Self::Columns: Send,
+    {
+        let shard_len = rows.len().div_ceil(shards.max(1)).max(1);
+        std::thread::scope(|scope| {
+            let handles = rows
+                .chunks(shard_len)
+                .map(|shard| scope.spawn(move || Self::to_simple_columns(shard)))
+                .collect::<Vec<_>>();
+
+            let mut cols = Self::Columns::default();
+            for handle in handles {
+                cols.merge(handle.join().expect("shard build panicked"));
+            }
+            cols
+        })
+    }
 }
 
 /// Simple Vec-backed column, mostly for testing or light use.
@@ -27,3 +90,113 @@ impl<T: Clone> VecColumn<T> {
         self.0.extend(other.0);
     }
 }
+
+impl<T: BitEncodable> VecColumn<T> {
+    /// Bit-packs the values into pages and writes them to `writer`, in the same layout
+    /// the streaming encoder produces. The width is the smallest that fits every value.
+    pub fn encode_to<W: Write>(&self, writer: &mut W, pool: SmartBufferPool) -> io::Result<()> {
+        let width = self
+            .0
+            .iter()
+            .map(|&v| bit_width_from_value(v))
+            .max()
+            .unwrap_or(1);
+        let width = clamp_width_to_type::<T>(width);
+        for page in PageEncoder::new(pool, self.0.iter().copied(), width, PAGE_DEFAULT_SIZE) {
+            writer.write_all(page?.as_slice())?;
+        }
+        Ok(())
+    }
+}
+
+/// Flattened list column: every element lives in `values` and `offsets`
+/// marks the list boundaries, so row `i` is `values[offsets[i]..offsets[i + 1]]`.
+#[derive(Debug, Clone)]
+pub struct ListColumn<T> {
+    pub values: Vec<T>,
+    pub offsets: Vec<usize>,
+}
+
+impl<T> Default for ListColumn<T> {
+    fn default() -> Self {
+        Self {
+            values: Vec::new(),
+            offsets: vec![0],
+        }
+    }
+}
+
+impl<T> ListColumn<T> {
+    pub fn len(&self) -> usize {
+        self.offsets.len().saturating_sub(1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the list stored for `row`.
+    pub fn get(&self, row: usize) -> Option<&[T]> {
+        let start = *self.offsets.get(row)?;
+        let end = *self.offsets.get(row + 1)?;
+        Some(&self.values[start..end])
+    }
+
+    /// Iterates the lists in row order.
+    pub fn iter(&self) -> impl Iterator<Item = &[T]> {
+        self.offsets.windows(2).map(|w| &self.values[w[0]..w[1]])
+    }
+}
+
+impl<T: Clone> ListColumn<T> {
+    pub fn push(&mut self, v: &[T]) {
+        self.values.extend_from_slice(v);
+        self.offsets.push(self.values.len());
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        let base = self.values.len();
+        self.values.extend(other.values);
+        self.offsets
+            .extend(other.offsets.into_iter().skip(1).map(|o| o + base));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::bitpack::v1::page_reader::PageDecoder;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_vec_column_encode_to_roundtrip() -> io::Result<()> {
+        let pool = SmartBufferPool::new(1 << 20);
+        let column = VecColumn((0..50_000u32).map(|v| v * 7 % 100_003).collect());
+
+        let mut bytes = Vec::new();
+        column.encode_to(&mut bytes, pool.clone())?;
+
+        let decoded =
+            PageDecoder::<_, u32>::new(pool, Cursor::new(bytes)).collect::<io::Result<Vec<_>>>()?;
+        assert_eq!(decoded, column.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_vec_column_encodes_nothing() -> io::Result<()> {
+        let mut bytes = Vec::new();
+        VecColumn::<u32>::default().encode_to(&mut bytes, SmartBufferPool::new(1 << 20))?;
+        assert!(bytes.is_empty());
+        Ok(())
+    }
+    #[test]
+    fn test_list_column_without_offsets_is_empty() {
+        let column = ListColumn::<u16> {
+            values: Vec::new(),
+            offsets: Vec::new(),
+        };
+        assert_eq!(column.len(), 0);
+        assert!(column.is_empty());
+        assert_eq!(column.get(0), None);
+    }
+}
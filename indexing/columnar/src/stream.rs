@@ -1,21 +1,36 @@
 use crate::buffers::smart_pool::SmartBufferPool;
 use crate::encoding::StreamingEncoder;
+use crate::encoding::bitpack::v1::common::BitEncodable;
+use crate::encoding::bitpack::v1::footer::StreamFooter;
+use crate::encoding::bitpack::v1::page_index::PageIndex;
+use crate::fd_limiter::{FdLimiter, FdPermit};
 use core::fmt;
 use std::fs::{self, File};
-use std::io::{self, BufWriter};
+use std::io::{self, BufReader, BufWriter, Seek, SeekFrom, Write};
 use std::path::PathBuf;
+use toolkit::footerfile::file_encoder::FooterFileEncoder;
 
-pub struct StreamColumn<T> {
-    path: PathBuf,
-    writer: BufWriter<File>,
+/// A column streamed value by value into `W`, by default a buffered file.
+pub struct StreamColumn<T, W: Write = BufWriter<File>> {
+    /// The backing file, if the column was opened from a path.
+    path: Option<PathBuf>,
+    writer: W,
     encoder: Box<dyn StreamingEncoder<T>>,
     pool: SmartBufferPool,
     index: Option<Box<dyn FieldIndex<T>>>,
     row_pos: usize,
+    /// Bytes the encoder has written to `writer` so far.
+    encoded_bytes: u64,
     temp_dir: PathBuf,
+    on_close: Option<OnClose<T>>,
+    /// Folds each pushed value into `min_max`; set along with `on_close`.
+    track_min_max: Option<MinMaxFold<T>>,
+    min_max: Option<(T, T)>,
+    // declared after `writer` so the file is closed before the permit is released.
+    _permit: Option<FdPermit>,
 }
 
-impl<T> fmt::Debug for StreamColumn<T> {
+impl<T, W: Write> fmt::Debug for StreamColumn<T, W> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("StreamColumn")
             .field("path", &self.path)
@@ -23,6 +38,85 @@ impl<T> fmt::Debug for StreamColumn<T> {
     }
 }
 
+/// Bytes that went into a column versus what its encoder wrote out, to compare encoders.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ColumnStats {
+    /// Values pushed.
+    pub values: u64,
+    /// `count * size_of::<T>()`; for heap types like `String` only the inline size counts.
+    pub raw_bytes_in: u64,
+    /// Bytes the encoder wrote, headers and footers included.
+    pub encoded_bytes_out: u64,
+}
+
+impl ColumnStats {
+    /// `encoded_bytes_out / raw_bytes_in`, below 1.0 when the encoding saves space, or
+    /// `None` for a column without values.
+    pub fn ratio(&self) -> Option<f64> {
+        (self.raw_bytes_in > 0).then(|| self.encoded_bytes_out as f64 / self.raw_bytes_in as f64)
+    }
+}
+
+/// Per-column `ColumnStats` of a closed streaming bundle, in field order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompressionReport {
+    pub columns: Vec<(String, ColumnStats)>,
+}
+
+impl CompressionReport {
+    pub fn column(&self, name: &str) -> Option<ColumnStats> {
+        self.columns
+            .iter()
+            .find(|(column, _)| column == name)
+            .map(|(_, stats)| *stats)
+    }
+
+    /// Sum over all columns.
+    pub fn total(&self) -> ColumnStats {
+        self.columns
+            .iter()
+            .fold(ColumnStats::default(), |total, (_, stats)| ColumnStats {
+                values: total.values + stats.values,
+                raw_bytes_in: total.raw_bytes_in + stats.raw_bytes_in,
+                encoded_bytes_out: total.encoded_bytes_out + stats.encoded_bytes_out,
+            })
+    }
+}
+
+/// What a column wrote, handed to the `StreamColumn::set_on_close` callback once its
+/// stream has ended.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CloseSummary<T> {
+    /// The backing file, if the column was opened from a path.
+    pub path: Option<PathBuf>,
+    pub values: u64,
+    /// Bytes the encoder wrote, headers and footers included.
+    pub bytes_out: u64,
+    /// Smallest and largest value pushed, `None` for a column without values.
+    pub min_max: Option<(T, T)>,
+}
+
+type OnClose<T> = Box<dyn FnOnce(&CloseSummary<T>)>;
+type MinMaxFold<T> = fn(&mut Option<(T, T)>, &T);
+
+/// Forwards writes to `inner`, adding the bytes written to `written`.
+struct Counted<'a, W> {
+    inner: &'a mut W,
+    written: &'a mut u64,
+}
+
+impl<W: Write> Write for Counted<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        *self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
 pub trait FieldIndex<T> {
     fn record(&mut self, value: &T, position: usize) -> io::Result<()>;
     fn flush(&mut self) -> io::Result<()>;
@@ -38,6 +132,112 @@ where
         encoder: Box<dyn StreamingEncoder<T>>,
         index: Option<Box<dyn FieldIndex<T>>>,
         temp_dir: PathBuf,
+    ) -> io::Result<Self> {
+        Self::open(path, pool, encoder, index, temp_dir, None)
+    }
+
+    /// Like `new`, but first takes permits from `limiter` for every file the column may
+    /// hold open at once, failing with `WouldBlock` when not enough are free. That is the
+    /// column file, the encoder's `open_files` such as a bitpack spill file, and for bitpack
+    /// columns the `.pidx` file `close_with_index` writes while reading the column back.
+    /// Files a `FieldIndex` opens are not counted. The permits are held until the column
+    /// is closed.
+    pub fn with_fd_limiter<P: Into<PathBuf>>(
+        path: P,
+        pool: SmartBufferPool,
+        encoder: Box<dyn StreamingEncoder<T>>,
+        index: Option<Box<dyn FieldIndex<T>>>,
+        temp_dir: PathBuf,
+        limiter: &FdLimiter,
+    ) -> io::Result<Self> {
+        let index_file = usize::from(encoder.writes_bitpack_pages());
+        let permit = limiter.try_permits(1 + encoder.open_files() + index_file)?;
+        Self::open(path, pool, encoder, index, temp_dir, Some(permit))
+    }
+
+    /// Reopens a column file written and closed earlier and appends to it.
+    ///
+    /// Existing bytes are left untouched: the new values go through a fresh
+    /// `begin_stream`/`end_stream` and land after them. This only decodes as one sequence
+    /// for encoders whose output is self-delimiting, such as the bitpack pages, where the
+    /// file's last page may simply hold fewer values than a full one. Stateful formats like
+    /// delta encoding restart at the append point and do not. Row positions restart at 0.
+    ///
+    /// A `StreamFooter` ending the file is cut off first, since readers stop at it and would
+    /// never reach the appended pages. A footer `encoder` writes in turn only covers the
+    /// appended values, so append with one that writes none.
+    pub fn open_append<P: Into<PathBuf>>(
+        path: P,
+        encoder: Box<dyn StreamingEncoder<T>>,
+        pool: SmartBufferPool,
+    ) -> io::Result<Self> {
+        let path = path.into();
+        let mut file = fs::OpenOptions::new().read(true).append(true).open(&path)?;
+        if let Some(footer) = StreamFooter::read_from_end(&mut file)? {
+            let len = file.metadata()?.len();
+            file.set_len(len - footer.encoded_len() as u64)?;
+        }
+        let mut column = StreamColumn::from_writer(
+            BufWriter::new(file),
+            pool,
+            encoder,
+            None,
+            std::env::temp_dir(),
+        )?;
+        column.path = Some(path);
+        Ok(column)
+    }
+
+    /// Streams into an anonymous file inside `temp_dir`, to be moved into a shared footer
+    /// file by `close_into`.
+    pub fn temporary(
+        pool: SmartBufferPool,
+        encoder: Box<dyn StreamingEncoder<T>>,
+        index: Option<Box<dyn FieldIndex<T>>>,
+        temp_dir: PathBuf,
+    ) -> io::Result<Self> {
+        let file = tempfile::tempfile_in(&temp_dir)?;
+        StreamColumn::from_writer(BufWriter::new(file), pool, encoder, index, temp_dir)
+    }
+
+    /// Like `temporary`, but holds permits from `limiter` like `with_fd_limiter`, for the
+    /// temporary file and the encoder's `open_files`.
+    pub fn temporary_with_fd_limiter(
+        pool: SmartBufferPool,
+        encoder: Box<dyn StreamingEncoder<T>>,
+        index: Option<Box<dyn FieldIndex<T>>>,
+        temp_dir: PathBuf,
+        limiter: &FdLimiter,
+    ) -> io::Result<Self> {
+        let permit = limiter.try_permits(1 + encoder.open_files())?;
+        let mut column = Self::temporary(pool, encoder, index, temp_dir)?;
+        column._permit = Some(permit);
+        Ok(column)
+    }
+
+    /// Ends the stream like `close_with_stats`, then copies the encoded column into `out` as
+    /// section `column_id`.
+    pub fn close_into(
+        mut self,
+        out: &mut FooterFileEncoder,
+        column_id: u32,
+    ) -> io::Result<ColumnStats> {
+        self.end()?;
+        let stats = self.stats();
+        let Self { writer, .. } = self;
+        let mut file = writer.into_inner().map_err(|e| e.into_error())?;
+        file.seek(SeekFrom::Start(0))?;
+        out.write(column_id, &mut file)?;
+        Ok(stats)
+    }
+
+    fn open<P: Into<PathBuf>>(
+        path: P,
+        pool: SmartBufferPool,
+        encoder: Box<dyn StreamingEncoder<T>>,
+        index: Option<Box<dyn FieldIndex<T>>>,
+        temp_dir: PathBuf,
+        permit: Option<FdPermit>,
     ) -> io::Result<Self> {
         let path = path.into();
 
@@ -46,39 +246,215 @@ where
         }
 
         let file = File::create(&path)?;
-        let mut writer = BufWriter::new(file);
-        encoder.begin_stream(&mut writer)?;
+        let mut column =
+            StreamColumn::from_writer(BufWriter::new(file), pool, encoder, index, temp_dir)?;
+        column.path = Some(path);
+        column._permit = permit;
+        Ok(column)
+    }
+}
+
+impl<T: BitEncodable + 'static> StreamColumn<T> {
+    /// Ends the stream like `close_with_stats` and writes a `PageIndex` of the column file
+    /// next to it, with the `.pidx` extension, clear of the `.idx` files field indexes use.
+    /// Only for bitpack-encoded columns opened from a path; other encoders are rejected
+    /// before the stream is ended.
+    ///
+    /// The data file is synced before the index is written to a temporary file, synced and
+    /// renamed into place, so an index is only ever visible next to the complete data it
+    /// describes. A column file without its index was not closed this way.
+    pub fn close_with_index(mut self) -> io::Result<ColumnStats> {
+        let path = self.path.clone().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "close_with_index needs a column opened from a path",
+            )
+        })?;
+        if !self.encoder.writes_bitpack_pages() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "close_with_index needs a bitpack-encoded column",
+            ));
+        }
+        self.end()?;
+        let stats = self.stats();
+        let Self { writer, .. } = self;
+        writer
+            .into_inner()
+            .map_err(|e| e.into_error())?
+            .sync_all()?;
+
+        let index = PageIndex::build::<T, _>(&mut BufReader::new(File::open(&path)?))?;
+        let index_path = path.with_extension("pidx");
+        let temp_path = path.with_extension("pidx.tmp");
+        let mut out = BufWriter::new(File::create(&temp_path)?);
+        index.write_to(&mut out)?;
+        out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        fs::rename(&temp_path, &index_path)?;
+        Ok(stats)
+    }
+}
+
+impl<T, W> StreamColumn<T, W>
+where
+    T: 'static,
+    W: Write,
+{
+    /// Streams the column into an arbitrary writer, e.g. an in-memory buffer or a socket.
+    pub fn from_writer(
+        mut writer: W,
+        pool: SmartBufferPool,
+        encoder: Box<dyn StreamingEncoder<T>>,
+        index: Option<Box<dyn FieldIndex<T>>>,
+        temp_dir: PathBuf,
+    ) -> io::Result<Self> {
+        let mut encoded_bytes = 0;
+        encoder.begin_stream(&mut Counted {
+            inner: &mut writer,
+            written: &mut encoded_bytes,
+        })?;
         Ok(Self {
-            path,
+            path: None,
             writer,
             encoder,
             pool,
             row_pos: 0,
+            encoded_bytes,
             index,
             temp_dir,
+            on_close: None,
+            track_min_max: None,
+            min_max: None,
+            _permit: None,
         })
     }
 
     pub fn push(&mut self, v: &T) -> io::Result<()> {
-        self.encoder
-            .encode_value(v, self.row_pos, &mut self.writer)?;
+        let mut writer = Counted {
+            inner: &mut self.writer,
+            written: &mut self.encoded_bytes,
+        };
+        self.encoder.encode_value(v, self.row_pos, &mut writer)?;
         if let Some(index) = &mut self.index {
             index.record(v, self.row_pos)?;
         }
+        if let Some(track) = self.track_min_max {
+            track(&mut self.min_max, v);
+        }
         self.row_pos += 1;
         Ok(())
     }
 
     pub fn close(mut self) -> io::Result<()> {
-        if let Some(mut index) = self.index {
+        self.end()
+    }
+
+    /// Ends the stream like `close` and reports how much the encoder wrote for the values
+    /// pushed.
+    pub fn close_with_stats(mut self) -> io::Result<ColumnStats> {
+        self.end()?;
+        Ok(self.stats())
+    }
+
+    /// Bytes pushed and encoded so far. Encoders that buffer, such as `categorical`, only
+    /// write on close, so this is final once the stream has ended.
+    pub fn stats(&self) -> ColumnStats {
+        ColumnStats {
+            values: self.row_pos as u64,
+            raw_bytes_in: (self.row_pos * size_of::<T>()) as u64,
+            encoded_bytes_out: self.encoded_bytes,
+        }
+    }
+
+    /// Ends the stream like `close`, then hands back the flushed writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.end()?;
+        let Self { writer, .. } = self;
+        Ok(writer)
+    }
+
+    fn end(&mut self) -> io::Result<()> {
+        if let Some(mut index) = self.index.take() {
             index.flush()?;
         }
-        self.encoder.end_stream(&mut self.writer)
+        self.encoder.end_stream(&mut Counted {
+            inner: &mut self.writer,
+            written: &mut self.encoded_bytes,
+        })?;
+        self.writer.flush()?;
+        if let Some(on_close) = self.on_close.take() {
+            on_close(&CloseSummary {
+                path: self.path.clone(),
+                values: self.row_pos as u64,
+                bytes_out: self.encoded_bytes,
+                min_max: self.min_max.take(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<T, W> StreamColumn<T, W>
+where
+    T: PartialOrd + Clone + 'static,
+    W: Write,
+{
+    /// Calls `cb` with a `CloseSummary` once the stream has ended, by whichever `close` or
+    /// `finish` method, e.g. to log it. Values pushed from now on count towards its
+    /// `min_max`, so set this before the first push.
+    pub fn set_on_close(&mut self, cb: impl FnOnce(&CloseSummary<T>) + 'static) {
+        self.on_close = Some(Box::new(cb));
+        self.track_min_max = Some(|min_max, v| match min_max {
+            Some((min, max)) => {
+                if *v < *min {
+                    *min = v.clone();
+                } else if *v > *max {
+                    *max = v.clone();
+                }
+            }
+            None => *min_max = Some((v.clone(), v.clone())),
+        });
+    }
+}
+
+/// How a derived streaming bundle opens its column files, for its generated
+/// `with_options`. The default matches `with_pool`.
+#[derive(Clone, Default)]
+pub struct StreamBundleOptions {
+    /// Directory the bundle writes its `StructName` directory into, in place of the
+    /// struct's `base_path` attribute.
+    pub base_path: Option<PathBuf>,
+    /// Every column takes permits from this limiter for the files it may hold open, as
+    /// `StreamColumn::with_fd_limiter` counts them, and keeps them until the bundle closes;
+    /// a `single_file` bundle takes one more for the file it writes on close. The cap must
+    /// cover the whole bundle, e.g. 3 per bitpack column of a per-field bundle and 2 of a
+    /// single-file one, or opening fails with `WouldBlock` rather than exceed it.
+    pub fd_limiter: Option<FdLimiter>,
+}
+
+impl StreamBundleOptions {
+    pub fn with_base_path<P: Into<PathBuf>>(mut self, base_path: P) -> Self {
+        self.base_path = Some(base_path.into());
+        self
+    }
+
+    pub fn with_fd_limiter(mut self, limiter: FdLimiter) -> Self {
+        self.fd_limiter = Some(limiter);
+        self
     }
 }
 
 pub trait StreamingColumnBundle<Row> {
     fn push(&mut self, row: &Row) -> io::Result<()>;
+
+    /// Ends every column's stream and writes a `manifest.json` describing them into the
+    /// bundle's directory. The default does nothing, for bundles with nothing to finish.
+    fn close(self) -> io::Result<()>
+    where
+        Self: Sized,
+    {
+        Ok(())
+    }
 }
 
 pub trait StreamingColumnar: Sized {
@@ -92,3 +468,208 @@ pub trait StreamingColumnar: Sized {
         cols
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::BitpackStreamWriter;
+    use crate::encoding::bitpack::v1::page_reader::PageDecoder;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_stream_column_into_memory() -> io::Result<()> {
+        let pool = SmartBufferPool::default();
+        let values: Vec<u32> = (0..20_000).map(|v| v % 1_000).collect();
+
+        let mut column = StreamColumn::from_writer(
+            Cursor::new(Vec::new()),
+            pool.clone(),
+            Box::new(BitpackStreamWriter::<u32>::new(pool.clone())),
+            None,
+            PathBuf::new(),
+        )?;
+        for v in &values {
+            column.push(v)?;
+        }
+        let bytes = column.finish()?.into_inner();
+
+        let decoded =
+            PageDecoder::<_, u32>::new(pool, Cursor::new(bytes)).collect::<io::Result<Vec<_>>>()?;
+        assert_eq!(decoded, values);
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_column_open_append() -> io::Result<()> {
+        let pool = SmartBufferPool::default();
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("values.bin");
+
+        let mut column = StreamColumn::new(
+            &path,
+            pool.clone(),
+            Box::new(BitpackStreamWriter::<u32>::new(pool.clone())),
+            None,
+            dir.path().to_path_buf(),
+        )?;
+        for v in 0..100u32 {
+            column.push(&v)?;
+        }
+        column.close()?;
+
+        let mut column = StreamColumn::open_append(
+            &path,
+            Box::new(BitpackStreamWriter::<u32>::new(pool.clone())),
+            pool.clone(),
+        )?;
+        // wider values than the first batch, so the appended page uses another width.
+        for v in 100..150u32 {
+            column.push(&(v * 1_000))?;
+        }
+        column.close()?;
+
+        let decoded =
+            PageDecoder::<_, u32>::new(pool, File::open(&path)?).collect::<io::Result<Vec<_>>>()?;
+        let expected: Vec<u32> = (0..100).chain((100..150).map(|v| v * 1_000)).collect();
+        assert_eq!(decoded, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_column_open_append_strips_footer() -> io::Result<()> {
+        let pool = SmartBufferPool::default();
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("values.bin");
+
+        let mut column = StreamColumn::new(
+            &path,
+            pool.clone(),
+            Box::new(BitpackStreamWriter::<u32>::new(pool.clone()).with_footer(true)),
+            None,
+            dir.path().to_path_buf(),
+        )?;
+        for v in 0..100u32 {
+            column.push(&v)?;
+        }
+        column.close()?;
+
+        let mut column = StreamColumn::open_append(
+            &path,
+            Box::new(BitpackStreamWriter::<u32>::new(pool.clone())),
+            pool.clone(),
+        )?;
+        for v in 100..150u32 {
+            column.push(&v)?;
+        }
+        column.close()?;
+
+        // with the footer left in place the decoder would stop after the first 100.
+        let decoded =
+            PageDecoder::<_, u32>::new(pool, File::open(&path)?).collect::<io::Result<Vec<_>>>()?;
+        assert_eq!(decoded, (0..150).collect::<Vec<_>>());
+        let mut file = File::open(&path)?;
+        assert_eq!(StreamFooter::read_from_end(&mut file)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_column_on_close_summary() -> io::Result<()> {
+        use std::sync::{Arc, Mutex};
+
+        let pool = SmartBufferPool::default();
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("values.bin");
+        let mut column = StreamColumn::new(
+            &path,
+            pool.clone(),
+            Box::new(BitpackStreamWriter::<u32>::new(pool)),
+            None,
+            dir.path().to_path_buf(),
+        )?;
+        let summary = Arc::new(Mutex::new(None));
+        let sink = summary.clone();
+        column.set_on_close(move |s| *sink.lock().unwrap() = Some(s.clone()));
+        for v in [40u32, 7, 99, 12] {
+            column.push(&v)?;
+        }
+        column.close()?;
+
+        let summary = summary.lock().unwrap().take().expect("callback not called");
+        assert_eq!(summary.path, Some(path.clone()));
+        assert_eq!(summary.values, 4);
+        assert_eq!(summary.bytes_out, fs::metadata(&path)?.len());
+        assert!(summary.bytes_out > 0);
+        assert_eq!(summary.min_max, Some((7, 99)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_column_close_with_index() -> io::Result<()> {
+        use crate::encoding::bitpack::v1::{
+            common::PAGE_DEFAULT_SIZE, page_writer::values_per_page,
+        };
+
+        let pool = SmartBufferPool::default();
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("values.bin");
+        // 17-bit values, so the column spans four default-sized pages.
+        let values: Vec<u32> = (0..100_000u32).map(|v| v * 7 % 100_003).collect();
+
+        let mut column = StreamColumn::new(
+            &path,
+            pool.clone(),
+            Box::new(BitpackStreamWriter::<u32>::new(pool.clone()).with_footer(false)),
+            None,
+            dir.path().to_path_buf(),
+        )?;
+        for v in &values {
+            column.push(v)?;
+        }
+        column.close_with_index()?;
+
+        let index_path = dir.path().join("values.pidx");
+        assert!(path.exists() && index_path.exists());
+        assert!(!dir.path().join("values.pidx.tmp").exists());
+
+        let index = PageIndex::read_from(&mut File::open(&index_path)?)?;
+        let per_page = values_per_page(17, PAGE_DEFAULT_SIZE) as u64;
+        assert_eq!(index.total_values, values.len() as u64);
+        let first_rows: Vec<u64> = index.pages.iter().map(|&(row, _)| row).collect();
+        assert_eq!(first_rows, [0, per_page, 2 * per_page, 3 * per_page]);
+
+        for row in [0, per_page - 1, per_page, 65_432, values.len() as u64 - 1] {
+            let (offset, in_page) = index.locate(row).unwrap();
+            let mut file = File::open(&path)?;
+            file.seek(SeekFrom::Start(offset))?;
+            let value = PageDecoder::<_, u32>::new(pool.clone(), file)
+                .nth(in_page)
+                .unwrap()?;
+            assert_eq!(value, values[row as usize], "row {row}");
+        }
+        assert_eq!(index.locate(values.len() as u64), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_column_close_with_index_rejects_other_encoders() -> io::Result<()> {
+        use crate::encoding::DeltaStreamEncoder;
+
+        let pool = SmartBufferPool::default();
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("values.bin");
+        let mut column = StreamColumn::new(
+            &path,
+            pool,
+            Box::new(DeltaStreamEncoder::new()),
+            None,
+            dir.path().to_path_buf(),
+        )?;
+        for v in 0..10i64 {
+            column.push(&v)?;
+        }
+        let err = column.close_with_index().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(!dir.path().join("values.pidx").exists());
+        Ok(())
+    }
+}
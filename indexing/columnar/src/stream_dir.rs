@@ -0,0 +1,480 @@
+use crate::buffers::smart_pool::SmartBufferPool;
+use crate::encoding::bitpack::v1::common::BitEncodable;
+use crate::encoding::bitpack::v1::page_reader::PageDecoder;
+use crate::encoding::{CategoricalDecoder, SparseDecoder, StreamingDecoder};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+pub use toolkit::footerfile::{file_decoder::FooterFileDecoder, file_encoder::FooterFileEncoder};
+
+/// Name of the manifest a streaming bundle writes next to its column files.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// File holding every column of a `#[columnar(layout = "single_file")]` bundle, one footer
+/// file section per column, keyed by the column's id from `COLUMN_IDS`.
+pub const SINGLE_FILE: &str = "columns.bin";
+
+/// One column of a streaming bundle, as recorded in the manifest.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestColumn {
+    pub name: String,
+    /// File name relative to the manifest's directory.
+    pub file: String,
+    /// Encoder kind, e.g. `bitpack`, `delta` or `categorical`.
+    pub encoder: String,
+    /// Rust element type, e.g. `u64`.
+    pub type_tag: String,
+    /// Section id within `file` when it is a footer file shared by every column.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<u32>,
+    /// Values the column holds; absent from manifests written before counts were recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub values: Option<u64>,
+}
+
+/// Describes every column a `StreamingColumnar` bundle wrote, so the output can be read
+/// back without the original struct.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StreamManifest {
+    pub columns: Vec<ManifestColumn>,
+}
+
+impl StreamManifest {
+    pub fn write_to<P: AsRef<Path>>(&self, dir: P) -> io::Result<()> {
+        let file = File::create(dir.as_ref().join(MANIFEST_FILE))?;
+        serde_json::to_writer_pretty(BufWriter::new(file), self).map_err(io::Error::other)
+    }
+
+    pub fn read_from<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+        let file = File::open(dir.as_ref().join(MANIFEST_FILE))?;
+        serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+    }
+}
+
+/// Decoded values of a single column, tagged with their element type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnValues {
+    U8(Vec<u8>),
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+    U64(Vec<u64>),
+    I8(Vec<i8>),
+    I16(Vec<i16>),
+    I32(Vec<i32>),
+    I64(Vec<i64>),
+}
+
+impl ColumnValues {
+    pub fn len(&self) -> usize {
+        match self {
+            ColumnValues::U8(v) => v.len(),
+            ColumnValues::U16(v) => v.len(),
+            ColumnValues::U32(v) => v.len(),
+            ColumnValues::U64(v) => v.len(),
+            ColumnValues::I8(v) => v.len(),
+            ColumnValues::I16(v) => v.len(),
+            ColumnValues::I32(v) => v.len(),
+            ColumnValues::I64(v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Reads a streaming bundle's output directory through its manifest.
+pub struct StreamDirReader {
+    dir: PathBuf,
+    manifest: StreamManifest,
+    pool: SmartBufferPool,
+}
+
+impl StreamDirReader {
+    pub fn open<P: Into<PathBuf>>(dir: P) -> io::Result<Self> {
+        let dir = dir.into();
+        let manifest = StreamManifest::read_from(&dir)?;
+        Ok(Self {
+            dir,
+            manifest,
+            pool: SmartBufferPool::default(),
+        })
+    }
+
+    pub fn manifest(&self) -> &StreamManifest {
+        &self.manifest
+    }
+
+    pub fn column(&self, name: &str) -> Option<&ManifestColumn> {
+        self.manifest.columns.iter().find(|c| c.name == name)
+    }
+
+    /// Decodes the named column using the encoder and type recorded in the manifest.
+    pub fn read_column(&self, name: &str) -> io::Result<ColumnValues> {
+        let column = self.require(name)?;
+        macro_rules! decode_as {
+            ($t:ty, $variant:ident) => {
+                self.values::<$t>(name)?
+                    .collect::<io::Result<Vec<_>>>()
+                    .map(ColumnValues::$variant)
+            };
+        }
+        match column.type_tag.as_str() {
+            "u8" => decode_as!(u8, U8),
+            "u16" => decode_as!(u16, U16),
+            "u32" => decode_as!(u32, U32),
+            "u64" => decode_as!(u64, U64),
+            "i8" => decode_as!(i8, I8),
+            "i16" => decode_as!(i16, I16),
+            "i32" => decode_as!(i32, I32),
+            "i64" => decode_as!(i64, I64),
+            ty => Err(unsupported(name, ty, &column.encoder)),
+        }
+    }
+
+    /// Streams the named column one value at a time, decoded with the encoder the manifest
+    /// records. Fails with `InvalidData` when the column holds values of another type.
+    pub fn values<T: BitEncodable + Send + 'static>(
+        &self,
+        name: &str,
+    ) -> io::Result<ColumnIter<T>> {
+        let column = self.require(name)?;
+        if column.type_tag != std::any::type_name::<T>() {
+            return Err(wrong_type::<T>(name, &column.type_tag));
+        }
+        let path = self.dir.join(&column.file);
+        let reader: Box<dyn Read> = match column.section {
+            Some(id) => Box::new(FooterFileDecoder::new(path)?.get_column(id)?),
+            None => Box::new(File::open(path)?),
+        };
+        let reader = BufReader::new(reader);
+        let values: ColumnIter<T> = match column.encoder.as_str() {
+            "bitpack" => Box::new(PageDecoder::<_, T>::new(self.pool.clone(), reader)),
+            "categorical" => Box::new(DecodedValues::new(CategoricalDecoder::<T>::new(), reader)),
+            "sparse" => Box::new(DecodedValues::new(SparseDecoder::<T>::new(), reader)),
+            "delta" if column.type_tag == "i64" => {
+                let deltas: Box<dyn Any> =
+                    Box::new(Box::new(DeltaValues { reader, prev: 0 }) as ColumnIter<i64>);
+                // the type tag check above makes `T` i64 here.
+                *deltas.downcast::<ColumnIter<T>>().unwrap()
+            }
+            encoder => return Err(unsupported(name, &column.type_tag, encoder)),
+        };
+        Ok(values)
+    }
+
+    /// Decodes the named column like `read_column`, as a `Vec<T>`. Fails with
+    /// `InvalidData` when the column holds values of another type.
+    pub fn read_values<T: Any>(&self, name: &str) -> io::Result<Vec<T>> {
+        let values: Box<dyn Any> = match self.read_column(name)? {
+            ColumnValues::U8(v) => Box::new(v),
+            ColumnValues::U16(v) => Box::new(v),
+            ColumnValues::U32(v) => Box::new(v),
+            ColumnValues::U64(v) => Box::new(v),
+            ColumnValues::I8(v) => Box::new(v),
+            ColumnValues::I16(v) => Box::new(v),
+            ColumnValues::I32(v) => Box::new(v),
+            ColumnValues::I64(v) => Box::new(v),
+        };
+        values.downcast::<Vec<T>>().map(|v| *v).map_err(|_| {
+            let found = self.column(name).map_or("", |c| c.type_tag.as_str());
+            wrong_type::<T>(name, found)
+        })
+    }
+
+    /// Describes every column for debugging, one line each: the manifest columns with their
+    /// encoder, type and first `limit` values, then any section of the single-file bundle
+    /// that no manifest column claims, which carries no tag to decode it with.
+    pub fn dump(&self, limit: usize) -> io::Result<String> {
+        let mut lines = Vec::new();
+        for column in &self.manifest.columns {
+            let kind = format!("{} {}", column.encoder, column.type_tag);
+            let Some(head) = self.head(column, limit)? else {
+                lines.push(format!("{} ({kind}): <not decodable>", column.name));
+                continue;
+            };
+            match column.values {
+                Some(n) => lines.push(format!("{} ({kind}, {n} values): {head}", column.name)),
+                None => lines.push(format!("{} ({kind}): {head}", column.name)),
+            }
+        }
+
+        let single = self.dir.join(SINGLE_FILE);
+        if single.exists() {
+            let claimed: HashSet<u32> = self
+                .manifest
+                .columns
+                .iter()
+                .filter_map(|c| c.section)
+                .collect();
+            let decoder = FooterFileDecoder::new(single)?;
+            for column in decoder.iter_columns() {
+                let (meta, _) = column?;
+                if !claimed.contains(&meta.id) {
+                    lines.push(format!(
+                        "section {}: {} bytes, not in the manifest",
+                        meta.id, meta.size
+                    ));
+                }
+            }
+        }
+        Ok(lines.join("\n"))
+    }
+
+    /// The first `limit` values of `column`, formatted like a slice, decoding no further.
+    /// `None` for columns `values` cannot decode, such as strings.
+    fn head(&self, column: &ManifestColumn, limit: usize) -> io::Result<Option<String>> {
+        fn head<T: BitEncodable + Send + std::fmt::Debug + 'static>(
+            reader: &StreamDirReader,
+            name: &str,
+            limit: usize,
+        ) -> io::Result<Option<String>> {
+            let values = match reader.values::<T>(name) {
+                Ok(values) => values,
+                Err(e) if e.kind() == ErrorKind::Unsupported => return Ok(None),
+                Err(e) => return Err(e),
+            };
+            let values = values.take(limit).collect::<io::Result<Vec<_>>>()?;
+            Ok(Some(format!("{values:?}")))
+        }
+        let name = column.name.as_str();
+        match column.type_tag.as_str() {
+            "u8" => head::<u8>(self, name, limit),
+            "u16" => head::<u16>(self, name, limit),
+            "u32" => head::<u32>(self, name, limit),
+            "u64" => head::<u64>(self, name, limit),
+            "i8" => head::<i8>(self, name, limit),
+            "i16" => head::<i16>(self, name, limit),
+            "i32" => head::<i32>(self, name, limit),
+            "i64" => head::<i64>(self, name, limit),
+            _ => Ok(None),
+        }
+    }
+
+    fn require(&self, name: &str) -> io::Result<&ManifestColumn> {
+        self.column(name).ok_or_else(|| {
+            io::Error::new(
+                ErrorKind::NotFound,
+                format!("column {name} is not in the manifest"),
+            )
+        })
+    }
+}
+
+fn unsupported(name: &str, ty: &str, encoder: &str) -> io::Error {
+    io::Error::new(
+        ErrorKind::Unsupported,
+        format!("column {name}: cannot decode {ty} values written by {encoder}"),
+    )
+}
+
+fn wrong_type<T>(name: &str, found: &str) -> io::Error {
+    io::Error::new(
+        ErrorKind::InvalidData,
+        format!(
+            "column {name} holds {found} values, not {}",
+            std::any::type_name::<T>()
+        ),
+    )
+}
+
+/// A column's values as `StreamDirReader::values` decodes them, one at a time.
+pub type ColumnIter<T> = Box<dyn Iterator<Item = io::Result<T>>>;
+
+/// Values of a single stream written by a `StreamingEncoder`, decoded on demand. Ends after
+/// the first error.
+struct DecodedValues<T, D, R> {
+    decoder: D,
+    reader: R,
+    started: bool,
+    done: bool,
+    _values: PhantomData<T>,
+}
+
+impl<T, D: StreamingDecoder<T>, R: Read> DecodedValues<T, D, R> {
+    fn new(decoder: D, reader: R) -> Self {
+        Self {
+            decoder,
+            reader,
+            started: false,
+            done: false,
+            _values: PhantomData,
+        }
+    }
+
+    fn step(&mut self) -> io::Result<Option<T>> {
+        if !self.started {
+            self.started = true;
+            self.decoder.begin_stream(&mut self.reader)?;
+        }
+        let value = self.decoder.decode_next(&mut self.reader)?;
+        if value.is_none() {
+            self.decoder.end_stream(&mut self.reader)?;
+        }
+        Ok(value)
+    }
+}
+
+impl<T, D: StreamingDecoder<T>, R: Read> Iterator for DecodedValues<T, D, R> {
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let step = self.step();
+        self.done = !matches!(step, Ok(Some(_)));
+        step.transpose()
+    }
+}
+
+/// Running sums of the little-endian `i64` deltas `DeltaStreamEncoder` writes.
+struct DeltaValues<R> {
+    reader: R,
+    prev: i64,
+}
+
+impl<R: Read> Iterator for DeltaValues<R> {
+    type Item = io::Result<i64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut buf = [0u8; 8];
+        let mut filled = 0;
+        while filled < buf.len() {
+            match self.reader.read(&mut buf[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        match filled {
+            0 => None,
+            8 => {
+                // the encoder's deltas wrap, so a corrupt file must not overflow here.
+                self.prev = self.prev.wrapping_add(i64::from_le_bytes(buf));
+                Some(Ok(self.prev))
+            }
+            _ => Some(Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("delta stream ends inside a value, after {filled} of its 8 bytes"),
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = StreamManifest {
+            columns: vec![ManifestColumn {
+                name: "id".to_string(),
+                file: "id.bin".to_string(),
+                encoder: "delta".to_string(),
+                type_tag: "i64".to_string(),
+                section: None,
+                values: Some(3),
+            }],
+        };
+        manifest.write_to(dir.path()).unwrap();
+
+        let mut data = Vec::new();
+        for delta in [5i64, 1, -2] {
+            data.extend_from_slice(&delta.to_le_bytes());
+        }
+        std::fs::write(dir.path().join("id.bin"), data).unwrap();
+
+        let reader = StreamDirReader::open(dir.path()).unwrap();
+        assert_eq!(reader.manifest(), &manifest);
+        assert_eq!(
+            reader.read_column("id").unwrap(),
+            ColumnValues::I64(vec![5, 6, 4])
+        );
+        let err = reader.read_column("missing").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        assert_eq!(reader.read_values::<i64>("id").unwrap(), vec![5, 6, 4]);
+        let err = reader.read_values::<u32>("id").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "column id holds i64 values, not u32");
+    }
+
+    #[test]
+    fn test_truncated_delta_column_is_invalid() {
+        let dir = tempfile::tempdir().unwrap();
+        StreamManifest {
+            columns: vec![ManifestColumn {
+                name: "id".to_string(),
+                file: "id.bin".to_string(),
+                encoder: "delta".to_string(),
+                type_tag: "i64".to_string(),
+                section: None,
+                values: Some(3),
+            }],
+        }
+        .write_to(dir.path())
+        .unwrap();
+
+        let mut data = Vec::new();
+        for delta in [i64::MAX, 1, -2] {
+            data.extend_from_slice(&delta.to_le_bytes());
+        }
+        data.truncate(data.len() - 3);
+        std::fs::write(dir.path().join("id.bin"), data).unwrap();
+
+        let reader = StreamDirReader::open(dir.path()).unwrap();
+        let values: Vec<_> = reader.values::<i64>("id").unwrap().collect();
+        assert_eq!(values.len(), 3);
+        // the second delta wraps instead of overflowing.
+        assert_eq!(values[1].as_ref().unwrap(), &i64::MIN);
+        let err = values[2].as_ref().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert_eq!(
+            reader.read_column("id").unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn test_dump_single_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut data = Vec::new();
+        for delta in [5i64, 1, -2] {
+            data.extend_from_slice(&delta.to_le_bytes());
+        }
+        let mut encoder = FooterFileEncoder::create(dir.path().join(SINGLE_FILE)).unwrap();
+        encoder.write(0, &mut io::Cursor::new(data)).unwrap();
+        encoder
+            .write(99, &mut io::Cursor::new(vec![0u8; 3]))
+            .unwrap();
+        encoder.close().unwrap();
+
+        StreamManifest {
+            columns: vec![ManifestColumn {
+                name: "id".to_string(),
+                file: SINGLE_FILE.to_string(),
+                encoder: "delta".to_string(),
+                type_tag: "i64".to_string(),
+                section: Some(0),
+                values: Some(3),
+            }],
+        }
+        .write_to(dir.path())
+        .unwrap();
+
+        let reader = StreamDirReader::open(dir.path()).unwrap();
+        assert_eq!(
+            reader.dump(2).unwrap(),
+            "id (delta i64, 3 values): [5, 6]\nsection 99: 3 bytes, not in the manifest"
+        );
+    }
+}
@@ -0,0 +1,49 @@
+use columnar::SmartBufferPool;
+use columnar::encoding::bitpack::v1::page_reader::decode_slice;
+use columnar::encoding::bitpack::v1::page_writer::PageEncoder;
+use std::io;
+use toolkit::footerfile::file_decoder::FooterFileDecoder;
+use toolkit::footerfile::file_encoder::FooterFileEncoder;
+
+fn encode_pages(pool: &SmartBufferPool, values: &[u32], width: u8) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    for page in PageEncoder::new(pool.clone(), values.iter().copied(), width, 512) {
+        bytes.extend_from_slice(page?.as_slice());
+    }
+    Ok(bytes)
+}
+
+#[test]
+fn test_decode_slice_u32_column_roundtrip() -> io::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("columns.bin");
+    let pool = SmartBufferPool::default();
+
+    let ids: Vec<u32> = (0..10_000).collect();
+    let scores: Vec<u32> = (0..10_000).map(|v| (v * 31) % 4096).collect();
+
+    let mut encoder = FooterFileEncoder::create(path.clone())?;
+    encoder.write(1, &mut encode_pages(&pool, &ids, 14)?.as_slice())?;
+    encoder.write(2, &mut encode_pages(&pool, &scores, 12)?.as_slice())?;
+    encoder.close()?;
+
+    let mut decoder = FooterFileDecoder::new(path)?;
+
+    // column 2 sits after column 1, so this also checks the slice offsets.
+    let decoded = decode_slice::<u32>(decoder.get_column(2)?, pool.clone())
+        .collect::<io::Result<Vec<_>>>()?;
+    assert_eq!(decoded, scores);
+
+    let mut lazy = decode_slice::<u32>(decoder.get_column(1)?, pool);
+    let mut chunk = [0u32; 256];
+    let mut decoded = Vec::new();
+    loop {
+        let n = lazy.read_values(&mut chunk)?;
+        if n == 0 {
+            break;
+        }
+        decoded.extend_from_slice(&chunk[..n]);
+    }
+    assert_eq!(decoded, ids);
+    Ok(())
+}
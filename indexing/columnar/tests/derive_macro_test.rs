@@ -1,4 +1,4 @@
-use columnar::Columnar; // trait re-exported from columnar crate
+use columnar::{ColumnBundle, Columnar, SimpleColumnBundle, SimpleColumnar}; // traits re-exported from columnar crate
 
 #[derive(Columnar, Default, Debug, Clone)]
 pub struct TestStruct {
@@ -39,3 +39,1121 @@ fn test_columnar_macro_and_runtime() {
     assert_eq!(collected_a, vec![1, 2, 3]);
     assert_eq!(collected_b, vec![1.0, 2.0, 3.0]);
 }
+
+#[test]
+fn test_columnar_merge_many_matches_pairwise() {
+    let shards = || {
+        (0..8)
+            .map(|s| {
+                let rows: Vec<TestStruct> = (0..10 + s)
+                    .map(|i| TestStruct {
+                        a: s * 100 + i,
+                        b: i as f32,
+                    })
+                    .collect();
+                TestStruct::to_columns(&rows)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut pairwise = TestStructColumns::default();
+    for shard in shards() {
+        pairwise.merge(shard);
+    }
+    let mut merged = TestStructColumns::default();
+    merged.merge_many(shards());
+
+    assert_eq!(
+        merged.a.len(),
+        (0..8).map(|s| 10 + s as usize).sum::<usize>()
+    );
+    assert_eq!(merged.a.chunks, pairwise.a.chunks);
+    assert_eq!(merged.b.chunks, pairwise.b.chunks);
+}
+
+#[derive(SimpleColumnar, Default, Debug, Clone)]
+pub struct ListStruct {
+    pub id: u32,
+    #[columnar(list)]
+    pub skills: Vec<u16>,
+}
+
+#[test]
+fn test_simple_list_column_layout() {
+    let rows = vec![
+        ListStruct {
+            id: 1,
+            skills: vec![10, 11, 12],
+        },
+        ListStruct {
+            id: 2,
+            skills: vec![],
+        },
+        ListStruct {
+            id: 3,
+            skills: vec![30],
+        },
+        ListStruct {
+            id: 4,
+            skills: vec![40, 41],
+        },
+    ];
+
+    let cols = ListStruct::to_simple_columns(&rows);
+
+    assert_eq!(cols.id.0, vec![1, 2, 3, 4]);
+    assert_eq!(cols.skills.values, vec![10, 11, 12, 30, 40, 41]);
+    assert_eq!(cols.skills.offsets, vec![0, 3, 3, 4, 6]);
+    assert_eq!(cols.skills.len(), rows.len());
+
+    let rebuilt: Vec<Vec<u16>> = cols.skills.iter().map(|s| s.to_vec()).collect();
+    let expected: Vec<Vec<u16>> = rows.iter().map(|r| r.skills.clone()).collect();
+    assert_eq!(rebuilt, expected);
+    assert_eq!(cols.skills.get(1), Some(&[][..]));
+    assert_eq!(cols.skills.get(4), None);
+}
+
+#[test]
+fn test_simple_bundle_clone_is_independent() {
+    let original = ListStruct::to_simple_columns(&[ListStruct {
+        id: 1,
+        skills: vec![1, 2],
+    }]);
+    let mut snapshot = original.clone();
+    snapshot.merge(ListStruct::to_simple_columns(&[ListStruct {
+        id: 2,
+        skills: vec![3],
+    }]));
+
+    assert_eq!(original.id.0, vec![1]);
+    assert_eq!(original.skills.values, vec![1, 2]);
+    assert_eq!(snapshot.id.0, vec![1, 2]);
+    assert_eq!(snapshot.skills.values, vec![1, 2, 3]);
+}
+
+#[test]
+fn test_simple_list_column_merge() {
+    let a = ListStruct::to_simple_columns(&[ListStruct {
+        id: 1,
+        skills: vec![1, 2],
+    }]);
+    let mut merged = a;
+    merged.merge(ListStruct::to_simple_columns(&[ListStruct {
+        id: 2,
+        skills: vec![3],
+    }]));
+
+    assert_eq!(merged.skills.values, vec![1, 2, 3]);
+    assert_eq!(merged.skills.offsets, vec![0, 2, 3]);
+    assert_eq!(merged.skills.get(1), Some(&[3u16][..]));
+}
+
+#[derive(SimpleColumnar, Debug, Clone)]
+pub struct HistogramStruct {
+    #[columnar(histogram = 4)]
+    pub score: u32,
+    #[columnar(rename = "temp", histogram = 2)]
+    pub temperature: f32,
+    pub id: u32,
+}
+
+#[test]
+fn test_simple_histogram_counts() {
+    // scores 0..100 with an extra spike in the top quarter
+    let rows: Vec<HistogramStruct> = (0..100u32)
+        .chain(std::iter::repeat_n(90, 20))
+        .map(|score| HistogramStruct {
+            score,
+            temperature: if score < 50 { -1.0 } else { 1.0 },
+            id: score,
+        })
+        .collect();
+    let cols = HistogramStruct::to_simple_columns(&rows);
+
+    let hist = cols.histogram("score").expect("score has a histogram");
+    assert_eq!(hist.min, 0.0);
+    assert_eq!(hist.max, 99.0);
+    assert_eq!(hist.total(), rows.len() as u64);
+    assert_eq!(hist.counts.len(), 4);
+    assert!(hist.counts[..3].iter().all(|c| (24..=26).contains(c)));
+    assert!((44..=46).contains(&hist.counts[3]));
+
+    let hist = cols.histogram("temp").expect("temp has a histogram");
+    assert_eq!(hist.counts, vec![50, 70]);
+
+    assert!(cols.histogram("id").is_none());
+}
+
+#[derive(SimpleColumnar, Debug, Clone)]
+pub struct LatencyStruct {
+    #[columnar(quantiles)]
+    pub micros: u32,
+    #[columnar(rename = "load", quantiles)]
+    pub cpu: f64,
+}
+
+#[test]
+fn test_simple_quantile_sketch_estimates_median() {
+    // micros is uniform over 0..50_000, cpu is skewed towards 0.
+    let rows: Vec<LatencyStruct> = (0..50_000u32)
+        .map(|i| LatencyStruct {
+            micros: i.wrapping_mul(40_503) % 50_000,
+            cpu: ((i % 100) as f64 / 100.0).powi(2),
+        })
+        .collect();
+    let cols = LatencyStruct::to_simple_columns(&rows);
+
+    let median = cols.micros_quantile(0.5).unwrap() as f64;
+    assert!((median - 25_000.0).abs() < 1_000.0, "median {median}");
+    let p95 = cols.micros_quantile(0.95).unwrap() as f64;
+    assert!((p95 - 47_500.0).abs() < 1_000.0, "p95 {p95}");
+    // true median of ((i % 100) / 100)^2 sits between 0.49^2 and 0.5^2.
+    let load = cols.load_quantile(0.5).unwrap();
+    assert!((load - 0.245).abs() < 0.03, "load median {load}");
+    assert_eq!(cols.micros_quantiles.len(), 50_000);
+
+    // shards merge their sketches into an estimate of the same quality.
+    let cols = LatencyStruct::to_simple_columns_parallel(&rows, 4);
+    assert_eq!(cols.micros_quantiles.len(), 50_000);
+    let median = cols.micros_quantile(0.5).unwrap() as f64;
+    assert!(
+        (median - 25_000.0).abs() < 1_000.0,
+        "sharded median {median}"
+    );
+}
+
+#[test]
+fn test_simple_parallel_build_is_deterministic() {
+    let rows: Vec<ListStruct> = (0..5_000u32)
+        .map(|id| ListStruct {
+            id,
+            skills: (0..(id % 5) as u16).map(|s| s * 7 + id as u16).collect(),
+        })
+        .collect();
+    let expected = ListStruct::to_simple_columns(&rows);
+
+    for run in 0..50 {
+        let shards = 1 + run % 9;
+        let cols = ListStruct::to_simple_columns_parallel(&rows, shards);
+        assert_eq!(cols.id.0, expected.id.0, "run {run} with {shards} shards");
+        assert_eq!(cols.skills.values, expected.skills.values);
+        assert_eq!(cols.skills.offsets, expected.skills.offsets);
+    }
+
+    let empty = ListStruct::to_simple_columns_parallel(&[], 4);
+    assert!(empty.id.0.is_empty());
+    assert!(empty.skills.is_empty());
+}
+
+#[derive(Columnar, Debug, Clone)]
+#[columnar(chunk_size = 4)]
+pub struct BatchStruct {
+    pub id: u64,
+    pub score: f32,
+}
+
+#[test]
+fn test_columnar_clear_reuses_allocations() {
+    let batch = |start: u64| -> Vec<BatchStruct> {
+        (start..start + 10)
+            .map(|id| BatchStruct {
+                id,
+                score: id as f32 / 2.0,
+            })
+            .collect()
+    };
+
+    let mut cols = BatchStruct::to_columns(&batch(0));
+    assert_eq!(cols.id.len(), 10);
+    let id_capacity = cols.id.capacity();
+    let score_capacity = cols.score.capacity();
+    assert!(id_capacity >= 10);
+
+    cols.clear();
+    assert_eq!(cols.id.len(), 0);
+    assert_eq!(cols.score.len(), 0);
+    assert!(cols.id.is_empty());
+    assert_eq!(cols.id.capacity(), id_capacity);
+    assert_eq!(cols.score.capacity(), score_capacity);
+
+    for row in batch(100) {
+        cols.push(&row);
+    }
+    let ids: Vec<u64> = cols.id.chunks.iter().flatten().copied().collect();
+    assert_eq!(ids, (100..110).collect::<Vec<_>>());
+    assert!(cols.id.chunks.iter().all(|c| c.len() <= 4));
+    // the second batch fits in the retained chunks.
+    assert_eq!(cols.id.capacity(), id_capacity);
+}
+
+#[derive(SimpleColumnar, Debug, Clone, PartialEq)]
+pub struct RenamedStruct {
+    pub id: u32,
+    #[columnar(rename = "foo")]
+    pub label: String,
+    #[columnar(list)]
+    pub tags: Vec<u8>,
+}
+
+#[test]
+fn test_simple_rename_roundtrips_to_original_field() {
+    let rows = vec![
+        RenamedStruct {
+            id: 1,
+            label: "a".into(),
+            tags: vec![1, 2],
+        },
+        RenamedStruct {
+            id: 2,
+            label: "b".into(),
+            tags: vec![],
+        },
+    ];
+    let cols = RenamedStruct::to_simple_columns(&rows);
+
+    // the column carries the renamed identifier...
+    assert_eq!(
+        RenamedStructVecColumns::column_names(),
+        ["id", "foo", "tags"]
+    );
+    assert_eq!(cols.foo.0, vec!["a".to_string(), "b".to_string()]);
+
+    // ...and reconstruction writes it back to `label`.
+    let rebuilt: Vec<RenamedStruct> = (0..rows.len()).map(|i| cols.row(i).unwrap()).collect();
+    assert_eq!(rebuilt, rows);
+    assert!(cols.row(rows.len()).is_none());
+}
+
+#[test]
+fn test_consistency_check_names_desynced_field() {
+    let rows = vec![RenamedStruct {
+        id: 1,
+        label: "a".into(),
+        tags: vec![1],
+    }];
+    let mut cols = RenamedStruct::to_simple_columns(&rows);
+    assert!(cols.assert_consistent().is_ok());
+    assert_eq!(cols.try_iter_rows().unwrap().count(), 1);
+
+    // a value pushed past the other columns would pair with a stale row.
+    cols.foo.0.push("stray".into());
+    let err = cols.assert_consistent().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(err.to_string().contains("`label`"), "{err}");
+    assert!(cols.try_iter_rows().is_err());
+
+    let mut cols = TestStruct::to_columns(&[TestStruct { a: 1, b: 1.0 }]);
+    assert!(cols.assert_consistent().is_ok());
+    cols.b.push(&2.0);
+    let err = cols.assert_consistent().unwrap_err();
+    assert!(err.to_string().contains("`b` has 2 values"), "{err}");
+}
+
+#[test]
+fn test_position_rows_serialize_to_json() {
+    use columnar::models::position::{Position, PositionVecColumns};
+
+    let rows = vec![
+        Position {
+            rcid: 42,
+            seniority: 3,
+            mapped_skills_v3: vec![7, 9],
+            raw_title: "Data Engineer".into(),
+            ..Default::default()
+        },
+        Position {
+            rcid: 43,
+            ..Default::default()
+        },
+    ];
+    let cols: PositionVecColumns = Position::to_simple_columns(&rows);
+    assert_eq!(cols.len(), 2);
+
+    let rebuilt: Vec<Position> = cols.iter_rows().collect();
+    assert_eq!(rebuilt, rows);
+
+    let json = serde_json::to_value(&rebuilt[0]).unwrap();
+    assert_eq!(json["rcid"], 42);
+    assert_eq!(json["seniority"], 3);
+    assert_eq!(json["skill_v3_id_list"], serde_json::json!([7, 9]));
+    assert_eq!(json["title_raw"], "Data Engineer");
+}
+
+#[test]
+fn test_columnar_with_chunk_size_override() {
+    let rows: Vec<Position> = (0..250)
+        .map(|i| Position {
+            rcid: i,
+            company_id: 0,
+            title: String::new(),
+            salary: 0.0,
+        })
+        .collect();
+    let cols = Position::to_columns_with_chunk_size(&rows, 100);
+
+    let sizes: Vec<usize> = cols.rcid.chunks.iter().map(Vec::len).collect();
+    assert_eq!(sizes, [100, 100, 50]);
+    assert_eq!(cols.rcid.chunk_size, 100);
+    assert!(cols.title.chunks.iter().all(|c| c.len() <= 100));
+    assert_eq!(cols.rcid.chunks[2][49], 249);
+}
+
+#[test]
+fn test_to_columns_batched() {
+    let rows = (0..2500).map(|i| TestStruct { a: i, b: i as f32 });
+    let mut sizes = Vec::new();
+    let mut firsts = Vec::new();
+    TestStruct::to_columns_batched(rows, 1000, |cols| {
+        sizes.push(cols.a.len());
+        firsts.push(*cols.a.first().unwrap());
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(sizes, [1000, 1000, 500]);
+    assert_eq!(firsts, [0, 1000, 2000]);
+}
+
+#[derive(SimpleColumnar, Debug, Clone)]
+pub struct OrderedStruct {
+    pub zeta: u8,
+    #[columnar(rename = "alpha")]
+    pub middle: u16,
+    #[columnar(skip)]
+    pub ignored: u32,
+    pub omega: u64,
+}
+
+#[test]
+fn test_simple_field_order_matches_declaration() {
+    assert_eq!(
+        OrderedStructVecColumns::FIELD_ORDER,
+        ["zeta", "alpha", "omega"]
+    );
+    // every derive generates it, from the same field walk as the column ids.
+    assert_eq!(InvoiceColumns::FIELD_ORDER, ["number", "total", "currency"]);
+    assert_eq!(
+        InvoiceColumns::FIELD_ORDER,
+        InvoiceVecColumns::COLUMN_IDS
+            .iter()
+            .map(|&(name, _)| name)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(ReadingStreamColumn::FIELD_ORDER, ["sensor", "level"]);
+
+    let rows = vec![OrderedStruct {
+        zeta: 1,
+        middle: 2,
+        ignored: 3,
+        omega: 4,
+    }];
+    let cols = OrderedStruct::to_simple_columns(&rows);
+    assert_eq!(
+        (cols.zeta.0[0], cols.alpha.0[0], cols.omega.0[0]),
+        (1, 2, 4)
+    );
+}
+
+/// Pushes `rows` through `columns` and closes it.
+fn write_rows<R>(mut columns: impl columnar::StreamingColumnBundle<R>, rows: &[R]) {
+    for row in rows {
+        columns.push(row).unwrap();
+    }
+    columns.close().unwrap();
+}
+
+#[derive(columnar_derive::StreamingColumnar, Debug, Clone)]
+pub struct Reading {
+    pub sensor: u32,
+    #[columnar(rename = "level")]
+    pub value: i16,
+}
+
+#[test]
+fn test_stream_manifest_decodes_named_column() {
+    use columnar::{ColumnValues, StreamDirReader};
+
+    let rows: Vec<Reading> = (0..100)
+        .map(|i| Reading {
+            sensor: i * 3,
+            value: i as i16 - 50,
+        })
+        .collect();
+    let dir = tempfile::tempdir().unwrap();
+    write_rows(ReadingStreamColumn::create_in(dir.path()).unwrap(), &rows);
+
+    let reader = StreamDirReader::open(dir.path().join("Reading")).unwrap();
+    let level = reader.column("level").unwrap();
+    assert_eq!(
+        (
+            level.file.as_str(),
+            level.encoder.as_str(),
+            level.type_tag.as_str()
+        ),
+        ("level.bin", "bitpack", "i16")
+    );
+    let values = reader.read_column("level").unwrap();
+
+    let expected: Vec<i16> = rows.iter().map(|r| r.value).collect();
+    assert_eq!(values, ColumnValues::I16(expected));
+}
+
+#[derive(columnar_derive::StreamingColumnar, Debug, Clone)]
+pub struct Staff {
+    pub id: u32,
+    #[columnar(encoder = "categorical")]
+    pub seniority: u16,
+}
+
+#[test]
+fn test_stream_categorical_encoder_attribute() {
+    use columnar::{ColumnValues, StreamDirReader};
+
+    let rows: Vec<Staff> = (0..50)
+        .map(|i| Staff {
+            id: i,
+            seniority: [1, 2, 3, 5, 8][i as usize % 5],
+        })
+        .collect();
+    let dir = tempfile::tempdir().unwrap();
+    write_rows(StaffStreamColumn::create_in(dir.path()).unwrap(), &rows);
+
+    let reader = StreamDirReader::open(dir.path().join("Staff")).unwrap();
+    assert_eq!(reader.column("seniority").unwrap().encoder, "categorical");
+    let values = reader.read_column("seniority").unwrap();
+
+    let expected: Vec<u16> = rows.iter().map(|r| r.seniority).collect();
+    assert_eq!(values, ColumnValues::U16(expected));
+}
+
+#[derive(columnar_derive::StreamingColumnar, Debug, Clone)]
+#[columnar(layout = "single_file")]
+pub struct Shipment {
+    pub weight: u32,
+    #[columnar(rename = "dest")]
+    pub destination: u16,
+}
+
+#[test]
+fn test_stream_single_file_layout() {
+    use columnar::encoding::bitpack::v1::page_reader::decode_slice;
+    use columnar::{ColumnValues, FooterFileDecoder, SINGLE_FILE, StreamDirReader};
+
+    let rows: Vec<Shipment> = (0..500)
+        .map(|i| Shipment {
+            weight: i * 11,
+            destination: (i % 40) as u16,
+        })
+        .collect();
+    let base = tempfile::tempdir().unwrap();
+    write_rows(ShipmentStreamColumn::create_in(base.path()).unwrap(), &rows);
+
+    // one footer file holds both columns; nothing is written per field.
+    let dir = base.path().join("Shipment");
+    assert!(!dir.join("weight.bin").exists());
+    let mut decoder = FooterFileDecoder::new(dir.join(SINGLE_FILE)).unwrap();
+    let weights = decode_slice::<u32>(
+        decoder.get_column(0).unwrap(),
+        columnar::SmartBufferPool::default(),
+    )
+    .collect::<std::io::Result<Vec<_>>>()
+    .unwrap();
+
+    // sections are keyed by column id, which the manifest records for readers too.
+    assert_eq!(ShipmentStreamColumn::column_id("dest"), Some(1));
+    let reader = StreamDirReader::open(&dir).unwrap();
+    assert_eq!(reader.column("dest").unwrap().section, Some(1));
+    let destinations = reader.read_column("dest").unwrap();
+
+    assert_eq!(weights, rows.iter().map(|r| r.weight).collect::<Vec<_>>());
+    let expected: Vec<u16> = rows.iter().map(|r| r.destination).collect();
+    assert_eq!(destinations, ColumnValues::U16(expected));
+}
+
+#[derive(columnar_derive::StreamingColumnar, Debug, Clone)]
+pub struct Parcel {
+    pub id: u32,
+    pub zone: u16,
+    pub route: u16,
+}
+
+#[test]
+fn test_stream_with_factory_overrides_encoder() {
+    use columnar::encoding::{CategoricalEncoder, EncoderFactory};
+    use columnar::{ColumnValues, StreamBundleOptions, StreamDirReader};
+
+    let factory = EncoderFactory::new()
+        .with_encoder::<u16, _>("categorical", |_| Box::new(CategoricalEncoder::new()));
+    let base = tempfile::tempdir().unwrap();
+    let columns = ParcelStreamColumn::with_options(
+        &factory,
+        columnar::SmartBufferPool::default(),
+        std::env::temp_dir(),
+        &StreamBundleOptions::default().with_base_path(base.path()),
+    )
+    .unwrap();
+    let rows: Vec<Parcel> = (0..200)
+        .map(|i| Parcel {
+            id: i,
+            zone: (i % 4) as u16 * 100,
+            route: i as u16 * 3,
+        })
+        .collect();
+    write_rows(columns, &rows);
+
+    let dir = base.path().join("Parcel");
+    let zone_bytes = std::fs::read(dir.join("zone.bin")).unwrap();
+    let reader = StreamDirReader::open(&dir).unwrap();
+    let id = reader.column("id").unwrap().encoder.clone();
+    let zone = reader.column("zone").unwrap().encoder.clone();
+    let zones = reader.read_column("zone").unwrap();
+
+    // only the registered type is overridden; the file starts with the dictionary size.
+    assert_eq!((id.as_str(), zone.as_str()), ("bitpack", "categorical"));
+    assert_eq!(zone_bytes[..4], 4u32.to_le_bytes());
+    let expected: Vec<u16> = rows.iter().map(|r| r.zone).collect();
+    assert_eq!(zones, ColumnValues::U16(expected));
+}
+
+#[test]
+fn test_stream_with_factory_overrides_one_column() {
+    use columnar::encoding::{BitpackStreamWriter, CategoricalEncoder, EncoderFactory};
+    use columnar::{ColumnValues, StreamBundleOptions, StreamDirReader};
+
+    let factory = EncoderFactory::new()
+        .with_encoder::<u32, _>("categorical", |_| Box::new(CategoricalEncoder::new()))
+        .with_encoder::<u16, _>("bitpack", |pool| {
+            Box::new(BitpackStreamWriter::<u16>::new(pool))
+        })
+        .with_column_encoder::<u16, _>("zone", "categorical", |_| {
+            Box::new(CategoricalEncoder::new())
+        });
+    let base = tempfile::tempdir().unwrap();
+    let columns = ParcelStreamColumn::with_options(
+        &factory,
+        columnar::SmartBufferPool::default(),
+        std::env::temp_dir(),
+        &StreamBundleOptions::default().with_base_path(base.path()),
+    )
+    .unwrap();
+    let rows: Vec<Parcel> = (0..200)
+        .map(|i| Parcel {
+            id: i,
+            zone: (i % 4) as u16 * 100,
+            route: i as u16 * 3,
+        })
+        .collect();
+    write_rows(columns, &rows);
+
+    let dir = base.path().join("Parcel");
+    let reader = StreamDirReader::open(&dir).unwrap();
+    let encoder = |name: &str| reader.column(name).unwrap().encoder.clone();
+
+    // `zone` and `route` are both u16, but only `zone` is overridden; `id` keeps the
+    // type-level registration.
+    assert_eq!(
+        (encoder("id"), encoder("zone"), encoder("route")),
+        (
+            "categorical".to_string(),
+            "categorical".to_string(),
+            "bitpack".to_string()
+        )
+    );
+    let expected: Vec<u16> = rows.iter().map(|r| r.zone).collect();
+    assert_eq!(
+        reader.read_column("zone").unwrap(),
+        ColumnValues::U16(expected)
+    );
+    let expected: Vec<u16> = rows.iter().map(|r| r.route).collect();
+    assert_eq!(
+        reader.read_column("route").unwrap(),
+        ColumnValues::U16(expected)
+    );
+}
+
+#[derive(columnar_derive::StreamingColumnar, Debug, Clone)]
+pub struct Probe {
+    pub sensor: u32,
+    #[columnar(encoder = "sparse", default = "0")]
+    pub fault: u16,
+}
+
+#[test]
+fn test_stream_sparse_column_roundtrip() {
+    use columnar::encoding::sparse::sparse_encoded_len;
+    use columnar::{ColumnValues, StreamDirReader};
+
+    let rows: Vec<Probe> = (0..1_000)
+        .map(|i| Probe {
+            sensor: i,
+            fault: if i % 20 == 3 { (i % 7 + 1) as u16 } else { 0 },
+        })
+        .collect();
+    let base = tempfile::tempdir().unwrap();
+    write_rows(ProbeStreamColumn::create_in(base.path()).unwrap(), &rows);
+
+    let dir = base.path().join("Probe");
+    let fault_len = std::fs::metadata(dir.join("fault.bin")).unwrap().len();
+    let reader = StreamDirReader::open(&dir).unwrap();
+    let encoder = reader.column("fault").unwrap().encoder.clone();
+    let faults = reader.read_column("fault").unwrap();
+
+    assert_eq!(encoder, "sparse");
+    assert_eq!(fault_len as usize, sparse_encoded_len::<u16>(1_000, 50));
+    let expected: Vec<u16> = rows.iter().map(|r| r.fault).collect();
+    assert_eq!(faults, ColumnValues::U16(expected));
+}
+
+#[derive(columnar_derive::StreamingColumnar, Debug, Clone, PartialEq)]
+pub struct Gauge {
+    pub id: u32,
+    #[columnar(rename = "temp")]
+    pub temperature: i16,
+    #[columnar(encoder = "sparse", default = "0")]
+    pub alarm: u8,
+    #[columnar(skip)]
+    pub scratch: u64,
+}
+
+#[test]
+fn test_stream_open_iter_rows() {
+    let rows: Vec<Gauge> = (0..300)
+        .map(|i| Gauge {
+            id: i * 5,
+            temperature: i as i16 - 150,
+            alarm: (i % 50 == 0) as u8,
+            scratch: 0,
+        })
+        .collect();
+    let dir = tempfile::tempdir().unwrap();
+    write_rows(GaugeStreamColumn::create_in(dir.path()).unwrap(), &rows);
+
+    let reader = GaugeStreamColumn::open(dir.path()).unwrap();
+    let read: Vec<Gauge> = reader
+        .iter_rows()
+        .unwrap()
+        .collect::<std::io::Result<_>>()
+        .unwrap();
+
+    assert_eq!(read, rows);
+    assert_eq!(
+        read[1],
+        Gauge {
+            id: 5,
+            temperature: -149,
+            alarm: 0,
+            scratch: 0
+        }
+    );
+}
+
+#[test]
+fn test_stream_iter_rows_reports_a_short_column() {
+    let gauge = |i: u32| Gauge {
+        id: i,
+        temperature: 20,
+        alarm: 0,
+        scratch: 0,
+    };
+    let long = tempfile::tempdir().unwrap();
+    write_rows(
+        GaugeStreamColumn::create_in(long.path()).unwrap(),
+        &(0..300).map(gauge).collect::<Vec<_>>(),
+    );
+    let short = tempfile::tempdir().unwrap();
+    write_rows(
+        GaugeStreamColumn::create_in(short.path()).unwrap(),
+        &(0..100).map(gauge).collect::<Vec<_>>(),
+    );
+    std::fs::copy(
+        short.path().join("Gauge/temp.bin"),
+        long.path().join("Gauge/temp.bin"),
+    )
+    .unwrap();
+
+    let reader = GaugeStreamColumn::open(long.path()).unwrap();
+    let rows: Vec<_> = reader.iter_rows().unwrap().collect();
+    // the 100 complete rows, then one error, and nothing after it.
+    assert_eq!(rows.len(), 101);
+    assert!(rows[..100].iter().all(Result::is_ok));
+    let err = rows[100].as_ref().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert_eq!(
+        err.to_string(),
+        "column temp ends after 100 values, before the others"
+    );
+}
+
+#[derive(columnar_derive::StreamingColumnar, Debug, Clone)]
+pub struct Meter {
+    pub reading: u64,
+    pub serial: u32,
+}
+
+#[test]
+fn test_stream_close_with_report() {
+    use columnar::StreamingColumnBundle;
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut columns = MeterStreamColumn::create_in(dir.path()).unwrap();
+    for i in 0..50_000u32 {
+        columns
+            .push(&Meter {
+                reading: (i % 16) as u64,
+                serial: i.wrapping_mul(2_654_435_761),
+            })
+            .unwrap();
+    }
+    let report = columns.close_with_report().unwrap();
+    let reading_len = std::fs::metadata(dir.path().join("Meter/reading.bin"))
+        .unwrap()
+        .len();
+
+    // 4-bit values stored from 8-byte fields.
+    let reading = report.column("reading").unwrap();
+    assert_eq!(reading.raw_bytes_in, 50_000 * 8);
+    assert_eq!(reading.encoded_bytes_out, reading_len);
+    assert!(reading.ratio().unwrap() < 0.1, "{reading:?}");
+
+    // scrambled full-width values do not compress.
+    let serial = report.column("serial").unwrap();
+    assert!(serial.ratio().unwrap() > 0.9, "{serial:?}");
+    assert_eq!(
+        report.total().encoded_bytes_out,
+        reading.encoded_bytes_out + serial.encoded_bytes_out
+    );
+}
+
+#[derive(columnar_derive::StreamingColumnar, Debug, Clone)]
+pub struct Listing {
+    #[columnar(encoder = "doc", tokenizer = "ngram:3")]
+    pub raw_title: String,
+    #[columnar(encoder = "doc", tokenizer = "text")]
+    pub description: String,
+    pub views: u32,
+}
+
+#[test]
+fn test_stream_doc_fields_use_their_tokenizers() {
+    use columnar::encoding::strings::common::hash_string;
+    use columnar::encoding::strings::doc_reader::DocReader;
+
+    let base = tempfile::tempdir().unwrap();
+    let listing = Listing {
+        raw_title: "Columnar".to_string(),
+        description: "Fast, Columnar storage".to_string(),
+        views: 7,
+    };
+    write_rows(
+        ListingStreamColumn::create_in(base.path()).unwrap(),
+        &[listing],
+    );
+
+    let dir = base.path().join("Listing");
+    let title = std::fs::read(dir.join("raw_title.bin")).unwrap();
+    let description = std::fs::read(dir.join("description.bin")).unwrap();
+
+    let reader = DocReader::new(columnar::SmartBufferPool::new(1 << 20));
+    let found = |doc: &[u8], terms: &[&str]| {
+        let tokens: Vec<u64> = terms.iter().map(|t| hash_string(t)).collect();
+        reader
+            .search(&mut std::io::Cursor::new(doc), &tokens)
+            .unwrap()
+    };
+    // the title is split into case-preserving 3-grams.
+    assert!(found(&title, &["Col", "olu", "lum"]));
+    assert!(!found(&title, &["Columnar"]));
+    // the description into lowercased words without punctuation.
+    assert!(found(&description, &["fast", "columnar"]));
+    assert!(!found(&description, &["Col"]));
+}
+
+#[test]
+fn test_stream_dump_skips_string_columns() {
+    use columnar::StreamDirReader;
+
+    let rows: Vec<Listing> = (0..100)
+        .map(|i| Listing {
+            raw_title: format!("title {i}"),
+            description: "a listing".to_string(),
+            views: i * 3,
+        })
+        .collect();
+    let base = tempfile::tempdir().unwrap();
+    write_rows(ListingStreamColumn::create_in(base.path()).unwrap(), &rows);
+
+    let reader = StreamDirReader::open(base.path().join("Listing")).unwrap();
+    assert_eq!(
+        reader.dump(3).unwrap(),
+        "raw_title (doc String): <not decodable>\n\
+         description (doc String): <not decodable>\n\
+         views (bitpack u32, 100 values): [0, 3, 6]"
+    );
+}
+
+pub type Labels = std::collections::BTreeSet<u16>;
+
+#[derive(Columnar, SimpleColumnar, Debug, Clone, PartialEq)]
+pub struct Blob {
+    pub payload: std::vec::Vec<u8>,
+    pub labels: Labels,
+    pub size: ::core::primitive::u32,
+}
+
+#[test]
+fn test_qualified_and_aliased_field_types() {
+    let rows: Vec<Blob> = (0..5u8)
+        .map(|i| Blob {
+            payload: vec![i; i as usize],
+            labels: (0..i as u16).collect(),
+            size: i as u32,
+        })
+        .collect();
+
+    let cols = Blob::to_columns(&rows);
+    cols.assert_consistent().unwrap();
+    assert_eq!(cols.payload.last(), Some(&vec![4u8; 4]));
+    assert_eq!(cols.labels.first(), Some(&Labels::new()));
+
+    let vec_cols = Blob::to_simple_columns(&rows);
+    assert_eq!(vec_cols.iter_rows().collect::<Vec<_>>(), rows);
+}
+
+// a cut-down `models::position::Position`, which only derives SimpleColumnar: its list
+// column rules out Columnar, and only Columnar builds projections.
+#[derive(Columnar, Debug, Clone)]
+#[columnar(projection = "Slim", fields("rcid", "company_id"))]
+pub struct Position {
+    pub rcid: u64,
+    pub company_id: u32,
+    pub title: String,
+    pub salary: f64,
+}
+
+#[test]
+fn test_columnar_projection() {
+    let rows: Vec<Position> = (0..10)
+        .map(|i| Position {
+            rcid: 1000 + i,
+            company_id: (i % 3) as u32,
+            title: format!("title {i}"),
+            salary: i as f64 * 1.5,
+        })
+        .collect();
+
+    let mut slim = PositionSlimColumns::default();
+    for row in &rows {
+        slim.push(row);
+    }
+    slim.assert_consistent().unwrap();
+
+    // exhaustive destructuring only compiles if these are the only columns.
+    let PositionSlimColumns { rcid, company_id } = slim;
+    assert_eq!(rcid.len(), 10);
+    assert_eq!(rcid.last(), Some(&1009));
+    let companies: Vec<u32> = company_id.chunks.concat();
+    assert_eq!(
+        companies,
+        rows.iter().map(|r| r.company_id).collect::<Vec<_>>()
+    );
+
+    // the full bundle is unaffected.
+    assert_eq!(Position::to_columns(&rows).title.len(), 10);
+}
+
+#[derive(Columnar, SimpleColumnar, Debug, Clone)]
+pub struct Invoice {
+    pub number: u64,
+    #[columnar(skip)]
+    pub scratch: u8,
+    #[columnar(rename = "total")]
+    pub amount: f64,
+    #[columnar(id = 40)]
+    pub currency: u16,
+}
+
+#[test]
+fn test_column_ids() {
+    // declaration indices, counting the skipped field, unless pinned with `id`.
+    let expected: &[(&str, u32)] = &[("number", 0), ("total", 2), ("currency", 40)];
+    assert_eq!(InvoiceColumns::COLUMN_IDS, expected);
+    assert_eq!(InvoiceVecColumns::COLUMN_IDS, expected);
+
+    assert_eq!(InvoiceColumns::column_id("total"), Some(2));
+    assert_eq!(InvoiceColumns::column_id("amount"), None);
+    assert_eq!(InvoiceColumns::column_id("scratch"), None);
+    assert_eq!(InvoiceColumns::column_name(40), Some("currency"));
+    assert_eq!(InvoiceVecColumns::column_name(1), None);
+}
+
+#[test]
+fn test_push_if_keeps_columns_aligned() {
+    use columnar::FilteredPush;
+
+    let rows: Vec<Position> = (0..10)
+        .map(|i| Position {
+            rcid: i,
+            company_id: (i % 2) as u32,
+            title: format!("title {i}"),
+            salary: i as f64,
+        })
+        .collect();
+
+    let mut cols = PositionColumns::default();
+    let mut kept = 0;
+    for row in &rows {
+        if cols.push_if(row, |r| r.company_id != 0).unwrap() {
+            kept += 1;
+        }
+    }
+    assert_eq!(kept, 5);
+    cols.assert_consistent().unwrap();
+    assert_eq!(cols.rcid.len(), 5);
+    assert_eq!(cols.company_id.len(), 5);
+    assert_eq!(cols.title.len(), 5);
+    assert_eq!(cols.salary.len(), 5);
+    assert_eq!(cols.rcid.chunks.concat(), vec![1, 3, 5, 7, 9]);
+}
+
+#[derive(SimpleColumnar, Debug, Clone, PartialEq)]
+pub struct Thermostat {
+    pub sensor: u32,
+    pub value: i64,
+    #[columnar(list)]
+    pub tags: Vec<u16>,
+}
+
+impl Default for Thermostat {
+    fn default() -> Self {
+        Thermostat {
+            sensor: 0,
+            value: -1,
+            tags: vec![0],
+        }
+    }
+}
+
+#[test]
+fn test_reconstruct_fills_push_config_excluded_fields() {
+    use columnar::{FilteredPush, PushConfig};
+
+    let rows: Vec<Thermostat> = (0..3)
+        .map(|i| Thermostat {
+            sensor: i,
+            value: i as i64 * 10,
+            tags: vec![i as u16; i as usize],
+        })
+        .collect();
+    let cfg = PushConfig::new(["sensor", "tags"]);
+    let mut cols = ThermostatVecColumns::default();
+    for row in &rows {
+        cols.push_with_config(row, &cfg).unwrap();
+    }
+    assert!(cols.value.0.is_empty());
+    // plain `row` has no value to put in the excluded field.
+    assert_eq!(cols.row(1), None);
+
+    let expected = Thermostat {
+        sensor: 1,
+        value: -1,
+        tags: vec![1],
+    };
+    assert_eq!(cols.row_or_default(1), Some(expected.clone()));
+    assert_eq!(cols.row_or_default(3), None);
+    let filled: Vec<Thermostat> = cols.iter_rows_or_default().collect();
+    assert_eq!(filled.len(), 3);
+    assert_eq!(filled[1], expected);
+    assert!(filled.iter().all(|r| r.value == -1));
+
+    // nothing pushed at all yields no rows rather than endless defaults.
+    assert_eq!(
+        ThermostatVecColumns::default()
+            .iter_rows_or_default()
+            .count(),
+        0
+    );
+}
+
+#[derive(columnar_derive::StreamingColumnar, Debug, Clone)]
+pub struct Dial {
+    pub knob: u32,
+    pub notch: u8,
+}
+
+#[test]
+fn test_stream_bundle_respects_fd_limiter() {
+    use columnar::{
+        FdLimiter, SmartBufferPool, StreamBundleOptions, StreamingColumnBundle,
+        encoding::EncoderFactory,
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    let open = |limiter: &FdLimiter| {
+        DialStreamColumn::with_options(
+            &EncoderFactory::default(),
+            SmartBufferPool::default(),
+            std::env::temp_dir(),
+            &StreamBundleOptions::default()
+                .with_base_path(dir.path())
+                .with_fd_limiter(limiter.clone()),
+        )
+    };
+
+    // each bitpack column needs its file, its spill file and room for a page index, so two
+    // columns do not fit under a cap of five; the refusal is an error, not a hang, and the
+    // first column's permits come back.
+    let tight = FdLimiter::new(5);
+    let err = open(&tight).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::WouldBlock);
+    assert_eq!(tight.available(), 5);
+
+    let limiter = FdLimiter::new(6);
+    let mut columns = open(&limiter).unwrap();
+    assert_eq!(limiter.available(), 0);
+    for i in 0..1_000u32 {
+        columns
+            .push(&Dial {
+                knob: i,
+                notch: (i % 7) as u8,
+            })
+            .unwrap();
+    }
+    columns.close().unwrap();
+    assert_eq!(limiter.available(), 6);
+}
+
+#[test]
+fn test_single_file_bundle_reserves_its_file_permit() {
+    use columnar::{
+        FdLimiter, SmartBufferPool, StreamBundleOptions, StreamingColumnBundle,
+        encoding::EncoderFactory,
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    let open = |limiter: &FdLimiter| {
+        ShipmentStreamColumn::with_options(
+            &EncoderFactory::default(),
+            SmartBufferPool::default(),
+            std::env::temp_dir(),
+            &StreamBundleOptions::default()
+                .with_base_path(dir.path())
+                .with_fd_limiter(limiter.clone()),
+        )
+    };
+
+    // two temp files and two spill files, plus the single file written on close.
+    let tight = FdLimiter::new(4);
+    assert!(open(&tight).is_err());
+    assert_eq!(tight.available(), 4);
+
+    let limiter = FdLimiter::new(5);
+    let mut columns = open(&limiter).unwrap();
+    assert_eq!(limiter.available(), 0);
+    columns
+        .push(&Shipment {
+            weight: 9,
+            destination: 2,
+        })
+        .unwrap();
+    columns.close().unwrap();
+    assert_eq!(limiter.available(), 5);
+}
@@ -0,0 +1,40 @@
+#[test]
+fn test_index_type_mismatch_errors() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/doc_index_on_numeric.rs");
+    t.compile_fail("tests/ui/categorical_on_string.rs");
+}
+
+#[test]
+fn test_duplicate_column_id_errors() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/duplicate_column_id.rs");
+}
+
+#[test]
+fn test_quantiles_outside_simple_columnar_errors() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/quantiles_on_columnar.rs");
+    t.compile_fail("tests/ui/quantiles_on_streaming.rs");
+}
+
+#[test]
+fn test_histogram_outside_simple_columnar_errors() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/histogram_on_columnar.rs");
+    t.compile_fail("tests/ui/histogram_on_streaming.rs");
+}
+
+#[test]
+fn test_projection_outside_columnar_errors() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/projection_on_simple.rs");
+    t.compile_fail("tests/ui/projection_on_streaming.rs");
+}
+
+#[test]
+fn test_list_outside_simple_columnar_errors() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/list_on_columnar.rs");
+    t.compile_fail("tests/ui/list_on_streaming.rs");
+}
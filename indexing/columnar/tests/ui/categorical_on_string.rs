@@ -0,0 +1,9 @@
+use columnar_derive::StreamingColumnar;
+
+#[derive(StreamingColumnar)]
+struct Row {
+    #[columnar(encoder = "string", index, index_type = "categorical")]
+    name: String,
+}
+
+fn main() {}
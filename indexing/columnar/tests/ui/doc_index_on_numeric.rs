@@ -0,0 +1,9 @@
+use columnar_derive::StreamingColumnar;
+
+#[derive(StreamingColumnar)]
+struct Row {
+    #[columnar(index, index_type = "doc_index")]
+    score: u32,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use columnar_derive::Columnar;
+
+#[derive(Columnar, Clone)]
+struct Row {
+    first: u32,
+    #[columnar(id = 0)]
+    second: u32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use columnar_derive::StreamingColumnar;
+
+#[derive(StreamingColumnar)]
+struct Row {
+    #[columnar(encoder = "bitpack", histogram = 8)]
+    latency: u32,
+}
+
+fn main() {}
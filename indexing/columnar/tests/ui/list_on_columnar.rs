@@ -0,0 +1,9 @@
+use columnar_derive::Columnar;
+
+#[derive(Columnar)]
+struct Row {
+    #[columnar(list)]
+    latency: Vec<u32>,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use columnar_derive::StreamingColumnar;
+
+#[derive(StreamingColumnar)]
+struct Row {
+    #[columnar(encoder = "bitpack", list)]
+    latency: Vec<u32>,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use columnar_derive::SimpleColumnar;
+
+#[derive(SimpleColumnar, Clone)]
+#[columnar(projection = "Slim", fields("id"))]
+struct Row {
+    id: u32,
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use columnar_derive::StreamingColumnar;
+
+#[derive(StreamingColumnar)]
+#[columnar(projection = "Slim", fields("id"))]
+struct Row {
+    #[columnar(encoder = "bitpack")]
+    id: u32,
+    #[columnar(encoder = "string")]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use columnar_derive::Columnar;
+
+#[derive(Columnar)]
+struct Row {
+    #[columnar(quantiles)]
+    latency: u32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use columnar_derive::StreamingColumnar;
+
+#[derive(StreamingColumnar)]
+struct Row {
+    #[columnar(encoder = "bitpack", quantiles)]
+    latency: u32,
+}
+
+fn main() {}
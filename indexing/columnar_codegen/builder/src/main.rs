@@ -7,9 +7,27 @@ use syn::{Attribute, DataStruct, DeriveInput, Fields, Ident, Item, ItemStruct, p
 fn main() -> syn::Result<()> {
     let args: Vec<String> = std::env::args().collect();
     let streaming_mode = args.iter().any(|a| a == "--stream");
+    // regenerate even when the output already holds what the codegen would write.
+    let force = args.iter().any(|a| a == "--force");
+    // report what would be regenerated without writing anything.
+    let dry_run = args.iter().any(|a| a == "--dry-run");
+    // validate every model with the codegen's own checks and write nothing.
+    let check = args.iter().any(|a| a == "--check");
     println!("streaming mode: {}", streaming_mode);
 
     let (input_dir, output_dir) = columnar_paths();
+    if check {
+        let problems = check_models(&input_dir);
+        for problem in &problems {
+            eprintln!("{problem}");
+        }
+        if !problems.is_empty() {
+            eprintln!("{} problem(s) found", problems.len());
+            std::process::exit(1);
+        }
+        println!("all models in {} passed", input_dir.display());
+        return Ok(());
+    }
     fs::create_dir_all(&output_dir).unwrap();
 
     println!(
@@ -18,6 +36,8 @@ fn main() -> syn::Result<()> {
         input_dir.display(),
     );
 
+    let mut regenerated = Vec::new();
+    let mut skipped = Vec::new();
     for entry in fs::read_dir(input_dir).unwrap() {
         let file = entry.unwrap().path();
         if file.extension().and_then(|e| e.to_str()) != Some("rs") {
@@ -28,9 +48,13 @@ fn main() -> syn::Result<()> {
         let parsed = parse_file(&src).unwrap();
         for item in parsed.items {
             if let Item::Struct(s) = item {
-                let has_stream = has_derive(&s, "StreamingColumnar");
                 let has_simple = has_derive(&s, "SimpleColumnar");
                 let has_columnar = has_derive(&s, "Columnar");
+                // models that only carry attributes (`ColumnarAttrs`) cannot derive
+                // StreamingColumnar inside the columnar crate without clashing with the
+                // generated impls, so their streaming code only comes from here.
+                let has_stream = has_derive(&s, "StreamingColumnar")
+                    || (has_derive(&s, "ColumnarAttrs") && !has_simple && !has_columnar);
 
                 let derive_input = item_struct_to_derive_input(&s);
 
@@ -61,15 +85,105 @@ fn main() -> syn::Result<()> {
                 };
                 let out_path = output_dir.join(format!("{}_{}.rs", name.to_lowercase(), mode));
 
-                fs::write(&out_path, generated.to_string()).unwrap();
-                format_with_rustfmt(&out_path);
-                println!("Generated {}", out_path.display());
+                let generated = generated.to_string();
+                if !force && is_up_to_date(&out_path, &generated) {
+                    skipped.push(out_path);
+                    continue;
+                }
+                if !dry_run {
+                    let stamped = format!("{}\n{generated}", stamp(&generated));
+                    fs::write(&out_path, stamped).unwrap();
+                    format_with_rustfmt(&out_path);
+                }
+                regenerated.push(out_path);
             }
         }
     }
+
+    let verb = if dry_run {
+        "Would regenerate"
+    } else {
+        "Regenerated"
+    };
+    for path in &regenerated {
+        println!("{verb} {}", path.display());
+    }
+    for path in &skipped {
+        println!("Skipped {} (up to date)", path.display());
+    }
+    println!(
+        "{} regenerated, {} skipped",
+        regenerated.len(),
+        skipped.len()
+    );
     Ok(())
 }
 
+/// Expands every derive each model struct in `input_dir` asks for, in every mode, and
+/// returns one `file: Struct: message` line per error the codegen reports.
+fn check_models(input_dir: &Path) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut files: Vec<PathBuf> = match fs::read_dir(input_dir) {
+        Ok(entries) => entries.filter_map(|e| e.ok().map(|e| e.path())).collect(),
+        Err(e) => return vec![format!("{}: {e}", input_dir.display())],
+    };
+    files.retain(|f| f.extension().and_then(|e| e.to_str()) == Some("rs"));
+    files.sort();
+
+    for file in files {
+        let parsed = match fs::read_to_string(&file)
+            .map_err(|e| e.to_string())
+            .and_then(|src| parse_file(&src).map_err(|e| e.to_string()))
+        {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                problems.push(format!("{}: {e}", file.display()));
+                continue;
+            }
+        };
+        for item in parsed.items {
+            let Item::Struct(s) = item else {
+                continue;
+            };
+            let derive_input = item_struct_to_derive_input(&s);
+            let expanders: [(&str, Expander); 3] = [
+                ("Columnar", expand_columnar),
+                ("SimpleColumnar", expand_simple_columnar),
+                ("StreamingColumnar", expand_streaming_columnar),
+            ];
+            for (derive, expand) in expanders {
+                if !has_derive(&s, derive) {
+                    continue;
+                }
+                if let Err(e) = expand(&derive_input, None) {
+                    problems.push(format!("{}: {}: {e}", file.display(), s.ident));
+                }
+            }
+        }
+    }
+    problems
+}
+
+type Expander =
+    fn(&DeriveInput, Option<proc_macro2::TokenStream>) -> syn::Result<proc_macro2::TokenStream>;
+
+/// First line of every generated file: a 64-bit FNV-1a hash of the unformatted codegen
+/// output it was written from.
+fn stamp(generated: &str) -> String {
+    let hash = generated.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    format!("// generated by columnar_codegen, hash {hash:016x}")
+}
+
+/// Returns true when `out_path` was written from exactly `generated`, i.e. regenerating it
+/// would produce the same file. Comparing the output rather than timestamps also catches
+/// codegen changes that leave the model untouched.
+fn is_up_to_date(out_path: &Path, generated: &str) -> bool {
+    fs::read_to_string(out_path)
+        .is_ok_and(|out| out.lines().next() == Some(stamp(generated).as_str()))
+}
+
 fn has_derive(s: &ItemStruct, name: &str) -> bool {
     s.attrs.iter().any(|attr| is_derive_with(attr, name))
 }
@@ -202,3 +316,62 @@ fn module_path_from_file(file: &Path) -> Vec<Ident> {
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_reports_unknown_encoder() {
+        let dir = std::env::temp_dir().join(format!("columnar_check_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("meter.rs"),
+            r#"
+            #[derive(StreamingColumnar)]
+            pub struct Meter {
+                #[columnar(encoder = "zstd")]
+                pub reading: u64,
+            }
+            "#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("plain.rs"),
+            "#[derive(StreamingColumnar)] pub struct Plain { pub id: u32 }",
+        )
+        .unwrap();
+        // other files and structs without the derives are ignored.
+        fs::write(dir.join("notes.txt"), "not rust").unwrap();
+
+        let problems = check_models(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(problems.len(), 1, "{problems:?}");
+        assert!(problems[0].contains("meter.rs: Meter: unknown encoder \"zstd\""));
+        assert!(problems[0].ends_with(
+            r#"expected one of "bitpack", "string", "delta", "categorical", "sparse", "doc""#
+        ));
+    }
+
+    #[test]
+    fn test_only_changed_outputs_are_regenerated() {
+        let dir = std::env::temp_dir().join(format!("columnar_builder_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let generated = "impl Person {}";
+        let out = dir.join("person_vec.rs");
+        fs::write(&out, format!("{}\nimpl Person {{}}\n", stamp(generated))).unwrap();
+        assert!(is_up_to_date(&out, generated));
+        // a changed model or codegen produces different output.
+        assert!(!is_up_to_date(&out, "impl Person { }"));
+
+        // files without a stamp and missing outputs always need generating.
+        let unstamped = dir.join("position_vec.rs");
+        fs::write(&unstamped, generated).unwrap();
+        assert!(!is_up_to_date(&unstamped, generated));
+        assert!(!is_up_to_date(&dir.join("missing_vec.rs"), generated));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,20 +1,36 @@
 use std::path;
 
-use syn::{Attribute, LitBool, LitInt, LitStr, Result};
+use syn::{Attribute, LitBool, LitInt, LitStr, Result, Token, punctuated::Punctuated};
 
 #[derive(Debug, Clone, Default)]
 pub struct StructAttrs {
     pub chunk_size: Option<usize>,
     pub storage: Option<String>,   // e.g. "vec" | "column" | "stream"
     pub base_path: Option<String>, // where to write files
+    pub layout: Option<String>,    // "per_field" (default) | "single_file"
+    pub projections: Vec<Projection>,
+}
+
+/// `#[columnar(projection = "Name", fields("a", "b"))]`: an extra bundle holding only the
+/// listed fields.
+#[derive(Debug, Clone, Default)]
+pub struct Projection {
+    pub name: String,
+    pub fields: Vec<String>,
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct FieldAttrs {
     pub rename: Option<String>,
     pub skip: bool,
-    pub encoder: Option<String>, // e.g. "delta", "fixed", "dict"
-    pub path: Option<String>,    // optional per field override path
+    pub encoder: Option<String>,   // e.g. "delta", "fixed", "dict"
+    pub path: Option<String>,      // optional per field override path
+    pub list: bool,                // flatten Vec<T> into values + offsets
+    pub histogram: Option<usize>,  // equi-width histogram bucket count
+    pub quantiles: bool,           // keep a quantile sketch while pushing
+    pub id: Option<u32>,           // footer file column id, default the declaration index
+    pub default: Option<String>,   // value the sparse encoder leaves out, e.g. "0"
+    pub tokenizer: Option<String>, // doc encoder analyzer: "whitespace", "text" or "ngram:N"
 
     pub index: bool,
     pub index_path: Option<String>,
@@ -41,6 +57,26 @@ pub fn parse_struct_attrs(attrs: &[Attribute]) -> Result<StructAttrs> {
                 let lit: LitStr = m.value()?.parse()?;
                 out.base_path = Some(lit.value());
                 Ok(())
+            } else if m.path.is_ident("layout") {
+                let lit: LitStr = m.value()?.parse()?;
+                out.layout = Some(lit.value());
+                Ok(())
+            } else if m.path.is_ident("projection") {
+                let lit: LitStr = m.value()?.parse()?;
+                out.projections.push(Projection {
+                    name: lit.value(),
+                    fields: Vec::new(),
+                });
+                Ok(())
+            } else if m.path.is_ident("fields") {
+                let content;
+                syn::parenthesized!(content in m.input);
+                let names = Punctuated::<LitStr, Token![,]>::parse_terminated(&content)?;
+                let Some(projection) = out.projections.last_mut() else {
+                    return Err(m.error("fields(...) must follow projection = \"...\""));
+                };
+                projection.fields.extend(names.iter().map(LitStr::value));
+                Ok(())
             } else {
                 Err(m.error("unsupported columnar attribute on struct"))
             }
@@ -78,6 +114,47 @@ pub fn parse_field_attrs(attrs: &[Attribute]) -> Result<FieldAttrs> {
                 return Ok(());
             }
 
+            // check if a Vec<T> field should be flattened into values + offsets
+            if m.path.is_ident("list") {
+                out.list = true;
+                return Ok(());
+            }
+
+            // check if we want a histogram over a numeric field
+            if m.path.is_ident("histogram") {
+                let lit: LitInt = m.value()?.parse()?;
+                out.histogram = Some(lit.base10_parse::<usize>()?);
+                return Ok(());
+            }
+
+            // check if we want approximate quantiles over a numeric field
+            if m.path.is_ident("quantiles") {
+                out.quantiles = true;
+                return Ok(());
+            }
+
+            // check if the column id is pinned instead of following declaration order
+            if m.path.is_ident("id") {
+                let lit: LitInt = m.value()?.parse()?;
+                out.id = Some(lit.base10_parse::<u32>()?);
+                return Ok(());
+            }
+
+            // check the value a sparse column leaves out, parsed as an expression of the field type
+            if m.path.is_ident("default") {
+                let lit: LitStr = m.value()?.parse()?;
+                lit.parse::<syn::Expr>()?;
+                out.default = Some(lit.value());
+                return Ok(());
+            }
+
+            // check how a doc-encoded string field is split into tokens
+            if m.path.is_ident("tokenizer") {
+                let lit: LitStr = m.value()?.parse()?;
+                out.tokenizer = Some(lit.value());
+                return Ok(());
+            }
+
             // check the path we want to encode the file to
             if m.path.is_ident("path") {
                 let lit: LitStr = m.value()?.parse()?;
@@ -55,13 +55,86 @@ pub fn expand(
         });
     }
 
+    generate::reject_simple_only(&specs, "Columnar")?;
+
+    let row_path = maybe_quality_path.unwrap_or_else(|| quote! { #row_ident});
+    let bundle = bundle_impl(
+        &specs,
+        vis,
+        &columns_ident,
+        &row_path,
+        struct_attrs.chunk_size,
+        &runtime,
+    );
+
+    let column_ids = generate::column_ids_impl(&specs, &columns_ident)?;
+
+    // each projection is a bundle of its own over a subset of the fields.
+    let mut projections = Vec::new();
+    for projection in &struct_attrs.projections {
+        let projection_ident = format_ident!("{}{}Columns", row_ident, projection.name);
+        if projection.fields.is_empty() {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                format!(
+                    "projection \"{}\" needs fields(\"...\") listing its columns",
+                    projection.name
+                ),
+            ));
+        }
+        let mut subset = Vec::new();
+        for name in &projection.fields {
+            let spec = specs
+                .iter()
+                .find(|spec| spec.field_ident == name && !spec.fattrs.skip)
+                .ok_or_else(|| {
+                    syn::Error::new_spanned(
+                        &input.ident,
+                        format!(
+                            "projection \"{}\" names \"{}\", which is not a column of {}",
+                            projection.name, name, row_ident
+                        ),
+                    )
+                })?;
+            subset.push(spec.clone());
+        }
+        projections.push(bundle_impl(
+            &subset,
+            vis,
+            &projection_ident,
+            &row_path,
+            struct_attrs.chunk_size,
+            &runtime,
+        ));
+    }
+
+    Ok(quote! {
+        #bundle
+        #column_ids
+        #(#projections)*
+
+        impl #runtime::Columnar for #row_path {
+            type Columns = #columns_ident;
+        }
+    })
+}
+
+/// Declares a columns struct holding `specs` and implements `ColumnBundle` and
+/// `FilteredPush` for it.
+fn bundle_impl(
+    specs: &[generate::FieldSpec],
+    vis: &syn::Visibility,
+    columns_ident: &syn::Ident,
+    row_path: &TokenStream,
+    chunk_size: Option<usize>,
+    runtime: &syn::Path,
+) -> TokenStream {
     let column_fields = specs
         .iter()
         .filter(|spec| !spec.fattrs.skip)
         .map(|spec| {
             let column_ident = &spec.column_ident;
             let field_ty = &spec.field_ty;
-            let runtime = runtime.clone();
             quote! { pub #column_ident: #runtime::Column<#field_ty>, }
         })
         .collect::<Vec<_>>();
@@ -85,6 +158,33 @@ pub fn expand(
         })
         .collect::<Vec<_>>();
 
+    let live_columns = specs
+        .iter()
+        .filter(|spec| !spec.fattrs.skip)
+        .map(|spec| &spec.column_ident)
+        .collect::<Vec<_>>();
+    let shard_lists = specs
+        .iter()
+        .filter(|spec| !spec.fattrs.skip)
+        .map(|spec| format_ident!("__{}_shards", spec.column_ident_ident()))
+        .collect::<Vec<_>>();
+    let merge_many_body = quote! {
+        #(let mut #shard_lists = Vec::with_capacity(others.len());)*
+        for __other in others {
+            #(#shard_lists.push(__other.#live_columns);)*
+        }
+        #(self.#live_columns.merge_many(#shard_lists);)*
+    };
+
+    let clear_body = specs
+        .iter()
+        .filter(|spec| !spec.fattrs.skip)
+        .map(|spec| {
+            let column_ident = &spec.column_ident;
+            quote! { self.#column_ident.clear(); }
+        })
+        .collect::<Vec<_>>();
+
     let set_chunk_body = specs
         .iter()
         .filter(|spec| !spec.fattrs.skip)
@@ -94,13 +194,12 @@ pub fn expand(
         })
         .collect::<Vec<_>>();
 
-    let chunk_size_impl = if let Some(chunk_size) = struct_attrs.chunk_size {
+    let chunk_size_impl = if let Some(chunk_size) = chunk_size {
         let init_fields = specs
             .iter()
             .filter(|spec| !spec.fattrs.skip)
             .map(|spec| {
                 let column_ident = &spec.column_ident;
-                let runtime = runtime.clone();
                 quote! { #column_ident: #runtime::Column::default().with_chunk_size(#chunk_size), }
             })
             .collect::<Vec<_>>();
@@ -116,20 +215,27 @@ pub fn expand(
         quote! { #[derive(Default, Debug)] #vis struct #columns_ident { #(#column_fields)* } }
     };
 
-    let struct_decl_if_needed = if struct_attrs.chunk_size.is_some() {
+    let struct_decl_if_needed = if chunk_size.is_some() {
         quote! { #[derive(Debug)] #vis struct #columns_ident { #(#column_fields)* } }
     } else {
         quote! {}
     };
 
-    let row_path = maybe_quality_path.unwrap_or_else(|| quote! { #row_ident});
-    let filtered_push_body = generate::push_with_config_body(&specs);
+    let consistency_body = generate::consistency_check_body(specs, &|spec| {
+        let column_ident = &spec.column_ident;
+        quote! { self.#column_ident.len() }
+    });
 
-    Ok(quote! {
+    let filtered_push_body = generate::push_with_config_body(specs);
+    let clone_bounds = generate::clone_bounds(specs);
+    let field_order = generate::field_order_impl(specs, columns_ident);
+
+    quote! {
         #struct_decl_if_needed
         #chunk_size_impl
+        #field_order
 
-        impl #runtime::ColumnBundle<#row_path> for #columns_ident {
+        impl #runtime::ColumnBundle<#row_path> for #columns_ident #clone_bounds {
             fn push(&mut self, row: &#row_path) {
                 #(#push_body)*
             }
@@ -138,19 +244,28 @@ pub fn expand(
                 #(#merge_body)*
             }
 
+            fn merge_many(&mut self, others: Vec<Self>) {
+                #merge_many_body
+            }
+
+            fn clear(&mut self) {
+                #(#clear_body)*
+            }
+
+            fn assert_consistent(&self) -> std::io::Result<()> {
+                #consistency_body
+            }
+
             fn set_chunk_size(&mut self, n: usize) {
                 #(#set_chunk_body)*
             }
         }
 
-        impl #runtime::Columnar for #row_path {
-            type Columns = #columns_ident;
-        }
-
-        impl #runtime::FilteredPush<#row_path> for #columns_ident {
-            fn push_with_config(&mut self, row: &#row_path, cfg: &#runtime::PushConfig) {
+        impl #runtime::FilteredPush<#row_path> for #columns_ident #clone_bounds {
+            fn push_with_config(&mut self, row: &#row_path, cfg: &#runtime::PushConfig) -> std::io::Result<()> {
                 #filtered_push_body
+                Ok(())
             }
         }
-    })
+    }
 }
@@ -2,6 +2,7 @@ use proc_macro2::Span;
 use quote::{ToTokens, quote};
 use syn::{Ident, Path, Type};
 
+#[derive(Clone)]
 pub struct FieldSpec {
     pub field_ident: Ident,
     pub field_ty: Type,
@@ -20,6 +21,118 @@ impl FieldSpec {
     }
 }
 
+/// Looks through the invisible groups and parentheses a type picks up when it comes from
+/// another macro, e.g. `(u32)` or a `$ty` fragment.
+fn strip_groups(ty: &Type) -> &Type {
+    match ty {
+        Type::Group(group) => strip_groups(&group.elem),
+        Type::Paren(paren) => strip_groups(&paren.elem),
+        _ => ty,
+    }
+}
+
+/// Returns `ty` when it is a path type without a qualified self, as in `<T as Trait>::X`,
+/// whose last segment names an associated type rather than the type itself.
+fn plain_path(ty: &Type) -> Option<&syn::TypePath> {
+    match strip_groups(ty) {
+        Type::Path(type_path) if type_path.qself.is_none() => Some(type_path),
+        _ => None,
+    }
+}
+
+/// Returns the element type `T` when `ty` is a `Vec<T>`.
+pub fn vec_elem_ty(ty: &Type) -> Option<&Type> {
+    let type_path = plain_path(ty)?;
+    let seg = type_path.path.segments.last()?;
+    if seg.ident != "Vec" {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(args) = &seg.arguments else {
+        return None;
+    };
+    match args.args.first() {
+        Some(syn::GenericArgument::Type(elem)) if args.args.len() == 1 => Some(elem),
+        _ => None,
+    }
+}
+
+/// Returns the last path segment of `ty` when it is a plain type path such as `String`
+/// or `std::string::String`.
+fn type_name(ty: &Type) -> Option<String> {
+    let type_path = plain_path(ty)?;
+    let seg = type_path.path.segments.last()?;
+    match seg.arguments {
+        syn::PathArguments::None => Some(seg.ident.to_string()),
+        _ => None,
+    }
+}
+
+/// Returns true when `ty` is `String`.
+pub fn is_string_ty(ty: &Type) -> bool {
+    type_name(ty).is_some_and(|name| name == "String")
+}
+
+/// Returns true when `ty` is one of the primitive integer or float types.
+pub fn is_numeric_ty(ty: &Type) -> bool {
+    const NUMERIC: &[&str] = &[
+        "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+        "f32", "f64",
+    ];
+    type_name(ty).is_some_and(|name| NUMERIC.contains(&name.as_str()))
+}
+
+/// `where` clause requiring every column's element type to be `Clone`, which the generated
+/// pushes rely on. Spelling the bound out turns a non-`Clone` field into a single error on
+/// its type instead of several inside the generated bodies.
+pub fn clone_bounds(fields: &[FieldSpec]) -> proc_macro2::TokenStream {
+    let tys = fields
+        .iter()
+        .filter(|f| !f.fattrs.skip)
+        .map(|f| &f.field_ty);
+    quote! { where #(#tys: Clone,)* }
+}
+
+/// Rejects the field attributes only `SimpleColumnar` implements, `quantiles`, `histogram`
+/// and `list`, in other derives, which would otherwise ignore them; `derive` names the
+/// derive in the error.
+pub fn reject_simple_only(fields: &[FieldSpec], derive: &str) -> syn::Result<()> {
+    for f in fields {
+        let attribute = if f.fattrs.quantiles {
+            "#[columnar(quantiles)]"
+        } else if f.fattrs.histogram.is_some() {
+            "#[columnar(histogram = N)]"
+        } else if f.fattrs.list {
+            "#[columnar(list)]"
+        } else {
+            continue;
+        };
+        return Err(syn::Error::new_spanned(
+            &f.field_ident,
+            format!("{attribute} is only supported by SimpleColumnar, not {derive}"),
+        ));
+    }
+    Ok(())
+}
+
+/// Rejects `#[columnar(projection = ...)]` in derives other than `Columnar`, the only one
+/// that builds projection bundles; `derive` names the derive in the error.
+pub fn reject_projections(
+    struct_attrs: &crate::attr::StructAttrs,
+    row_ident: &Ident,
+    derive: &str,
+) -> syn::Result<()> {
+    match struct_attrs.projections.first() {
+        Some(projection) => Err(syn::Error::new_spanned(
+            row_ident,
+            format!(
+                "projection \"{}\" is only supported by Columnar, not {derive}",
+                projection.name
+            ),
+        )),
+        None => Ok(()),
+    }
+}
+
 /// Generates a struct definition for a columnar struct, given
 /// the fields that should be included in the struct.
 ///
@@ -36,13 +149,15 @@ impl FieldSpec {
 /// the same identifier as `columns_ident`. The struct will have fields for
 /// each of the `FieldSpec` objects in `fields`, with the type of each field
 /// generated by calling `backend_ty_for` with the corresponding `FieldSpec`.
-/// The struct will also derive the traits named in `derives`.
+/// The struct will also derive the traits named in `derives`, and gets the
+/// `extra_fields` (complete `pub name: Type,` declarations) after the columns.
 pub fn make_column_struct(
     vis: &syn::Visibility,
     columns_ident: &Ident,
     fields: &[FieldSpec],
     backend_ty_for: &dyn Fn(&FieldSpec) -> proc_macro2::TokenStream,
     derives: &[&str],
+    extra_fields: &[proc_macro2::TokenStream],
 ) -> proc_macro2::TokenStream {
     let derived = {
         let list = derives
@@ -63,6 +178,76 @@ pub fn make_column_struct(
         #derived
         #vis struct #columns_ident {
             #(#cols)*
+            #(#extra_fields)*
+        }
+    }
+}
+
+/// Ids of the columns in a footer file: a column's declaration index among all the
+/// struct's fields (skipped ones included, so skipping a field shifts nothing), unless
+/// `#[columnar(id = N)]` sets it. Two columns with the same id are a compile error.
+///
+/// Generates `COLUMN_IDS`, `column_id(name)` and `column_name(id)` on `columns_ident`.
+pub fn column_ids_impl(
+    fields: &[FieldSpec],
+    columns_ident: &Ident,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let mut ids: Vec<(String, u32)> = Vec::new();
+    for (index, f) in fields.iter().enumerate() {
+        if f.fattrs.skip {
+            continue;
+        }
+        let id = column_id(index, f);
+        let name = f.column_ident_ident().to_string();
+        if let Some((other, _)) = ids.iter().find(|(_, other_id)| *other_id == id) {
+            return Err(syn::Error::new_spanned(
+                &f.field_ident,
+                format!("column id {id} of `{name}` is already used by `{other}`"),
+            ));
+        }
+        ids.push((name, id));
+    }
+    let entries = ids.iter().map(|(name, id)| quote! { (#name, #id) });
+    Ok(quote! {
+        impl #columns_ident {
+            /// `(column name, id)` of every column: the field's declaration index unless
+            /// `#[columnar(id = N)]` sets it. Use these ids for footer file sections.
+            pub const COLUMN_IDS: &'static [(&'static str, u32)] = &[#(#entries),*];
+
+            pub fn column_id(name: &str) -> Option<u32> {
+                Self::COLUMN_IDS
+                    .iter()
+                    .find(|(column, _)| *column == name)
+                    .map(|&(_, id)| id)
+            }
+
+            pub fn column_name(id: u32) -> Option<&'static str> {
+                Self::COLUMN_IDS
+                    .iter()
+                    .find(|(_, column_id)| *column_id == id)
+                    .map(|&(name, _)| name)
+            }
+        }
+    })
+}
+
+/// Id of the field declared at `index`, as listed in `COLUMN_IDS`.
+pub fn column_id(index: usize, f: &FieldSpec) -> u32 {
+    f.fattrs.id.unwrap_or(index as u32)
+}
+
+/// Generates `FIELD_ORDER` on `columns_ident`: the column names of the non-skipped fields
+/// in declaration order, after `rename`.
+pub fn field_order_impl(fields: &[FieldSpec], columns_ident: &Ident) -> proc_macro2::TokenStream {
+    let names = fields
+        .iter()
+        .filter(|f| !f.fattrs.skip)
+        .map(|f| f.column_ident_ident().to_string());
+    quote! {
+        impl #columns_ident {
+            /// Column names in declaration order, after `rename` and without skipped fields.
+            /// Columns are stored and pushed in this order.
+            pub const FIELD_ORDER: &'static [&'static str] = &[#(#names),*];
         }
     }
 }
@@ -95,6 +280,33 @@ pub fn push_impl_body_stream(fields: &[FieldSpec]) -> proc_macro2::TokenStream {
     }
 }
 
+/// Body of `assert_consistent`: compares the length of every non-skipped column, given by
+/// `len_of`, against the first one and reports the first field that differs.
+pub fn consistency_check_body(
+    fields: &[FieldSpec],
+    len_of: &dyn Fn(&FieldSpec) -> proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let lens = fields.iter().filter(|f| !f.fattrs.skip).map(|f| {
+        let name = f.field_ident.to_string();
+        let len = len_of(f);
+        quote! { (#name, #len) }
+    });
+    quote! {
+        let __lens: &[(&str, usize)] = &[#(#lens),*];
+        if let Some((&(first, expected), rest)) = __lens.split_first() {
+            for &(field, len) in rest {
+                if len != expected {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("column for field `{field}` has {len} values but `{first}` has {expected}"),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 // When you add a new backend, provide a new backend_ty_for() function that maps a field to a runtime type (e.g., Vec<T>, Column<T>, StreamColumn<T>).
 pub fn merge_impl_body(fields: &[FieldSpec]) -> proc_macro2::TokenStream {
     let stmts = fields.iter().filter(|f| !f.fattrs.skip).map(|f| {
@@ -9,6 +9,8 @@ pub fn expand(
 ) -> Result<TokenStream> {
     let rt = pathing::runtime_path().unwrap();
     let row_indent = &input.ident;
+    let struct_attrs = attr::parse_struct_attrs(&input.attrs)?;
+    generate::reject_projections(&struct_attrs, row_indent, "SimpleColumnar")?;
     let vis = input.vis.clone();
     let columns_ident = format_ident!("{}VecColumns", row_indent);
 
@@ -36,6 +38,32 @@ pub fn expand(
         let field_ident = f.ident.unwrap();
         let fattrs = attr::parse_field_attrs(&f.attrs)?;
         let field_ty = f.ty;
+        if fattrs.list && generate::vec_elem_ty(&field_ty).is_none() {
+            return Err(syn::Error::new_spanned(
+                &field_ty,
+                "#[columnar(list)] requires a Vec<T> field",
+            ));
+        }
+        if let Some(buckets) = fattrs.histogram {
+            if buckets == 0 {
+                return Err(syn::Error::new_spanned(
+                    &field_ident,
+                    "#[columnar(histogram = N)] requires N > 0",
+                ));
+            }
+            if fattrs.list || !generate::is_numeric_ty(&field_ty) {
+                return Err(syn::Error::new_spanned(
+                    &field_ty,
+                    "#[columnar(histogram = N)] requires a numeric field",
+                ));
+            }
+        }
+        if fattrs.quantiles && (fattrs.list || !generate::is_numeric_ty(&field_ty)) {
+            return Err(syn::Error::new_spanned(
+                &field_ty,
+                "#[columnar(quantiles)] requires a numeric field",
+            ));
+        }
         let col_name = fattrs
             .rename
             .clone()
@@ -52,30 +80,262 @@ pub fn expand(
     let backend_ty_for = |fs: &generate::FieldSpec| {
         let ty = &fs.field_ty;
         // quote! {::std::vec::Vec<#ty>}
-        quote! { #rt::VecColumn<#ty> }
+        match generate::vec_elem_ty(ty) {
+            Some(elem) if fs.fattrs.list => quote! { #rt::ListColumn<#elem> },
+            _ => quote! { #rt::VecColumn<#ty> },
+        }
     };
 
+    // `#[columnar(quantiles)]` columns get a `{column}_quantiles` sketch next to them,
+    // fed by `push` and combined by `merge`.
+    let quantile_specs = specs
+        .iter()
+        .filter(|f| f.fattrs.quantiles && !f.fattrs.skip)
+        .collect::<Vec<_>>();
+    let sketch_ident =
+        |f: &generate::FieldSpec| format_ident!("{}_quantiles", f.column_ident_ident());
+    let sketch_fields = quantile_specs
+        .iter()
+        .map(|f| {
+            let si = sketch_ident(f);
+            let ty = &f.field_ty;
+            quote! { pub #si: #rt::QuantileSketch<#ty>, }
+        })
+        .collect::<Vec<_>>();
+
+    // `push` already clones every field, so each column type is known to be `Clone` too.
     let cols_struct = generate::make_column_struct(
         &vis,
         &columns_ident,
         &specs,
         &backend_ty_for,
-        &["Debug", "Default"],
+        &["Debug", "Default", "Clone"],
+        &sketch_fields,
     );
 
     let push_body = generate::push_impl_body(&specs);
     let merge_body = generate::merge_impl_body(&specs);
+    let sketch_push = quantile_specs
+        .iter()
+        .map(|f| {
+            let si = sketch_ident(f);
+            let fi = &f.field_ident;
+            quote! { self.#si.insert(row.#fi); }
+        })
+        .collect::<Vec<_>>();
+    let sketch_push_with_config = quantile_specs
+        .iter()
+        .map(|f| {
+            let si = sketch_ident(f);
+            let fi = &f.field_ident;
+            let name_str = fi.to_string();
+            quote! {
+                if cfg.is_allowed(#name_str) {
+                    self.#si.insert(row.#fi);
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    let sketch_merge = quantile_specs
+        .iter()
+        .map(|f| {
+            let si = sketch_ident(f);
+            quote! { self.#si.merge(other.#si); }
+        })
+        .collect::<Vec<_>>();
+    let quantile_fns = quantile_specs
+        .iter()
+        .map(|f| {
+            let si = sketch_ident(f);
+            let fn_ident = format_ident!("{}_quantile", f.column_ident_ident());
+            let ty = &f.field_ty;
+            quote! {
+                /// Approximate value at quantile `q` of the column, from its bounded sketch.
+                pub fn #fn_ident(&self, q: f64) -> Option<#ty> {
+                    self.#si.quantile(q)
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    let impl_quantiles = if quantile_fns.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            impl #columns_ident {
+                #(#quantile_fns)*
+            }
+        }
+    };
+
+    let histogram_arms = specs
+        .iter()
+        .filter(|f| !f.fattrs.skip)
+        .filter_map(|f| {
+            let buckets = f.fattrs.histogram?;
+            let ci = &f.column_ident;
+            let name_str = f.column_ident_ident().to_string();
+            Some(quote! {
+                #name_str => Some(#rt::Histogram::from_values(
+                    self.#ci.0.iter().map(|v| *v as f64),
+                    #buckets,
+                )),
+            })
+        })
+        .collect::<Vec<_>>();
+    let impl_histogram = if histogram_arms.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            impl #columns_ident {
+                /// Equi-width histogram of a `#[columnar(histogram = N)]` column over
+                /// the values pushed so far. Returns `None` for other columns.
+                pub fn histogram(&self, column: &str) -> Option<#rt::Histogram> {
+                    match column {
+                        #(#histogram_arms)*
+                        _ => None,
+                    }
+                }
+            }
+        }
+    };
 
     let row_path = maybe_quality_path.unwrap_or_else(|| quote! { #row_indent});
+    let column_ids = generate::column_ids_impl(&specs, &columns_ident)?;
+
+    // columns are read by `column_ident` but assigned back by `field_ident`, so renamed
+    // fields land on the original struct field.
+    let field_order = generate::field_order_impl(&specs, &columns_ident);
+    let row_fields = specs
+        .iter()
+        .map(|f| {
+            let fi = &f.field_ident;
+            let ci = &f.column_ident;
+            if f.fattrs.skip {
+                quote! { #fi: ::std::default::Default::default(), }
+            } else if f.fattrs.list {
+                quote! { #fi: self.#ci.get(index)?.to_vec(), }
+            } else {
+                quote! { #fi: self.#ci.0.get(index)?.clone(), }
+            }
+        })
+        .collect::<Vec<_>>();
+    let default_fields = specs
+        .iter()
+        .map(|f| {
+            let fi = &f.field_ident;
+            let ci = &f.column_ident;
+            if f.fattrs.skip {
+                quote! { #fi: ::std::default::Default::default(), }
+            } else if f.fattrs.list {
+                quote! {
+                    #fi: if self.#ci.is_empty() {
+                        defaults.#fi.clone()
+                    } else {
+                        self.#ci.get(index)?.to_vec()
+                    },
+                }
+            } else {
+                quote! {
+                    #fi: if self.#ci.0.is_empty() {
+                        defaults.#fi.clone()
+                    } else {
+                        self.#ci.0.get(index)?.clone()
+                    },
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    let column_lens = specs
+        .iter()
+        .filter(|f| !f.fattrs.skip)
+        .map(|f| {
+            let ci = &f.column_ident;
+            if f.fattrs.list {
+                quote! { self.#ci.len() }
+            } else {
+                quote! { self.#ci.0.len() }
+            }
+        })
+        .collect::<Vec<_>>();
+    let len_expr = match specs.iter().find(|f| !f.fattrs.skip) {
+        Some(f) if f.fattrs.list => {
+            let ci = &f.column_ident;
+            quote! { self.#ci.len() }
+        }
+        Some(f) => {
+            let ci = &f.column_ident;
+            quote! { self.#ci.0.len() }
+        }
+        None => quote! { 0 },
+    };
+    let consistency_body = generate::consistency_check_body(&specs, &|f| {
+        let ci = &f.column_ident;
+        if f.fattrs.list {
+            quote! { self.#ci.len() }
+        } else {
+            quote! { self.#ci.0.len() }
+        }
+    });
+    let clone_bounds = generate::clone_bounds(&specs);
+    let impl_reconstruct = quote! {
+        impl #columns_ident #clone_bounds {
+            pub fn column_names() -> &'static [&'static str] {
+                Self::FIELD_ORDER
+            }
+
+            /// Number of rows pushed so far.
+            pub fn len(&self) -> usize {
+                #len_expr
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+
+            /// Rebuilds the row at `index`; skipped fields get their `Default`.
+            pub fn row(&self, index: usize) -> Option<#row_path> {
+                Some(#row_path {
+                    #(#row_fields)*
+                })
+            }
+
+            /// Rebuilds every row as an owned value, e.g. to serialize them back out.
+            pub fn iter_rows(&self) -> impl Iterator<Item = #row_path> + '_ {
+                (0..self.len()).filter_map(move |index| self.row(index))
+            }
+
+            /// Like `iter_rows`, but first checks with `assert_consistent` that no column is
+            /// longer or shorter than the others, which would yield rows mixing stale values.
+            pub fn try_iter_rows(&self) -> std::io::Result<impl Iterator<Item = #row_path> + '_> {
+                #rt::SimpleColumnBundle::<#row_path>::assert_consistent(self)?;
+                Ok(self.iter_rows())
+            }
+        }
+    };
     let impl_bundle = quote! {
-        impl #rt::SimpleColumnBundle<#row_path> for #columns_ident {
+        impl #rt::SimpleColumnBundle<#row_path> for #columns_ident #clone_bounds {
             fn push(&mut self, row: &#row_path) {
                 #push_body
+                #(#sketch_push)*
             }
 
             fn merge(&mut self, other: Self) {
+                #(#sketch_merge)*
                 #merge_body
             }
+
+            fn assert_consistent(&self) -> std::io::Result<()> {
+                #consistency_body
+            }
+
+            fn row_with_defaults(&self, index: usize, defaults: &#row_path) -> Option<#row_path> {
+                if index >= 0usize #(.max(#column_lens))* {
+                    return None;
+                }
+                Some(#row_path {
+                    #(#default_fields)*
+                })
+            }
         }
     };
 
@@ -87,15 +347,22 @@ pub fn expand(
 
     let filtered_push_body = generate::push_with_config_body(&specs);
     let impl_filtered = quote! {
-        impl #rt::FilteredPush<#row_path> for #columns_ident {
-            fn push_with_config(&mut self, row: &#row_path, cfg: &#rt::PushConfig) {
+        impl #rt::FilteredPush<#row_path> for #columns_ident #clone_bounds {
+            fn push_with_config(&mut self, row: &#row_path, cfg: &#rt::PushConfig) -> std::io::Result<()> {
                 #filtered_push_body
+                #(#sketch_push_with_config)*
+                Ok(())
             }
         }
     };
 
     Ok(quote! {
         #cols_struct
+        #impl_histogram
+        #impl_quantiles
+        #impl_reconstruct
+        #field_order
+        #column_ids
         #impl_bundle
         #impl_row
         #impl_filtered
@@ -1,7 +1,4 @@
-use crate::{
-    attr::{self, StructAttrs},
-    generate, pathing,
-};
+use crate::{attr, generate, pathing};
 use proc_macro2::TokenStream;
 use quote::{ToTokens, format_ident, quote};
 use syn::{Data, DeriveInput, Field, Fields, Result};
@@ -13,6 +10,7 @@ pub fn expand(
     let rt = pathing::runtime_path().unwrap();
     let sattr = attr::parse_struct_attrs(&input.attrs)?;
     let row_ident = &input.ident;
+    generate::reject_projections(&sattr, row_ident, "StreamingColumnar")?;
     let vis = input.vis.clone();
     let columns_ident = format_ident!("{}StreamColumn", row_ident);
 
@@ -34,99 +32,270 @@ pub fn expand(
         }
     };
 
-    let specs = get_specs(&fields);
+    let single_file = match sattr.layout.as_deref() {
+        None | Some("per_field") => false,
+        Some("single_file") => true,
+        Some(other) => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                format!(
+                    "unknown layout \"{}\", expected \"per_field\" or \"single_file\"",
+                    other
+                ),
+            ));
+        }
+    };
+
+    let specs = get_specs(&fields)?;
+    generate::reject_simple_only(&specs, "StreamingColumnar")?;
+    for spec in &specs {
+        validate_encoder(spec)?;
+        validate_index(spec)?;
+        validate_default(spec)?;
+        validate_tokenizer(spec, &rt)?;
+    }
     let backend_ty_for = |fs: &generate::FieldSpec| {
         let ty = &fs.field_ty;
         quote! { #rt::StreamColumn<#ty> }
     };
-    let cols_struct =
-        generate::make_column_struct(&vis, &columns_ident, &specs, &backend_ty_for, &["Debug"]);
+    let cols_struct = generate::make_column_struct(
+        &vis,
+        &columns_ident,
+        &specs,
+        &backend_ty_for,
+        &["Debug"],
+        // manifest name of each column's encoder, in field order, the directory the
+        // bundle writes to, and the limiter permit for a single file opened on close.
+        &[quote! {
+            __encoders: Vec<&'static str>,
+            __dir: std::path::PathBuf,
+            __permit: Option<#rt::FdPermit>,
+        }],
+    );
 
-    // 3️⃣ Encoder initialization with optional pool injection
+    // 3️⃣ Encoder initialization: the factory's encoder for the column, or for the field
+    // type, if it has one, otherwise the one named by the attribute, with optional pool injection.
     let inits = specs.iter().filter(|f| !f.fattrs.skip).map(|f| {
         let ci = &f.column_ident;
         let ty = &f.field_ty;
         let encoder_name = f.fattrs.encoder.as_deref().unwrap_or("bitpack");
 
         // Determine if encoder expects a pool argument
-        let (encoder_expr, needs_pool) = match encoder_name {
+        let (encoder_call, needs_pool) = match encoder_name {
             "bitpack" => (
-                quote! { #rt::encoding::BitpackStreamWriter::<#ty>::new },
+                quote! { #rt::encoding::BitpackStreamWriter::<#ty>::new(pool.clone()) },
+                true,
+            ),
+            "string" => (
+                quote! { #rt::encoding::StringStreamEncoder::new(pool.clone()) },
                 true,
             ),
-            "string" => (quote! { #rt::encoding::StringStreamEncoder::new }, true),
             "delta" => (
-                quote! { #rt::encoding::DeltaStreamEncoder::<#ty>::new },
+                quote! { #rt::encoding::DeltaStreamEncoder::<#ty>::new() },
+                false,
+            ),
+            "categorical" => (
+                quote! { #rt::encoding::CategoricalEncoder::<#ty>::new() },
                 false,
             ),
-            _ => (quote! { compile_error!("Unknown encoder type"); }, false),
+            "sparse" => {
+                // parse_field_attrs already checked the expression parses.
+                let default = match &f.fattrs.default {
+                    Some(default) => syn::parse_str::<syn::Expr>(default).unwrap().to_token_stream(),
+                    None => quote! { ::core::default::Default::default() },
+                };
+                (
+                    quote! { #rt::encoding::SparseStreamEncoder::<#ty>::new(#default) },
+                    false,
+                )
+            }
+            "doc" => {
+                // validate_tokenizer already rejected specs this returns `None` for.
+                let tokenizer = tokenizer_expr(f.fattrs.tokenizer.as_deref(), &rt).unwrap();
+                (
+                    quote! {
+                        #rt::encoding::strings::doc_stream_writer::DocStreamWriter::default()
+                            .with_tokenizer(Box::new(#tokenizer))
+                    },
+                    false,
+                )
+            }
+            _ => unreachable!("validate_encoder rejects unknown encoders"),
         };
 
         // Directory-style path: StructName/field.bin
-        let struct_name = row_ident.to_string();
         let field_name = ci.to_token_stream().to_string().replace(' ', "");
-        let rel_path = format!("{}/{}.bin", struct_name, field_name);
+        let file_name = format!("{}.bin", field_name);
+        let path_expr = quote! { __dir.join(#file_name) };
 
-        let path_expr = if let Some(base) = &sattr.base_path {
-            let joined = format!("{}/{}", base.trim_end_matches('/'), rel_path);
-            quote! { #joined }
+        let index_expr = get_index_expr(f, &field_name, &rt);
+        let pool_expr = if needs_pool {
+            quote! { pool.clone() }
         } else {
-            quote! { #rel_path }
+            quote! { #rt::SmartBufferPool::default() }
         };
-
-        let index_expr = get_index_expr(f, &struct_name, &field_name, sattr.clone(), rt.clone());
-        // Conditionally add pool
-        if needs_pool {
+        // single-file columns are staged in temp files and copied into sections on close.
+        let column = if single_file {
             quote! {
-                #ci: #rt::StreamColumn::new(
-                    #path_expr,
-                    pool.clone(),
-                    Box::new(#encoder_expr(pool.clone())),
-                    #index_expr,
-                    temp_dir.clone(),
-                ).unwrap(),
+                match &options.fd_limiter {
+                    Some(__limiter) => #rt::StreamColumn::temporary_with_fd_limiter(
+                        #pool_expr,
+                        __encoder,
+                        #index_expr,
+                        temp_dir.clone(),
+                        __limiter,
+                    )?,
+                    None => #rt::StreamColumn::temporary(
+                        #pool_expr,
+                        __encoder,
+                        #index_expr,
+                        temp_dir.clone(),
+                    )?,
+                }
             }
         } else {
             quote! {
-                #ci: #rt::StreamColumn::new(
-                    #path_expr,
-                    #rt::SmartBufferPool::default(),
-                    Box::new(#encoder_expr()),
-                    #index_expr,
-                    temp_dir.clone(),
-                ).unwrap(),
+                match &options.fd_limiter {
+                    Some(__limiter) => #rt::StreamColumn::with_fd_limiter(
+                        #path_expr,
+                        #pool_expr,
+                        __encoder,
+                        #index_expr,
+                        temp_dir.clone(),
+                        __limiter,
+                    )?,
+                    None => #rt::StreamColumn::new(
+                        #path_expr,
+                        #pool_expr,
+                        __encoder,
+                        #index_expr,
+                        temp_dir.clone(),
+                    )?,
+                }
             }
+        };
+        quote! {
+            #ci: {
+                let (__name, __encoder) = factory
+                    .build_for::<#ty>(#field_name, pool.clone())
+                    .unwrap_or_else(|| {
+                        let __encoder: Box<dyn #rt::encoding::StreamingEncoder<#ty>> = Box::new(#encoder_call);
+                        (#encoder_name, __encoder)
+                    });
+                __encoders.push(__name);
+                #column
+            },
         }
     });
 
     let push_body = generate::push_impl_body_stream(&specs);
+    let struct_dir = match &sattr.base_path {
+        Some(base) => format!("{}/{}", base.trim_end_matches('/'), row_ident),
+        None => row_ident.to_string(),
+    };
+    let struct_name = row_ident.to_string();
+    let close_body = close_impl_body(&specs, single_file, &rt);
+    // the single file is created on close, while every column still holds its permits.
+    let file_permit = if single_file {
+        quote! {
+            match &options.fd_limiter {
+                Some(__limiter) => Some(__limiter.try_permit()?),
+                None => None,
+            }
+        }
+    } else {
+        quote! { None }
+    };
     // let merge_body = generate::merge_impl_body(&specs);
 
     let impl_default = quote! {
         impl #columns_ident {
+            /// Opens a bundle that writes under `base_path` instead of the struct's
+            /// `base_path` attribute, with the default pool and encoders and temp files in
+            /// the system temp directory.
+            pub fn create_in<P: AsRef<std::path::Path>>(base_path: P) -> std::io::Result<Self> {
+                Self::with_options(
+                    &#rt::encoding::EncoderFactory::default(),
+                    #rt::SmartBufferPool::default(),
+                    std::env::temp_dir(),
+                    &#rt::StreamBundleOptions::default().with_base_path(base_path.as_ref()),
+                )
+            }
+
             fn with_pool(pool: #rt::SmartBufferPool, temp_dir: std::path::PathBuf) -> Self {
-                Self {
+                Self::with_factory(&#rt::encoding::EncoderFactory::default(), pool, temp_dir)
+            }
+
+            /// Like `with_pool`, but takes each column's encoder from `factory` when it has
+            /// one for the column or the field type.
+            pub fn with_factory(
+                factory: &#rt::encoding::EncoderFactory,
+                pool: #rt::SmartBufferPool,
+                temp_dir: std::path::PathBuf,
+            ) -> Self {
+                Self::with_options(factory, pool, temp_dir, &#rt::StreamBundleOptions::default())
+                    .unwrap()
+            }
+
+            /// Like `with_factory`, but returns an error instead of panicking when a column
+            /// cannot be opened, writes under `options.base_path` when set and opens every
+            /// column through `options.fd_limiter` when set.
+            pub fn with_options(
+                factory: &#rt::encoding::EncoderFactory,
+                pool: #rt::SmartBufferPool,
+                temp_dir: std::path::PathBuf,
+                options: &#rt::StreamBundleOptions,
+            ) -> std::io::Result<Self> {
+                let __dir = match &options.base_path {
+                    Some(base) => base.join(#struct_name),
+                    None => std::path::PathBuf::from(#struct_dir),
+                };
+                let __permit = #file_permit;
+                let mut __encoders = Vec::new();
+                Ok(Self {
                     #(#inits)*
-                }
+                    __encoders,
+                    __dir,
+                    __permit,
+                })
+            }
+
+            /// Closes the bundle like `close` and reports, per column, the raw bytes pushed
+            /// against the encoded bytes written.
+            pub fn close_with_report(self) -> std::io::Result<#rt::CompressionReport> {
+                #close_body
             }
         }
 
-        // impl Default for #columns_ident {
-        //     fn default() -> Self {
-        //         let pool = #rt::SmartBufferPool::new(64 * 1024);
-        //         let path = #rt::TempDir::new().unwrap().path().clone();
-        //         Self::with_pool(pool, path)
-        //     }
-        // }
+        impl Default for #columns_ident {
+            /// Writes under the struct's `base_path` attribute, or the working directory
+            /// when it has none.
+            fn default() -> Self {
+                Self::with_pool(#rt::SmartBufferPool::default(), std::env::temp_dir())
+            }
+        }
     };
 
     let row_path = maybe_quality_path.unwrap_or_else(|| quote! { #row_ident});
+    let reader_ident = format_ident!("{}Reader", row_ident);
+    let impl_reader = reader_impl(
+        &specs,
+        &vis,
+        &columns_ident,
+        &reader_ident,
+        &row_path,
+        &row_ident.to_string(),
+        &rt,
+    );
     let impl_bundle = quote! {
         impl #rt::StreamingColumnBundle<#row_path> for #columns_ident {
             fn push(&mut self, row: &#row_path) -> std::io::Result<()> {
                 #push_body
                 Ok(())
             }
+            fn close(self) -> std::io::Result<()> {
+                self.close_with_report().map(|_| ())
+            }
         }
     };
 
@@ -146,64 +315,365 @@ pub fn expand(
         }
     };
 
+    let field_order = generate::field_order_impl(&specs, &columns_ident);
+    let column_ids = generate::column_ids_impl(&specs, &columns_ident)?;
+
     Ok(quote! {
         #cols_struct
+        #field_order
+        #column_ids
         #impl_default
         #impl_bundle
         #impl_row
         #impl_filtered
+        #impl_reader
     })
 }
 
-fn get_specs(fields: &[Field]) -> Vec<generate::FieldSpec> {
+/// Element types the generated reader decodes, spelled as the manifest's type tags.
+const READABLE_TYPES: [&str; 8] = ["u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64"];
+
+/// Whether `StreamDirReader::values` can decode the column of `f`: an integer field under
+/// any encoder but the string and doc ones, whose values are not read back.
+fn is_readable(f: &generate::FieldSpec) -> bool {
+    let tag = f.field_ty.to_token_stream().to_string().replace(' ', "");
+    READABLE_TYPES.contains(&tag.as_str())
+        && !matches!(f.fattrs.encoder.as_deref(), Some("string" | "doc"))
+}
+
+/// Declares `reader_ident`, which reads back what the bundle wrote, and the `open` that
+/// returns it. `iter_rows` streams every column through the manifest and zips them into
+/// rows; skipped fields get their `Default`. Generates nothing when a field is not
+/// `is_readable`.
+fn reader_impl(
+    specs: &[generate::FieldSpec],
+    vis: &syn::Visibility,
+    columns_ident: &syn::Ident,
+    reader_ident: &syn::Ident,
+    row_path: &TokenStream,
+    struct_name: &str,
+    rt: &syn::Path,
+) -> TokenStream {
+    let live: Vec<_> = specs.iter().filter(|f| !f.fattrs.skip).collect();
+    if !live.iter().all(|f| is_readable(f)) {
+        return quote! {};
+    }
+    let names: Vec<_> = live
+        .iter()
+        .map(|f| {
+            f.column_ident
+                .to_token_stream()
+                .to_string()
+                .replace(' ', "")
+        })
+        .collect();
+    let vars: Vec<_> = live
+        .iter()
+        .map(|f| format_ident!("__{}", f.field_ident))
+        .collect();
+    let tys = live.iter().map(|f| &f.field_ty);
+    let count = live.len();
+    let fields = specs.iter().map(|f| {
+        let fi = &f.field_ident;
+        if f.fattrs.skip {
+            quote! { #fi: ::core::default::Default::default(), }
+        } else {
+            let var = format_ident!("__{}", fi);
+            let name = f
+                .column_ident
+                .to_token_stream()
+                .to_string()
+                .replace(' ', "");
+            quote! { #fi: #var.ok_or_else(|| __ended(#name))??, }
+        }
+    });
+    quote! {
+        /// Reads back the columns a bundle wrote.
+        #vis struct #reader_ident {
+            dir: #rt::StreamDirReader,
+        }
+
+        impl #columns_ident {
+            /// Opens the bundle `create_in(base_path)` wrote, from its directory under
+            /// `base_path`. There is no fallback: the struct's `base_path` attribute and the
+            /// working directory are not consulted.
+            pub fn open<P: AsRef<std::path::Path>>(base_path: P) -> std::io::Result<#reader_ident> {
+                Ok(#reader_ident {
+                    dir: #rt::StreamDirReader::open(base_path.as_ref().join(#struct_name))?,
+                })
+            }
+        }
+
+        impl #reader_ident {
+            /// Streams the rows in the order they were pushed, decoding one value per column
+            /// at a time. Yields `InvalidData` once, and then stops, when a column runs out
+            /// before the others; decode errors are passed on the same way.
+            pub fn iter_rows(
+                &self,
+            ) -> std::io::Result<impl Iterator<Item = std::io::Result<#row_path>>> {
+                #(let mut #vars = self.dir.values::<#tys>(#names)?;)*
+                let mut __row = 0usize;
+                let mut __failed = false;
+                Ok(std::iter::from_fn(move || {
+                    if __failed {
+                        return None;
+                    }
+                    #(let #vars = #vars.next();)*
+                    let __all_ended: [bool; #count] = [#(#vars.is_none()),*];
+                    if __all_ended.iter().all(|ended| *ended) {
+                        return None;
+                    }
+                    let __ended = |name: &str| {
+                        std::io::Error::new(
+                            std::io::ErrorKind::InvalidData,
+                            format!("column {name} ends after {__row} values, before the others"),
+                        )
+                    };
+                    let __built = (|| -> std::io::Result<#row_path> {
+                        Ok(#row_path { #(#fields)* })
+                    })();
+                    __row += 1;
+                    __failed = __built.is_err();
+                    Some(__built)
+                }))
+            }
+        }
+    }
+}
+
+/// Closes each column, then records its file, encoder and element type in the manifest
+/// and returns what each column's encoder wrote. Encoder names come from `__encoders`,
+/// which `with_options` fills in field order.
+/// With `single_file`, every column is copied into the section of one footer file that its
+/// id from `COLUMN_IDS` names.
+fn close_impl_body(
+    specs: &[generate::FieldSpec],
+    single_file: bool,
+    rt: &syn::Path,
+) -> TokenStream {
+    let live = specs.iter().enumerate().filter(|(_, f)| !f.fattrs.skip);
+    let column_name = |f: &generate::FieldSpec| {
+        f.column_ident
+            .to_token_stream()
+            .to_string()
+            .replace(' ', "")
+    };
+    let closes = live.clone().map(|(index, f)| {
+        let ci = &f.column_ident;
+        let name = column_name(f);
+        let close = if single_file {
+            let id = generate::column_id(index, f);
+            quote! { self.#ci.close_into(&mut __file, #id)? }
+        } else {
+            quote! { self.#ci.close_with_stats()? }
+        };
+        quote! { __report.columns.push((#name.to_string(), #close)); }
+    });
+    let entries = live.enumerate().map(|(i, (index, f))| {
+        let name = column_name(f);
+        let (file, section) = if single_file {
+            let id = generate::column_id(index, f);
+            (quote! { #rt::SINGLE_FILE }, quote! { Some(#id) })
+        } else {
+            let file = format!("{}.bin", name);
+            (quote! { #file }, quote! { None })
+        };
+        let type_tag = f.field_ty.to_token_stream().to_string().replace(' ', "");
+        quote! {
+            #rt::ManifestColumn {
+                name: #name.to_string(),
+                file: #file.to_string(),
+                encoder: self.__encoders[#i].to_string(),
+                type_tag: #type_tag.to_string(),
+                section: #section,
+                values: Some(__report.columns[#i].1.values),
+            },
+        }
+    });
+    let (open_file, close_file) = if single_file {
+        (
+            quote! {
+                let mut __file = #rt::FooterFileEncoder::create(
+                    self.__dir.join(#rt::SINGLE_FILE),
+                )?;
+            },
+            quote! { __file.close()?; },
+        )
+    } else {
+        (quote! {}, quote! {})
+    };
+    quote! {
+        std::fs::create_dir_all(&self.__dir)?;
+        #open_file
+        let mut __report = #rt::CompressionReport::default();
+        #(#closes)*
+        #close_file
+        let manifest = #rt::StreamManifest {
+            columns: vec![#(#entries)*],
+        };
+        manifest.write_to(&self.__dir)?;
+        Ok(__report)
+    }
+}
+
+fn get_specs(fields: &[Field]) -> Result<Vec<generate::FieldSpec>> {
     fields
         .iter()
         .map(|f| {
             let field_ident = f.ident.clone().unwrap();
-            let fattrs = attr::parse_field_attrs(&f.attrs).unwrap();
+            let fattrs = attr::parse_field_attrs(&f.attrs)?;
             let field_ty = f.ty.clone();
             let col_name = fattrs
                 .rename
                 .clone()
                 .unwrap_or_else(|| field_ident.to_string());
             let column_ident = format_ident!("{}", col_name);
-            generate::FieldSpec {
+            Ok(generate::FieldSpec {
                 field_ident: field_ident.clone(),
                 field_ty,
                 column_ident: column_ident.into(),
                 fattrs,
-            }
+            })
         })
-        .collect::<Vec<_>>()
+        .collect()
 }
 
-fn get_index_expr(
-    f: &generate::FieldSpec,
-    struct_name: &str,
-    field_name: &str,
-    sattr: StructAttrs,
-    rt: syn::Path,
-) -> TokenStream {
+/// Encoders `#[columnar(encoder = ...)]` accepts on streaming fields.
+const ENCODERS: [&str; 6] = ["bitpack", "string", "delta", "categorical", "sparse", "doc"];
+
+/// Rejects encoder names no streaming encoder answers to.
+fn validate_encoder(f: &generate::FieldSpec) -> Result<()> {
+    match f.fattrs.encoder.as_deref() {
+        Some(encoder) if !f.fattrs.skip && !ENCODERS.contains(&encoder) => {
+            Err(syn::Error::new_spanned(
+                &f.field_ident,
+                format!(
+                    "unknown encoder \"{}\", expected one of {}",
+                    encoder,
+                    ENCODERS.map(|e| format!("\"{e}\"")).join(", ")
+                ),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Tokenizer a `tokenizer = "..."` spec names, `WhitespaceTokenizer` when there is none, or
+/// `None` for a spec no tokenizer answers to.
+fn tokenizer_expr(spec: Option<&str>, rt: &syn::Path) -> Option<TokenStream> {
+    let tokenizer = quote! { #rt::encoding::strings::tokenizer };
+    match spec {
+        None | Some("whitespace") => Some(quote! { #tokenizer::WhitespaceTokenizer }),
+        Some("text") => Some(quote! { #tokenizer::TextTokenizer::new() }),
+        Some(spec) => {
+            let n = spec.strip_prefix("ngram:")?.parse::<usize>().ok()?;
+            (n > 0).then(|| quote! { #tokenizer::NgramTokenizer::new(#n) })
+        }
+    }
+}
+
+/// Rejects `tokenizer` on fields that are not doc encoded, and unknown tokenizer specs.
+fn validate_tokenizer(f: &generate::FieldSpec, rt: &syn::Path) -> Result<()> {
+    let Some(spec) = f.fattrs.tokenizer.as_deref() else {
+        return Ok(());
+    };
+    if f.fattrs.skip {
+        return Ok(());
+    }
+    if f.fattrs.encoder.as_deref() != Some("doc") {
+        return Err(syn::Error::new_spanned(
+            &f.field_ident,
+            "`tokenizer` is only used by encoder = \"doc\"",
+        ));
+    }
+    if tokenizer_expr(Some(spec), rt).is_none() {
+        return Err(syn::Error::new_spanned(
+            &f.field_ident,
+            format!(
+                "unknown tokenizer \"{}\", expected \"whitespace\", \"text\" or \"ngram:N\" with N > 0",
+                spec
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Rejects `default` on fields whose encoder has no use for it.
+fn validate_default(f: &generate::FieldSpec) -> Result<()> {
+    if f.fattrs.default.is_none() || f.fattrs.skip {
+        return Ok(());
+    }
+    if f.fattrs.encoder.as_deref() != Some("sparse") {
+        return Err(syn::Error::new_spanned(
+            &f.field_ident,
+            "`default` is only used by encoder = \"sparse\"",
+        ));
+    }
+    Ok(())
+}
+
+/// Rejects index settings that cannot work with the field's type or encoder, so the
+/// user gets one clear error instead of trait errors inside the generated code.
+fn validate_index(f: &generate::FieldSpec) -> Result<()> {
+    if !f.fattrs.index || f.fattrs.skip {
+        return Ok(());
+    }
+    let ty = &f.field_ty;
+    let encoder = f.fattrs.encoder.as_deref().unwrap_or("bitpack");
+    match f.fattrs.index_type.as_deref() {
+        Some("doc_index") => {
+            if generate::is_numeric_ty(ty) || encoder != "string" {
+                return Err(syn::Error::new_spanned(
+                    &f.field_ident,
+                    "index_type = \"doc_index\" requires a String field with encoder = \"string\"",
+                ));
+            }
+        }
+        Some("categorical") => {
+            if generate::is_string_ty(ty) || encoder == "string" {
+                return Err(syn::Error::new_spanned(
+                    &f.field_ident,
+                    "index_type = \"categorical\" requires a numeric field; use index_type = \"doc_index\" for String fields",
+                ));
+            }
+        }
+        Some(other) => {
+            return Err(syn::Error::new_spanned(
+                &f.field_ident,
+                format!(
+                    "unknown index_type \"{}\", expected \"doc_index\" or \"categorical\"",
+                    other
+                ),
+            ));
+        }
+        None => {
+            return Err(syn::Error::new_spanned(
+                &f.field_ident,
+                "#[columnar(index)] requires an index_type",
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn get_index_expr(f: &generate::FieldSpec, field_name: &str, rt: &syn::Path) -> TokenStream {
     if f.fattrs.index {
         let index_type = f.fattrs.index_type.as_deref().unwrap();
-        let rel_index_path = format!("{}/{}.idx", struct_name, field_name);
+        let index_file = format!("{}.idx", field_name);
         let index_path_expr = if let Some(index_path) = &f.fattrs.index_path {
-            quote! { #index_path }
-        } else if let Some(base) = &sattr.base_path {
-            let joined = format!("{}/{}", base.trim_end_matches('/'), rel_index_path);
-            quote! { #joined }
+            quote! { std::path::PathBuf::from(#index_path) }
         } else {
-            quote! { #rel_index_path }
+            quote! { __dir.join(#index_file) }
         };
 
         let ty = &f.field_ty;
 
         match index_type {
             "doc_index" => quote! {
-                Some(Box::new(#rt::indexing::DocIndex::new(temp_dir, #index_path_expr)) as Box<dyn #rt::FieldIndex<#ty>>)
+                Some(Box::new(#rt::indexing::DocIndex::new(temp_dir.clone(), #index_path_expr)) as Box<dyn #rt::FieldIndex<#ty>>)
             },
             "categorical" => quote! {
-                Some(Box::new(#rt::indexing::Categorial::new(temp_dir, #index_path_expr)) as Box<dyn #rt::FieldIndex<#ty>>)
+                Some(Box::new(#rt::indexing::Categorial::new(temp_dir.clone(), #index_path_expr)) as Box<dyn #rt::FieldIndex<#ty>>)
             },
             _ => quote! {
             compile_error!("Unknown index type")
@@ -4,7 +4,7 @@ pub const MAGIC_FOOTER: &[u8; 6] = b"FOOTR1";
 pub const MAGIC_AND_DATA_SIZE: usize = 14;
 const COLUMN_META_SIZE: usize = 20;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ColumnMeta {
     pub id: u32,
     pub offset: u64,
@@ -42,6 +42,26 @@ impl Footer {
     }
 
     fn read_from_buffer(buff: &[u8]) -> io::Result<Footer> {
+        if buff.len() < MAGIC_AND_DATA_SIZE {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "footer of {} bytes is shorter than its {} byte trailer",
+                    buff.len(),
+                    MAGIC_AND_DATA_SIZE
+                ),
+            ));
+        }
+        let columns_len = buff.len() - MAGIC_AND_DATA_SIZE;
+        if !columns_len.is_multiple_of(COLUMN_META_SIZE) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "footer column region of {} bytes is not a multiple of {}",
+                    columns_len, COLUMN_META_SIZE
+                ),
+            ));
+        }
         if &buff[buff.len() - 6..] != MAGIC_FOOTER {
             return Err(std::io::Error::new(
                 std::io::ErrorKind::InvalidData,
@@ -58,7 +78,7 @@ impl Footer {
         let size = u64::from_le_bytes(buff[start..end].try_into().unwrap());
 
         let columns = buff[..start]
-            .chunks(20)
+            .chunks_exact(COLUMN_META_SIZE)
             .map(|chunk| {
                 let id = u32::from_le_bytes(chunk[0..4].try_into().unwrap());
                 let offset = u64::from_le_bytes(chunk[4..12].try_into().unwrap());
@@ -141,4 +161,32 @@ mod test {
         let footer2 = Footer::read_from_buffer(&vec).expect("err reading from vec");
         assert_eq!(footer, footer2);
     }
+
+    #[test]
+    fn test_read_from_short_buffer_errors() {
+        let err = Footer::read_from_buffer(&[1, 2, 3]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("3 bytes"), "{err}");
+    }
+
+    #[test]
+    fn test_read_from_buffer_with_partial_column_errors() {
+        let footer = Footer {
+            columns: vec![ColumnMeta {
+                id: 1,
+                offset: 0,
+                size: 10,
+            }],
+            size: COLUMN_META_SIZE as u64,
+            magic: *MAGIC_FOOTER,
+        };
+        let mut vec = Vec::new();
+        footer.write_to(&mut vec).unwrap();
+        // drop 7 bytes of the column entry, keeping the trailer intact.
+        vec.drain(..7);
+
+        let err = Footer::read_from_buffer(&vec).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("multiple of 20"), "{err}");
+    }
 }
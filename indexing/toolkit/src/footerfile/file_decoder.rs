@@ -1,4 +1,4 @@
-use crate::footerfile::common::{Footer, get_footer};
+use crate::footerfile::common::{ColumnMeta, Footer, get_footer};
 use crate::fspkg::file_slice::FileSlice;
 use crate::fspkg::sectioned_slice::{FileSliceColumn, FileSlicer};
 use std::io;
@@ -7,6 +7,8 @@ use std::{fs, path::PathBuf};
 pub struct FooterFileDecoder {
     footer: Footer,
     path: PathBuf,
+    /// Byte offset where the footer starts; column data lives in `0..data_end`.
+    data_end: u64,
 }
 
 impl FooterFileDecoder {
@@ -19,8 +21,96 @@ impl FooterFileDecoder {
     pub fn new(path: PathBuf) -> io::Result<Self> {
         let mut file = fs::File::open(&path)?;
         let file_size = file.metadata()?.len();
-        let (_, footer) = get_footer(&mut file, file_size)?;
-        Ok(Self { footer, path })
+        let (data_end, footer) = get_footer(&mut file, file_size)?;
+        Ok(Self {
+            footer,
+            path,
+            data_end,
+        })
+    }
+
+    /// Ids of the columns in the file, in the order they were written.
+    ///
+    /// Served from the footer read by `new`; the file is not touched again.
+    pub fn column_ids(&self) -> Vec<u32> {
+        self.footer.columns.iter().map(|c| c.id).collect()
+    }
+
+    /// Offset and size of the column with id `column_id`, or `None` if the file has no such
+    /// column. Like `column_ids`, this never reads column data or reopens the file.
+    ///
+    /// The format carries no checksums yet, so there is none to report.
+    pub fn column_meta(&self, column_id: u32) -> Option<&ColumnMeta> {
+        self.footer.columns.iter().find(|c| c.id == column_id)
+    }
+
+    /// Every column in the order it was written, with a `FileSlice` over its data. Each slice
+    /// opens the file on its own, so they can be read independently, e.g. by a dump tool
+    /// walking a file it knows nothing about.
+    ///
+    /// # Errors
+    ///
+    /// An item is an error when the file cannot be opened for that column.
+    pub fn iter_columns(
+        &self,
+    ) -> impl Iterator<Item = io::Result<(ColumnMeta, FileSlice<fs::File>)>> + '_ {
+        self.footer.columns.iter().map(|column| {
+            let file = fs::File::open(&self.path)?;
+            let slice = FileSlice::new(file, column.offset, column.size)?;
+            Ok((column.clone(), slice))
+        })
+    }
+
+    /// Checks that the footer describes a sane file: column ids are unique and every
+    /// column lies inside the data region without overlapping another column.
+    ///
+    /// The format carries no checksums yet, so column contents are not verified.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidData` error describing the first problem found.
+    pub fn validate(&self) -> io::Result<()> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+        let mut columns: Vec<&ColumnMeta> = self.footer.columns.iter().collect();
+        columns.sort_by_key(|c| c.id);
+        for pair in columns.windows(2) {
+            if pair[0].id == pair[1].id {
+                return Err(invalid(format!("duplicate column id {}", pair[0].id)));
+            }
+        }
+
+        for column in &columns {
+            let end = column.offset.checked_add(column.size);
+            if end.is_none_or(|end| end > self.data_end) {
+                return Err(invalid(format!(
+                    "column {} range {}..{} exceeds data region 0..{}",
+                    column.id,
+                    column.offset,
+                    column.offset.saturating_add(column.size),
+                    self.data_end
+                )));
+            }
+        }
+
+        // an empty range holds no bytes, so it cannot overlap anything.
+        columns.retain(|c| c.size > 0);
+        columns.sort_by_key(|c| (c.offset, c.size));
+        for pair in columns.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            if a.offset + a.size > b.offset {
+                return Err(invalid(format!(
+                    "column {} range {}..{} overlaps column {} range {}..{}",
+                    a.id,
+                    a.offset,
+                    a.offset + a.size,
+                    b.id,
+                    b.offset,
+                    b.offset + b.size
+                )));
+            }
+        }
+        Ok(())
     }
 
     /// Returns a `FileSlicer` that provides a view into the file at `path`
@@ -99,4 +189,199 @@ mod tests {
         column.read_to_end(&mut buffer).unwrap();
         assert_eq!(buffer, vec![1, 2, 3, 4, 5, 6, 7, 8, 9]);
     }
+
+    #[test]
+    fn test_validate_valid_file() {
+        let temp_dir = tempdir().expect("err creating temp dir");
+        let path = temp_dir.path().join("footer_file");
+        let mut encoder = FooterFileEncoder::create(path.clone()).expect("err creating file");
+        encoder.write(1, &mut Cursor::new(vec![1u8; 10])).unwrap();
+        encoder.write(2, &mut Cursor::new(vec![2u8; 0])).unwrap();
+        encoder.write(3, &mut Cursor::new(vec![3u8; 7])).unwrap();
+        encoder.close().unwrap();
+
+        let decoder = FooterFileDecoder::new(path).expect("err decoding footer");
+        decoder.validate().expect("valid file");
+    }
+
+    #[test]
+    fn test_column_metadata_without_reading_data() {
+        let temp_dir = tempdir().expect("err creating temp dir");
+        let path = temp_dir.path().join("footer_file");
+        let mut encoder = FooterFileEncoder::create(path.clone()).expect("err creating file");
+        encoder.write(7, &mut Cursor::new(vec![1u8; 10])).unwrap();
+        encoder.write(3, &mut Cursor::new(vec![2u8; 0])).unwrap();
+        encoder.write(42, &mut Cursor::new(vec![3u8; 25])).unwrap();
+        encoder.close().unwrap();
+
+        let decoder = FooterFileDecoder::new(path.clone()).expect("err decoding footer");
+        // with the file gone, anything that reopened it or read column bytes would fail.
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(decoder.column_ids(), vec![7, 3, 42]);
+        assert_eq!(
+            decoder.column_meta(42),
+            Some(&ColumnMeta {
+                id: 42,
+                offset: 10,
+                size: 25,
+            })
+        );
+        assert_eq!(
+            decoder.column_meta(3).map(|c| (c.offset, c.size)),
+            Some((10, 0))
+        );
+        assert_eq!(decoder.column_meta(8), None);
+    }
+
+    #[test]
+    fn test_iter_columns() {
+        let temp_dir = tempdir().expect("err creating temp dir");
+        let path = temp_dir.path().join("footer_file");
+        let mut encoder = FooterFileEncoder::create(path.clone()).expect("err creating file");
+        encoder
+            .write(9, &mut Cursor::new(b"first".to_vec()))
+            .unwrap();
+        encoder
+            .write(4, &mut Cursor::new(b"second!".to_vec()))
+            .unwrap();
+        encoder.close().unwrap();
+
+        let decoder = FooterFileDecoder::new(path).expect("err decoding footer");
+        let columns = decoder
+            .iter_columns()
+            .map(|column| {
+                let (meta, mut slice) = column.unwrap();
+                let mut data = Vec::new();
+                slice.read_to_end(&mut data).unwrap();
+                (meta, data)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            columns,
+            vec![
+                (
+                    ColumnMeta {
+                        id: 9,
+                        offset: 0,
+                        size: 5,
+                    },
+                    b"first".to_vec()
+                ),
+                (
+                    ColumnMeta {
+                        id: 4,
+                        offset: 5,
+                        size: 7,
+                    },
+                    b"second!".to_vec()
+                ),
+            ]
+        );
+    }
+
+    fn write_raw(path: &PathBuf, data_len: usize, columns: Vec<ColumnMeta>) {
+        let mut file = fs::File::create(path).unwrap();
+        std::io::Write::write_all(&mut file, &vec![0u8; data_len]).unwrap();
+        let footer = Footer {
+            columns,
+            size: 0,
+            magic: *crate::footerfile::common::MAGIC_FOOTER,
+        };
+        footer.write_to(&mut file).unwrap();
+    }
+
+    #[test]
+    fn test_validate_empty_column_inside_another() {
+        let temp_dir = tempdir().expect("err creating temp dir");
+        let path = temp_dir.path().join("empty_inside");
+        write_raw(
+            &path,
+            20,
+            vec![
+                ColumnMeta {
+                    id: 1,
+                    offset: 0,
+                    size: 10,
+                },
+                ColumnMeta {
+                    id: 2,
+                    offset: 4,
+                    size: 0,
+                },
+                ColumnMeta {
+                    id: 3,
+                    offset: 10,
+                    size: 10,
+                },
+            ],
+        );
+        FooterFileDecoder::new(path)
+            .unwrap()
+            .validate()
+            .expect("an empty column overlaps nothing");
+    }
+
+    #[test]
+    fn test_validate_corrupted_file() {
+        let temp_dir = tempdir().expect("err creating temp dir");
+        let path = temp_dir.path().join("overlap");
+        write_raw(
+            &path,
+            20,
+            vec![
+                ColumnMeta {
+                    id: 1,
+                    offset: 0,
+                    size: 12,
+                },
+                ColumnMeta {
+                    id: 2,
+                    offset: 8,
+                    size: 12,
+                },
+            ],
+        );
+        let err = FooterFileDecoder::new(path)
+            .unwrap()
+            .validate()
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            "column 1 range 0..12 overlaps column 2 range 8..20"
+        );
+
+        let path = temp_dir.path().join("out_of_bounds");
+        write_raw(
+            &path,
+            20,
+            vec![ColumnMeta {
+                id: 1,
+                offset: 16,
+                size: 8,
+            }],
+        );
+        let err = FooterFileDecoder::new(path)
+            .unwrap()
+            .validate()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "column 1 range 16..24 exceeds data region 0..20"
+        );
+
+        let path = temp_dir.path().join("duplicate");
+        let column = |offset| ColumnMeta {
+            id: 5,
+            offset,
+            size: 4,
+        };
+        write_raw(&path, 20, vec![column(0), column(4)]);
+        let err = FooterFileDecoder::new(path)
+            .unwrap()
+            .validate()
+            .unwrap_err();
+        assert_eq!(err.to_string(), "duplicate column id 5");
+    }
 }
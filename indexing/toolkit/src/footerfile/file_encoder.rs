@@ -1,9 +1,7 @@
-use crate::footerfile::common::{
-    ColumnMeta, Footer, MAGIC_AND_DATA_SIZE, MAGIC_FOOTER, get_footer,
-};
+use crate::footerfile::common::{ColumnMeta, Footer, MAGIC_FOOTER, get_footer};
 use std::fs;
+use std::io;
 use std::io::Seek;
-use std::io::{self, Read, Write};
 use std::path::PathBuf;
 
 pub struct FooterFileEncoder {
@@ -25,17 +23,19 @@ impl FooterFileEncoder {
         })
     }
 
+    /// Reopens an existing file to append more columns.
+    ///
+    /// New column data is written from where the old footer starts, so existing columns are
+    /// never touched; only the footer region is overwritten on `close`.
     pub fn open(path: PathBuf) -> io::Result<Self> {
-        let mut file = fs::File::open(path)?;
+        let mut file = fs::OpenOptions::new().read(true).write(true).open(path)?;
         let file_size = file.metadata()?.len();
-        let offset = file_size - (MAGIC_AND_DATA_SIZE as u64);
-        file.seek(io::SeekFrom::Start(offset))?;
-        let footer = Footer::read_from(&mut file)?;
-        file.seek(io::SeekFrom::Start(offset))?;
+        let (data_end, footer) = get_footer(&mut file, file_size)?;
+        file.seek(io::SeekFrom::Start(data_end))?;
         Ok(Self {
             footer,
             file,
-            current_offset: offset,
+            current_offset: data_end,
         })
     }
 
@@ -63,6 +63,9 @@ impl FooterFileEncoder {
 
     pub fn close(&mut self) -> io::Result<()> {
         self.footer.write_to(&mut self.file)?;
+        // drop any bytes left over from a longer footer before a reopen.
+        let end = self.file.stream_position()?;
+        self.file.set_len(end)?;
         self.file.sync_all()?;
         Ok(())
     }
@@ -71,6 +74,8 @@ impl FooterFileEncoder {
 #[cfg(test)]
 mod tests {
     use crate::footerfile::file_decoder::FooterFileDecoder;
+    use crate::temp::dir::TempDir;
+    use std::io::Read;
 
     use super::*;
 
@@ -95,4 +100,41 @@ mod tests {
         column.read_to_end(&mut buffer).unwrap();
         assert_eq!(buffer, b"Hello Rust");
     }
+
+    #[test]
+    fn test_reopen_append_leaves_columns_untouched() {
+        let temp_dir = TempDir::new().expect("error creating temp dir");
+        let path = temp_dir.path().join("append.bin");
+
+        let mut encoder = FooterFileEncoder::create(path.clone()).unwrap();
+        encoder.write(1, &mut &b"first column"[..]).unwrap();
+        encoder.write(2, &mut &b"second"[..]).unwrap();
+        encoder.close().unwrap();
+
+        let before = fs::read(&path).unwrap();
+        let data_end = (b"first column".len() + b"second".len()) as u64;
+
+        let mut encoder = FooterFileEncoder::open(path.clone()).unwrap();
+        encoder.write(3, &mut &b"third!"[..]).unwrap();
+        encoder.close().unwrap();
+
+        let after = fs::read(&path).unwrap();
+        // columns 1 and 2 are byte-identical; only the old footer was overwritten.
+        assert_eq!(after[..data_end as usize], before[..data_end as usize]);
+
+        let mut decoder = FooterFileDecoder::new(path).unwrap();
+        decoder.validate().unwrap();
+        let mut read = |id| {
+            let mut buffer = Vec::new();
+            decoder
+                .get_column(id)
+                .unwrap()
+                .read_to_end(&mut buffer)
+                .unwrap();
+            buffer
+        };
+        assert_eq!(read(1), b"first column");
+        assert_eq!(read(2), b"second");
+        assert_eq!(read(3), b"third!");
+    }
 }
@@ -21,6 +21,8 @@ where
     size: u64,
     /// The current read/seek position, relative to the start of the slice.
     current_pos: u64,
+    /// Whether seeks past the end of the slice are allowed.
+    lenient_seek: bool,
 }
 
 impl<F> FileSlice<F>
@@ -42,13 +44,32 @@ where
             start_offset: offset,
             size,           // Store the size directly.
             current_pos: 0, // Our own relative position starts at 0.
+            lenient_seek: false,
         })
     }
 
+    /// Allows seeking past the end of the slice, as `SectionedSlice` does.
+    ///
+    /// Reads from a position at or beyond the end return 0 bytes (EOF).
+    pub fn with_lenient_seek(mut self, lenient: bool) -> Self {
+        self.lenient_seek = lenient;
+        self
+    }
+
     /// Returns the total size of this slice.
     pub fn len(&self) -> u64 {
         self.size
     }
+
+    /// Returns the current position in the inner reader, i.e. `start_offset + current_pos`.
+    pub fn absolute_position(&self) -> u64 {
+        self.start_offset + self.current_pos
+    }
+
+    /// Consumes the slice and returns the inner reader, left at `absolute_position`.
+    pub fn into_inner(self) -> F {
+        self.inner
+    }
 }
 
 impl<F> Read for FileSlice<F>
@@ -57,7 +78,7 @@ where
 {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         // Calculate how many bytes are left to read within our slice's bounds.
-        let bytes_left = self.size - self.current_pos;
+        let bytes_left = self.size.saturating_sub(self.current_pos);
         if bytes_left == 0 {
             return Ok(0); // End of slice.
         }
@@ -99,7 +120,7 @@ where
 
         let new_pos_relative = new_pos_relative as u64;
 
-        if new_pos_relative > self.size {
+        if new_pos_relative > self.size && !self.lenient_seek {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
                 "seek beyond end of slice",
@@ -303,6 +324,44 @@ mod tests {
         assert_eq!(result2.unwrap_err().kind(), io::ErrorKind::InvalidInput);
     }
 
+    #[test]
+    fn test_read_after_lenient_seek_past_end() {
+        let path = create_test_file("lenient_seek.bin").unwrap();
+        let file = File::open(&path.0).unwrap();
+        let mut slice = FileSlice::new(file, 10, 10)
+            .unwrap()
+            .with_lenient_seek(true);
+
+        assert_eq!(slice.seek(SeekFrom::End(5)).unwrap(), 15);
+
+        let mut buf = [0u8; 4];
+        assert_eq!(slice.read(&mut buf).unwrap(), 0);
+
+        // Seeking back into range reads normally again.
+        slice.seek(SeekFrom::Start(8)).unwrap();
+        assert_eq!(slice.read(&mut buf).unwrap(), 2);
+        assert_eq!(&buf[..2], b"ij");
+    }
+
+    #[test]
+    fn test_into_inner_continues_from_slice_position() {
+        let path = create_test_file("into_inner.bin").unwrap();
+        let file = File::open(&path.0).unwrap();
+        let mut slice = FileSlice::new(file, 10, 10).unwrap(); // "abcdefghij"
+
+        let mut buf = [0u8; 4];
+        slice.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"abcd");
+        assert_eq!(slice.absolute_position(), 14);
+
+        // the inner file is no longer bounded by the slice.
+        let mut file = slice.into_inner();
+        assert_eq!(file.stream_position().unwrap(), 14);
+        let mut rest = String::new();
+        file.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "efghijklmnopqrstuvwxyz");
+    }
+
     #[test]
     fn test_zero_length_slice() {
         let path = create_test_file("zero_length.bin").unwrap();
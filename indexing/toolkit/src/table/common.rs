@@ -123,3 +123,64 @@ impl IsAllowedId for u64 {
         self.to_le_bytes().to_vec()
     }
 }
+
+// Signed ids are offset by their type's minimum before bucketing, so `to_u64` is a
+// bijection onto `0..=unsigned::MAX` that keeps the ordering (`MIN` maps to 0). The stored
+// bytes are the plain little-endian signed value.
+macro_rules! impl_signed_id {
+    ($($t:ty => $u:ty),*) => {
+        $(
+            impl private::Sealed for $t {}
+            impl IsAllowedId for $t {
+                fn byte_size() -> usize {
+                    mem::size_of::<$t>()
+                }
+                fn to_u64(self) -> u64 {
+                    self.wrapping_sub(<$t>::MIN) as $u as u64
+                }
+                fn write_le_bytes(self, slice: &mut [u8]) {
+                    slice.copy_from_slice(&self.to_le_bytes());
+                }
+
+                fn from_le_bytes(slice: &[u8]) -> Result<Self, &'static str> {
+                    let array = slice
+                        .try_into()
+                        .map_err(|_| concat!("Slice does not have the size of ", stringify!($t)))?;
+                    Ok(<$t>::from_le_bytes(array))
+                }
+
+                fn get_le_bytes(&self) -> Vec<u8> {
+                    self.to_le_bytes().to_vec()
+                }
+            }
+        )*
+    };
+}
+
+impl_signed_id!(i16 => u16, i32 => u32, i64 => u64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signed_to_u64_is_ordered_bijection() {
+        assert_eq!(i32::MIN.to_u64(), 0);
+        assert_eq!((-1i32).to_u64(), (1 << 31) - 1);
+        assert_eq!(0i32.to_u64(), 1 << 31);
+        assert_eq!(i32::MAX.to_u64(), u32::MAX as u64);
+        assert_eq!(i16::MIN.to_u64(), 0);
+        assert_eq!(i64::MIN.to_u64(), 0);
+        assert_eq!(i64::MAX.to_u64(), u64::MAX);
+
+        let ids = [i32::MIN, i32::MIN + 1, -70_000, -1, 0, 1, 70_000, i32::MAX];
+        for pair in ids.windows(2) {
+            assert!(pair[0].to_u64() < pair[1].to_u64());
+        }
+        for id in ids {
+            let mut buf = [0u8; 4];
+            id.write_le_bytes(&mut buf);
+            assert_eq!(<i32 as IsAllowedId>::from_le_bytes(&buf), Ok(id));
+        }
+    }
+}
@@ -1,10 +1,13 @@
 use crate::table::common::IsAllowedId;
-use crate::table::key_reader::KeyEntry;
+use crate::table::common::OffsetHeader;
+use crate::table::key_reader::{
+    KeyEntry, contains_id, find_all_headers_by_id, read_bucket_headers,
+};
 
 use super::common::{HEADER_SIZE, MAGIC, ROW_OFFSET_SIZE};
 use super::key_reader::KeyReader;
 use super::reader_source_provider::SourceProvider;
-use std::io::{self, Read};
+use std::io::{self, Read, Seek};
 use std::marker::PhantomData;
 
 pub struct Decoder<T: IsAllowedId> {
@@ -26,6 +29,12 @@ impl<T: IsAllowedId> Decoder<T> {
                 "invalid magic number",
             ));
         }
+        if rows == 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "table header has zero rows",
+            ));
+        }
         Ok(Decoder {
             rows,
             provider,
@@ -34,12 +43,12 @@ impl<T: IsAllowedId> Decoder<T> {
     }
 
     pub fn query(&mut self, values: &[T]) -> io::Result<KeyReader<T>> {
+        self.check_rows()?;
         let row_position: Vec<KeyEntry<T>> = values
             .iter()
-            .map(|&id| {
-                let pos = id.to_u64() % self.get_rows();
-                let row_offset = (HEADER_SIZE as u64) + ((ROW_OFFSET_SIZE as u64) * pos);
-                KeyEntry { id, row_offset }
+            .map(|&id| KeyEntry {
+                id,
+                row_offset: self.row_offset(id),
             })
             .collect();
 
@@ -47,14 +56,68 @@ impl<T: IsAllowedId> Decoder<T> {
         Ok(KeyReader::new(row_position, reader))
     }
 
+    /// Returns every header stored for `id`, ordered by offset. Unlike `query`, which
+    /// stops at the first match, this keeps all entries written under a duplicate id.
+    pub fn find_all_by_id(&mut self, id: T) -> io::Result<Vec<OffsetHeader<T>>> {
+        self.check_rows()?;
+        let mut reader = self.provider.create_reader()?;
+        find_all_headers_by_id(&mut reader, self.row_offset(id), id)
+    }
+
+    /// Reports whether `id` has an entry, doing the same lookup as `query` without
+    /// positioning a reader over the section data.
+    pub fn contains(&mut self, id: T) -> io::Result<bool> {
+        self.check_rows()?;
+        let mut reader = self.provider.create_reader()?;
+        contains_id(&mut reader, self.row_offset(id), id)
+    }
+
+    /// Returns every header in the table, bucket by bucket, duplicates included. Within a
+    /// bucket headers come in `(id, offset)` order.
+    pub fn scan_all(&mut self) -> io::Result<Vec<OffsetHeader<T>>> {
+        self.check_rows()?;
+        let mut reader = self.provider.create_reader()?;
+        let mut headers = Vec::new();
+        for bucket in 0..self.rows {
+            let row_offset = (HEADER_SIZE as u64) + (ROW_OFFSET_SIZE as u64) * bucket;
+            headers.extend(read_bucket_headers(&mut reader, row_offset)?);
+        }
+        Ok(headers)
+    }
+
+    /// Reads the section `header` points at.
+    pub fn read_section(&mut self, header: &OffsetHeader<T>) -> io::Result<Vec<u8>> {
+        let mut reader = self.provider.create_reader()?;
+        reader.seek(io::SeekFrom::Start(header.offset))?;
+        let mut data = vec![0u8; header.size as usize];
+        reader.read_exact(&mut data)?;
+        Ok(data)
+    }
+
+    fn row_offset(&self, id: T) -> u64 {
+        let pos = id.to_u64() % self.get_rows();
+        (HEADER_SIZE as u64) + ((ROW_OFFSET_SIZE as u64) * pos)
+    }
+
     pub fn get_rows(&self) -> u64 {
         self.rows
     }
+
+    // `new` already rejects this, but bucket lookups divide by `rows` so guard them anyway.
+    fn check_rows(&self) -> io::Result<()> {
+        if self.rows == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "table header has zero rows",
+            ));
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 
-mod tests {
+pub(crate) mod tests {
     use crate::table::reader_source_provider::MemoryCreator;
 
     use super::super::super::temp::file::TempFile;
@@ -64,14 +127,14 @@ mod tests {
     use std::fs::File;
     use std::io::Write;
 
-    fn create_header(rows: u64) -> Vec<u8> {
+    pub(crate) fn create_header(rows: u64) -> Vec<u8> {
         let mut data = Vec::new();
         data.extend_from_slice(&MAGIC.to_le_bytes());
         data.extend_from_slice(&rows.to_le_bytes());
         data.extend(std::iter::repeat(0u8).take(16));
         data
     }
-    fn row_offset_to_bytes(vec: &mut Vec<u8>, offset: u64, size: u32) {
+    pub(crate) fn row_offset_to_bytes(vec: &mut Vec<u8>, offset: u64, size: u32) {
         vec.extend_from_slice(&offset.to_le_bytes());
         vec.extend_from_slice(&size.to_le_bytes());
     }
@@ -104,7 +167,7 @@ mod tests {
         assert_eq!(decoder.get_rows(), 10);
     }
 
-    fn offset_to_bytes<T: IsAllowedId>(offset: &OffsetHeader<T>) -> Vec<u8> {
+    pub(crate) fn offset_to_bytes<T: IsAllowedId>(offset: &OffsetHeader<T>) -> Vec<u8> {
         let mut vec = Vec::new();
         vec.extend_from_slice(&offset.offset.to_le_bytes());
         vec.extend_from_slice(&offset.id.get_le_bytes());
@@ -143,4 +206,117 @@ mod tests {
         next_reader.read_to_end(&mut buf).unwrap();
         assert_eq!(buf, payload);
     }
+
+    #[test]
+    fn test_decoder_find_all_by_id() {
+        use super::super::super::temp::dir::TempDir;
+        use super::super::encoder::Encoder;
+
+        let temp_dir = TempDir::new().expect("error creating temp dir");
+        let mut encoder = Encoder::new(temp_dir.path()).expect("error creating encoder");
+        let first = encoder.write(7_u32, b"first").unwrap();
+        encoder.write(3_u32, b"other").unwrap();
+        let second = encoder.write(7_u32, b"second").unwrap();
+
+        let mut out = Vec::new();
+        encoder.export(&mut out).expect("error exporting");
+
+        let provider = SourceProvider::Memory(MemoryCreator::new(out));
+        let mut decoder = Decoder::<u32>::new(provider).expect("no decoder created");
+        let headers = decoder.find_all_by_id(7).expect("error finding headers");
+        assert_eq!(
+            headers,
+            vec![
+                OffsetHeader {
+                    offset: first,
+                    id: 7,
+                    size: 5
+                },
+                OffsetHeader {
+                    offset: second,
+                    id: 7,
+                    size: 6
+                },
+            ]
+        );
+        assert!(decoder.find_all_by_id(8).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_decoder_rejects_zero_rows() {
+        let provider = SourceProvider::Memory(MemoryCreator::new(create_header(0)));
+        let err = Decoder::<u32>::new(provider)
+            .err()
+            .expect("zero rows should be rejected");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // a decoder that somehow ended up with zero rows errors instead of dividing by zero.
+        let mut decoder = Decoder::<u32> {
+            rows: 0,
+            provider: SourceProvider::Memory(MemoryCreator::new(create_header(0))),
+            phantom: PhantomData,
+        };
+        let err = decoder.query(&[1]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = decoder.find_all_by_id(1).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_decoder_contains_matches_query() {
+        use super::super::super::temp::dir::TempDir;
+        use super::super::encoder::Encoder;
+
+        let temp_dir = TempDir::new().expect("error creating temp dir");
+        let mut encoder = Encoder::new(temp_dir.path()).expect("error creating encoder");
+        let present: Vec<u32> = (0..200u32).map(|i| i * 3).collect();
+        for id in &present {
+            encoder.write(*id, &id.to_le_bytes()).unwrap();
+        }
+        let mut out = Vec::new();
+        encoder.export(&mut out).expect("error exporting");
+
+        let provider = SourceProvider::Memory(MemoryCreator::new(out));
+        let mut decoder = Decoder::<u32>::new(provider).expect("no decoder created");
+        for id in 0..600u32 {
+            let found = decoder.query(&[id]).unwrap().next_reader().unwrap().is_ok();
+            assert_eq!(decoder.contains(id).unwrap(), found, "id {id}");
+            assert_eq!(found, id % 3 == 0, "id {id}");
+        }
+    }
+
+    #[test]
+    fn test_decoder_signed_keys() {
+        use super::super::super::temp::dir::TempDir;
+        use super::super::encoder::Encoder;
+
+        let ids = [i32::MIN, i32::MIN + 1, -1_000_000, -7, -1, 0, 5, i32::MAX];
+        let temp_dir = TempDir::new().expect("error creating temp dir");
+        let mut encoder = Encoder::with_expected_keys(temp_dir.path(), 64).unwrap();
+        let mut offsets = Vec::new();
+        for id in ids {
+            offsets.push(encoder.write(id, &id.to_le_bytes()).unwrap());
+        }
+        let mut out = Vec::new();
+        encoder.export(&mut out).expect("error exporting");
+
+        let provider = SourceProvider::Memory(MemoryCreator::new(out));
+        let mut decoder = Decoder::<i32>::new(provider).expect("no decoder created");
+        assert_eq!(decoder.get_rows(), 16);
+        for (id, offset) in ids.iter().zip(offsets) {
+            assert!(decoder.contains(*id).unwrap(), "id {id}");
+            let headers = decoder.find_all_by_id(*id).unwrap();
+            assert_eq!(
+                headers,
+                vec![OffsetHeader {
+                    offset,
+                    id: *id,
+                    size: 4
+                }]
+            );
+        }
+        for absent in [i32::MIN + 2, -8, 1, i32::MAX - 1] {
+            assert!(!decoder.contains(absent).unwrap(), "id {absent}");
+        }
+    }
 }
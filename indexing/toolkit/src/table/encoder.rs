@@ -1,13 +1,38 @@
+use std::collections::HashSet;
 use std::io::{self, BufWriter, ErrorKind, Write};
 use std::path::PathBuf;
 
 use crate::table::common::{HEADER_SIZE, IsAllowedId, MAGIC, OffsetHeader, ROW_OFFSET_SIZE};
+use crate::table::decoder::Decoder;
+use crate::table::wal::{self, WalWriter};
 const PAGE_SIZE: usize = 512;
+/// Target load factor used when the caller supplies an expected key count.
+const KEYS_PER_BUCKET: usize = 4;
+
+/// Which table wins when `Encoder::merge_tables` finds a key in both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    KeepA,
+    KeepB,
+    /// Fail the merge, before anything is written.
+    Error,
+}
+
+/// How a key set spreads over a bucket table, from `Encoder::estimate_distribution`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DistReport {
+    /// Keys in the fullest bucket, i.e. the longest scan a lookup can hit.
+    pub max_bucket: usize,
+    pub avg_bucket: f64,
+    pub empty_buckets: usize,
+}
 
 pub struct Encoder<T: IsAllowedId> {
     writer: BufWriter<std::fs::File>,
     offset: u64,
     vec: Vec<OffsetHeader<T>>,
+    expected_keys: Option<usize>,
+    wal: Option<WalWriter>,
 }
 
 impl<T: IsAllowedId> Encoder<T> {
@@ -19,23 +44,79 @@ impl<T: IsAllowedId> Encoder<T> {
             writer,
             offset: 0,
             vec: Vec::new(),
+            expected_keys: None,
+            wal: None,
         })
     }
 
+    /// Creates an encoder that logs every write to a fresh write-ahead log at `wal_path`.
+    /// If the build is interrupted before `export`, `recover` resumes it from the log.
+    pub fn with_wal(tmp_dir: PathBuf, wal_path: PathBuf) -> io::Result<Self> {
+        let mut encoder = Self::new(tmp_dir)?;
+        encoder.wal = Some(WalWriter::create(&wal_path)?);
+        Ok(encoder)
+    }
+
+    /// Rebuilds an encoder from the write-ahead log at `wal_path`, replaying every durable
+    /// write. New writes keep appending to the same log.
+    pub fn recover(tmp_dir: PathBuf, wal_path: PathBuf) -> io::Result<Self> {
+        let mut encoder = Self::new(tmp_dir)?;
+        wal::replay(&wal_path, |ids: &[T], data: &[u8]| {
+            encoder.write_entries(ids, data).map(|_| ())
+        })?;
+        encoder.wal = Some(WalWriter::open(&wal_path)?);
+        Ok(encoder)
+    }
+
+    /// Creates an encoder sized for roughly `expected` keys. The key list is reserved up
+    /// front and `export` sizes the bucket table for ~`KEYS_PER_BUCKET` keys per bucket.
+    pub fn with_expected_keys(tmp_dir: PathBuf, expected: usize) -> io::Result<Self> {
+        let mut encoder = Self::new(tmp_dir)?;
+        encoder.vec.reserve(expected);
+        encoder.expected_keys = Some(expected);
+        Ok(encoder)
+    }
+
+    /// Syncs the write-ahead log every `records` writes instead of after each one; see
+    /// `WalWriter::with_sync_every`. Has no effect without a log.
+    pub fn with_wal_sync_every(mut self, records: usize) -> Self {
+        self.wal = self.wal.map(|wal| wal.with_sync_every(records));
+        self
+    }
+
+    /// Makes every write so far durable in the write-ahead log, if there is one.
+    pub fn sync_wal(&mut self) -> io::Result<()> {
+        match self.wal.as_mut() {
+            Some(wal) => wal.sync(),
+            None => Ok(()),
+        }
+    }
+
+    fn bucket_len(&self, header_size: usize) -> usize {
+        match self.expected_keys {
+            Some(expected) => expected
+                .max(self.vec.len())
+                .div_ceil(KEYS_PER_BUCKET)
+                .max(1),
+            None => (self.vec.len() * header_size / PAGE_SIZE) + 1,
+        }
+    }
+
     pub fn write(&mut self, id: T, data: &[u8]) -> io::Result<u64> {
-        let offset = self.offset;
-        self.offset += data.len() as u64;
-        self.writer.write_all(data)?;
-        self.vec.push(OffsetHeader {
-            id,
-            offset,
-            size: data.len() as u32,
-        });
-        Ok(offset)
+        if let Some(wal) = self.wal.as_mut() {
+            wal.append(&[id], data)?;
+        }
+        self.write_entries(&[id], data)
     }
 
     // we do not care for performance here so we can just use a dynamic reader.
     pub fn write_from_reader(&mut self, id: T, reader: &mut dyn io::Read) -> io::Result<u64> {
+        if self.wal.is_some() {
+            // the log needs the payload, so buffer it instead of streaming.
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data)?;
+            return self.write(id, &data);
+        }
         let offset = self.offset;
         match io::copy(reader, &mut self.writer) {
             Ok(size) => {
@@ -52,6 +133,12 @@ impl<T: IsAllowedId> Encoder<T> {
     }
 
     pub fn write_multi_key(&mut self, ids: &[T], reader: &mut dyn io::Read) -> io::Result<u64> {
+        if let Some(wal) = self.wal.as_mut() {
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data)?;
+            wal.append(ids, &data)?;
+            return self.write_entries(ids, &data);
+        }
         let offset = self.offset;
         match io::copy(reader, &mut self.writer) {
             Ok(size) => {
@@ -69,6 +156,77 @@ impl<T: IsAllowedId> Encoder<T> {
         }
     }
 
+    fn write_entries(&mut self, ids: &[T], data: &[u8]) -> io::Result<u64> {
+        let offset = self.offset;
+        self.offset += data.len() as u64;
+        self.writer.write_all(data)?;
+        for id in ids {
+            self.vec.push(OffsetHeader {
+                id: *id,
+                offset,
+                size: data.len() as u32,
+            });
+        }
+        Ok(offset)
+    }
+
+    /// Writes every key of tables `a` and `b` into this encoder once. A key stored under
+    /// several entries in one table keeps its first, the one `query` returns; a key in both
+    /// tables is resolved by `conflict`.
+    pub fn merge_tables(
+        &mut self,
+        mut a: Decoder<T>,
+        mut b: Decoder<T>,
+        conflict: ConflictPolicy,
+    ) -> io::Result<()> {
+        let a_headers = first_per_key(a.scan_all()?);
+        let b_headers = first_per_key(b.scan_all()?);
+
+        let mut shared = HashSet::new();
+        for header in &b_headers {
+            if a.contains(header.id)? {
+                if conflict == ConflictPolicy::Error {
+                    return Err(io::Error::new(
+                        ErrorKind::AlreadyExists,
+                        format!("key {:?} is in both tables", header.id),
+                    ));
+                }
+                shared.insert(header.id.to_u64());
+            }
+        }
+
+        for header in &a_headers {
+            if conflict == ConflictPolicy::KeepB && shared.contains(&header.id.to_u64()) {
+                continue;
+            }
+            let data = a.read_section(header)?;
+            self.write(header.id, &data)?;
+        }
+        for header in &b_headers {
+            if conflict == ConflictPolicy::KeepA && shared.contains(&header.id.to_u64()) {
+                continue;
+            }
+            let data = b.read_section(header)?;
+            self.write(header.id, &data)?;
+        }
+        Ok(())
+    }
+
+    /// Reports how `ids` would spread over `bucket_len` buckets in `export`, so a bucket
+    /// count can be picked without writing a table. A `bucket_len` of 0 counts as 1.
+    pub fn estimate_distribution(ids: &[T], bucket_len: usize) -> DistReport {
+        let bucket_len = bucket_len.max(1);
+        let mut counts = vec![0usize; bucket_len];
+        for id in ids {
+            counts[bucket_of(*id, bucket_len)] += 1;
+        }
+        DistReport {
+            max_bucket: counts.iter().copied().max().unwrap_or(0),
+            avg_bucket: ids.len() as f64 / bucket_len as f64,
+            empty_buckets: counts.iter().filter(|&&c| c == 0).count(),
+        }
+    }
+
     pub fn export<W: io::Write>(&mut self, w: &mut W) -> io::Result<()> {
         if self.vec.is_empty() {
             return Err(io::Error::new(ErrorKind::InvalidData, "no rows to write"));
@@ -77,7 +235,7 @@ impl<T: IsAllowedId> Encoder<T> {
         let mut buffer_writer = BufWriter::new(w);
 
         let header_size = 8 + T::byte_size() + 4;
-        let bucket_len = (self.vec.len() * header_size / PAGE_SIZE) + 1;
+        let bucket_len = self.bucket_len(header_size);
         let mut matrix = Vec::with_capacity(bucket_len);
         for _ in 0..bucket_len {
             matrix.push(Vec::new());
@@ -85,12 +243,12 @@ impl<T: IsAllowedId> Encoder<T> {
 
         for row in &self.vec {
             // we want to explicitly move here
-            let bucket = (row.id.to_u64() % (bucket_len as u64)) as usize;
-            matrix[bucket].push(row);
+            matrix[bucket_of(row.id, bucket_len)].push(row);
         }
         // matrix.set_len(bucket_len);
+        // stable sort by (id, offset) so duplicate ids always come out in write order.
         for row in &mut matrix {
-            row.sort_by_key(|x| x.id.to_u64());
+            row.sort_by_key(|x| (x.id.to_u64(), x.offset));
         }
 
         let num_buckets = matrix.len();
@@ -99,12 +257,14 @@ impl<T: IsAllowedId> Encoder<T> {
         let mut current_offset = HEADER_SIZE + (ROW_OFFSET_SIZE * num_buckets);
         for row in &matrix {
             offsets.push((current_offset as u64, row.len() as u32));
-            current_offset += row.len() + header_size;
+            current_offset += row.len() * header_size;
         }
 
         let mut buffer = Vec::new();
         buffer.extend_from_slice(&u64::to_le_bytes(MAGIC));
         buffer.extend_from_slice(&u64::to_le_bytes(num_buckets as u64));
+        // the decoder expects the bucket offsets to start at HEADER_SIZE.
+        buffer.resize(HEADER_SIZE, 0);
 
         for (offset, size) in offsets {
             buffer.extend_from_slice(&u64::to_le_bytes(offset));
@@ -127,6 +287,19 @@ impl<T: IsAllowedId> Encoder<T> {
     }
 }
 
+fn bucket_of<T: IsAllowedId>(id: T, bucket_len: usize) -> usize {
+    (id.to_u64() % (bucket_len as u64)) as usize
+}
+
+/// Keeps the first header of every id, in scan order.
+fn first_per_key<T: IsAllowedId>(headers: Vec<OffsetHeader<T>>) -> Vec<OffsetHeader<T>> {
+    let mut seen = HashSet::new();
+    headers
+        .into_iter()
+        .filter(|header| seen.insert(header.id.to_u64()))
+        .collect()
+}
+
 #[cfg(test)]
 
 mod test {
@@ -155,4 +328,218 @@ mod test {
             .export(&mut out_writer)
             .expect("error exporting to cursor");
     }
+
+    #[test]
+    fn test_encoder_export_bucket_layout() {
+        let temp_dir = TempDir::new().expect("error creating temp dir");
+        // four buckets, each holding several headers.
+        let mut encoder = Encoder::with_expected_keys(temp_dir.path(), 4 * KEYS_PER_BUCKET)
+            .expect("error creating encoder");
+        // every id twice, so buckets hold more than one header.
+        for id in (0..10_u32).chain(0..10) {
+            encoder
+                .write(id, b"row")
+                .expect("error writing byte values");
+        }
+        let mut out = Vec::new();
+        encoder.export(&mut out).expect("error exporting");
+
+        // bucket offsets start right after the padded header, and each bucket's headers
+        // follow the previous bucket's without gaps.
+        let header_size = OffsetHeader::<u32>::size();
+        let num_buckets = u64::from_le_bytes(out[8..16].try_into().unwrap()) as usize;
+        let mut expected_offset = HEADER_SIZE + ROW_OFFSET_SIZE * num_buckets;
+        let mut seen = 0;
+        for bucket in 0..num_buckets {
+            let entry = HEADER_SIZE + ROW_OFFSET_SIZE * bucket;
+            let offset = u64::from_le_bytes(out[entry..entry + 8].try_into().unwrap());
+            let count = u32::from_le_bytes(out[entry + 8..entry + 12].try_into().unwrap());
+            assert_eq!(offset as usize, expected_offset, "bucket {bucket}");
+            for i in 0..count as usize {
+                let start = expected_offset + i * header_size;
+                let header =
+                    OffsetHeader::<u32>::from_buffer(&out[start..start + header_size]).unwrap();
+                assert_eq!(header.id as usize % num_buckets, bucket);
+                seen += 1;
+            }
+            expected_offset += count as usize * header_size;
+        }
+        assert_eq!(seen, 20);
+        assert_eq!(expected_offset, out.len());
+    }
+
+    #[test]
+    fn test_encoder_wal_recover() {
+        let wal_dir = TempDir::new().expect("error creating temp dir");
+        let wal_path = wal_dir.path().join("table.wal");
+
+        let crashed_dir = TempDir::new().expect("error creating temp dir");
+        {
+            let mut encoder = Encoder::with_wal(crashed_dir.path(), wal_path.clone())
+                .expect("error creating encoder");
+            encoder.write(1_u32, b"Hello, Rust!").unwrap();
+            encoder
+                .write_from_reader(2_u32, &mut io::Cursor::new(b"Hello, 2!"))
+                .unwrap();
+            // dropped without export, as if the process died here.
+        }
+
+        let recovered_dir = TempDir::new().expect("error creating temp dir");
+        let mut recovered = Encoder::<u32>::recover(recovered_dir.path(), wal_path.clone())
+            .expect("error recovering encoder");
+        recovered
+            .write_multi_key(&[3_u32, 4_u32], &mut io::Cursor::new(b"Hello, 3,4!"))
+            .unwrap();
+        let mut recovered_out = Vec::new();
+        recovered.export(&mut recovered_out).unwrap();
+
+        let expected_dir = TempDir::new().expect("error creating temp dir");
+        let mut expected = Encoder::new(expected_dir.path()).expect("error creating encoder");
+        expected.write(1_u32, b"Hello, Rust!").unwrap();
+        expected.write(2_u32, b"Hello, 2!").unwrap();
+        expected
+            .write_multi_key(&[3_u32, 4_u32], &mut io::Cursor::new(b"Hello, 3,4!"))
+            .unwrap();
+        let mut expected_out = Vec::new();
+        expected.export(&mut expected_out).unwrap();
+
+        assert_eq!(recovered_out, expected_out);
+
+        // the log now holds all three writes and can be recovered again.
+        let again_dir = TempDir::new().expect("error creating temp dir");
+        let again = Encoder::<u32>::recover(again_dir.path(), wal_path).unwrap();
+        assert_eq!(again.vec.len(), 4);
+        assert_eq!(again.offset, expected.offset);
+    }
+
+    #[test]
+    fn test_encoder_expected_keys_bucket_count() {
+        fn exported_buckets(expected: usize, written: u32) -> Vec<u32> {
+            let temp_dir = TempDir::new().expect("error creating temp dir");
+            let mut encoder = Encoder::with_expected_keys(temp_dir.path(), expected)
+                .expect("error creating encoder");
+            for i in 0..written {
+                let id = i * 7919;
+                encoder
+                    .write(id, &id.to_le_bytes())
+                    .expect("error writing byte values");
+            }
+            let mut out = Vec::new();
+            encoder.export(&mut out).expect("error exporting");
+
+            let num_buckets = u64::from_le_bytes(out[8..16].try_into().unwrap()) as usize;
+            (0..num_buckets)
+                .map(|bucket| {
+                    let entry = HEADER_SIZE + ROW_OFFSET_SIZE * bucket + 8;
+                    u32::from_le_bytes(out[entry..entry + 4].try_into().unwrap())
+                })
+                .collect()
+        }
+
+        // 7919 is coprime with 250, so 1000 keys land exactly 4 to a bucket.
+        assert_eq!(exported_buckets(1000, 1000), vec![4; 250]);
+        assert_eq!(exported_buckets(10, 10).len(), 3);
+        assert_eq!(exported_buckets(1, 1).len(), 1);
+        // writing more keys than expected grows the table instead of overfilling it.
+        assert_eq!(exported_buckets(10, 20).len(), 5);
+    }
+
+    #[test]
+    fn test_estimate_distribution() {
+        let ids: Vec<u32> = vec![0, 3, 6, 9, 1, 4, 12, 15, 18, 7];
+        let report = Encoder::<u32>::estimate_distribution(&ids, 6);
+
+        // by hand, id % 6: 0 -> {0, 6, 12, 18}, 1 -> {1, 7}, 3 -> {3, 9, 15}, 4 -> {4}.
+        assert_eq!(
+            report,
+            DistReport {
+                max_bucket: 4,
+                avg_bucket: 10.0 / 6.0,
+                empty_buckets: 2,
+            }
+        );
+
+        let empty = Encoder::<u32>::estimate_distribution(&[], 4);
+        assert_eq!((empty.max_bucket, empty.empty_buckets), (0, 4));
+    }
+
+    /// A one-bucket table holding `entries`, their data right after the headers.
+    fn table(entries: &[(u32, &[u8])]) -> Decoder<u32> {
+        use crate::table::decoder::tests::{create_header, offset_to_bytes, row_offset_to_bytes};
+        use crate::table::reader_source_provider::{MemoryCreator, SourceProvider};
+
+        let mut data = create_header(1);
+        let headers_start = (HEADER_SIZE + ROW_OFFSET_SIZE) as u64;
+        row_offset_to_bytes(&mut data, headers_start, entries.len() as u32);
+
+        let mut offset = headers_start + (entries.len() * OffsetHeader::<u32>::size()) as u64;
+        for (id, payload) in entries {
+            let header = OffsetHeader {
+                offset,
+                id: *id,
+                size: payload.len() as u32,
+            };
+            data.extend_from_slice(&offset_to_bytes(&header));
+            offset += payload.len() as u64;
+        }
+        for (_, payload) in entries {
+            data.extend_from_slice(payload);
+        }
+        Decoder::new(SourceProvider::Memory(MemoryCreator::new(data))).unwrap()
+    }
+
+    /// Merges the two test tables, exports the result and reads every `(id, data)` pair
+    /// back through a `Decoder` over the exported table.
+    fn merged(conflict: ConflictPolicy) -> io::Result<Vec<(u32, Vec<u8>)>> {
+        use crate::table::reader_source_provider::{MemoryCreator, SourceProvider};
+
+        let a = table(&[(1, b"a-one"), (2, b"a-two")]);
+        let b = table(&[(2, b"b-two"), (3, b"b-three")]);
+        let temp_dir = TempDir::new().expect("error creating temp dir");
+        let mut encoder = Encoder::new(temp_dir.path()).expect("error creating encoder");
+        encoder.merge_tables(a, b, conflict)?;
+        let mut out = Vec::new();
+        encoder.export(&mut out)?;
+        // dropping the encoder flushes the section data its headers point into.
+        drop(encoder);
+
+        let written = std::fs::read(temp_dir.path().join("tmp_file.bin"))?;
+        let mut decoder = Decoder::<u32>::new(SourceProvider::Memory(MemoryCreator::new(out)))?;
+        let mut pairs: Vec<(u32, Vec<u8>)> = decoder
+            .scan_all()?
+            .iter()
+            .map(|h| {
+                let start = h.offset as usize;
+                (h.id, written[start..start + h.size as usize].to_vec())
+            })
+            .collect();
+        pairs.sort();
+        Ok(pairs)
+    }
+
+    #[test]
+    fn test_merge_tables_conflict_policy() {
+        let keep_a = merged(ConflictPolicy::KeepA).unwrap();
+        assert_eq!(
+            keep_a,
+            vec![
+                (1, b"a-one".to_vec()),
+                (2, b"a-two".to_vec()),
+                (3, b"b-three".to_vec())
+            ]
+        );
+
+        let keep_b = merged(ConflictPolicy::KeepB).unwrap();
+        assert_eq!(
+            keep_b,
+            vec![
+                (1, b"a-one".to_vec()),
+                (2, b"b-two".to_vec()),
+                (3, b"b-three".to_vec())
+            ]
+        );
+
+        let err = merged(ConflictPolicy::Error).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+    }
 }
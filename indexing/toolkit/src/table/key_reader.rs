@@ -60,23 +60,10 @@ impl<T: IsAllowedId> KeyReader<T> {
     fn process_next_entry<'a>(&'a mut self) -> io::Result<SectionReader<'a>> {
         let entry = self.entries.remove(0);
 
-        // --- Find search area bounds ---
-        self.reader.seek(io::SeekFrom::Start(entry.row_offset))?;
-        let mut buffer = [0u8; ROW_OFFSET_SIZE];
-        self.reader.read_exact(&mut buffer)?;
-        let data_offset = u64::from_le_bytes(buffer[0..8].try_into().unwrap());
-        let row_count = u32::from_le_bytes(buffer[8..12].try_into().unwrap());
-        let row_byte_size = (OffsetHeader::<T>::size()) as u32 * row_count;
-
         // --- Perform the search ---
-        let found_header = {
-            self.reader.seek(io::SeekFrom::Start(data_offset))?;
-            let search_limit = (&mut self.reader).take(row_byte_size as u64);
-            let mut search_reader = BufReader::new(search_limit);
-            // The `?` here cleanly propagates any I/O error or "Not Found" error
-            // from the find_header_by_id function.
-            find_header_by_id(&mut search_reader, entry.id)?
-        };
+        // The `?` here cleanly propagates any I/O error or "Not Found" error
+        // from the find_header_by_id function.
+        let found_header = search_bucket(&mut self.reader, entry.row_offset, entry.id)?;
 
         // If we get here, the header was found successfully. The `?` operator
         // handled the error case for us.
@@ -93,6 +80,79 @@ impl<T: IsAllowedId> KeyReader<T> {
     }
 }
 
+/// Reads the bucket entry `row_offset` points at, as `(data_offset, row_count)`.
+fn read_bucket(reader: &mut ReaderSource, row_offset: u64) -> io::Result<(u64, u32)> {
+    reader.seek(io::SeekFrom::Start(row_offset))?;
+    let mut buffer = [0u8; ROW_OFFSET_SIZE];
+    reader.read_exact(&mut buffer)?;
+    let data_offset = u64::from_le_bytes(buffer[0..8].try_into().unwrap());
+    let row_count = u32::from_le_bytes(buffer[8..12].try_into().unwrap());
+    Ok((data_offset, row_count))
+}
+
+/// Finds the first header for `target_id` in the bucket at `row_offset`, or `NotFound`.
+fn search_bucket<T: IsAllowedId>(
+    reader: &mut ReaderSource,
+    row_offset: u64,
+    target_id: T,
+) -> io::Result<OffsetHeader<T>> {
+    let (data_offset, row_count) = read_bucket(reader, row_offset)?;
+    let row_byte_size = (OffsetHeader::<T>::size() as u64) * row_count as u64;
+    reader.seek(io::SeekFrom::Start(data_offset))?;
+    let mut search_reader = BufReader::new(reader.take(row_byte_size));
+    find_header_by_id(&mut search_reader, target_id)
+}
+
+/// Runs the same bucket search as `KeyReader` but only reports whether `target_id` is present.
+pub fn contains_id<T: IsAllowedId>(
+    reader: &mut ReaderSource,
+    row_offset: u64,
+    target_id: T,
+) -> io::Result<bool> {
+    match search_bucket(reader, row_offset, target_id) {
+        Ok(_) => Ok(true),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Reads the bucket that `row_offset` points at and returns every header stored for
+/// `target_id`. Buckets are sorted by `(id, offset)`, so duplicates come back in offset order.
+pub fn find_all_headers_by_id<T: IsAllowedId>(
+    reader: &mut ReaderSource,
+    row_offset: u64,
+    target_id: T,
+) -> io::Result<Vec<OffsetHeader<T>>> {
+    let mut found = read_bucket_headers(reader, row_offset)?;
+    found.retain(|header| header.id == target_id);
+    Ok(found)
+}
+
+/// Returns every header in the bucket at `row_offset`, in stored order.
+pub fn read_bucket_headers<T: IsAllowedId>(
+    reader: &mut ReaderSource,
+    row_offset: u64,
+) -> io::Result<Vec<OffsetHeader<T>>> {
+    let (data_offset, row_count) = read_bucket(reader, row_offset)?;
+    let struct_size = OffsetHeader::<T>::size();
+    reader.seek(io::SeekFrom::Start(data_offset))?;
+    let mut search_reader = BufReader::new(reader.take((struct_size as u64) * row_count as u64));
+    let mut header_buffer = vec![0u8; struct_size];
+    // `row_count` comes from the file, so let the vector grow with what is actually read.
+    let mut headers = Vec::new();
+    for _ in 0..row_count {
+        search_reader.read_exact(&mut header_buffer)?;
+        let header = OffsetHeader::from_buffer(&header_buffer).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("error reading from buffer {}", e),
+            )
+        })?;
+        headers.push(header);
+    }
+    Ok(headers)
+}
+
 fn find_header_by_id<R: Read, T: IsAllowedId>(
     reader: &mut R,
     target_id: T,
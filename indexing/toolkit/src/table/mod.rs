@@ -4,3 +4,4 @@ pub mod encoder;
 pub mod key_reader;
 pub mod reader_source;
 pub mod reader_source_provider;
+pub mod wal;
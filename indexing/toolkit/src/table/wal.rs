@@ -0,0 +1,230 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::Path;
+
+use crate::table::common::IsAllowedId;
+
+/// Append-only log of table writes, used to resume an interrupted build.
+///
+/// Each record is `[id_count: u32][ids][len: u32][data]` with every integer little endian
+/// and ids stored at `T::byte_size()`. By default a record is durable once `append`
+/// returns; `with_sync_every` trades that for throughput by syncing every few records.
+pub struct WalWriter {
+    writer: BufWriter<File>,
+    sync_every: usize,
+    unsynced: usize,
+}
+
+impl WalWriter {
+    /// Creates a new log at `path`, discarding any previous contents.
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let file = File::create(path)?;
+        Ok(Self::from_file(file))
+    }
+
+    /// Opens an existing log at `path` and appends after its last record.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().append(true).open(path)?;
+        Ok(Self::from_file(file))
+    }
+
+    fn from_file(file: File) -> Self {
+        Self {
+            writer: BufWriter::new(file),
+            sync_every: 1,
+            unsynced: 0,
+        }
+    }
+
+    /// Syncs the log once every `records` appends instead of after each one, for bulk
+    /// ingestion. Records appended since the last sync can be lost in a crash; call `sync`
+    /// to make them durable at a point of your choosing. `0` syncs only on `sync`.
+    pub fn with_sync_every(mut self, records: usize) -> Self {
+        self.sync_every = records;
+        self
+    }
+
+    /// Flushes buffered records and syncs them to disk.
+    pub fn sync(&mut self) -> io::Result<()> {
+        self.writer.flush()?;
+        self.writer.get_ref().sync_data()?;
+        self.unsynced = 0;
+        Ok(())
+    }
+
+    pub fn append<T: IsAllowedId>(&mut self, ids: &[T], data: &[u8]) -> io::Result<()> {
+        let mut record = Vec::with_capacity(8 + ids.len() * T::byte_size() + data.len());
+        record.extend_from_slice(&(ids.len() as u32).to_le_bytes());
+        for id in ids {
+            record.extend_from_slice(&id.get_le_bytes());
+        }
+        record.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        record.extend_from_slice(data);
+
+        self.writer.write_all(&record)?;
+        self.unsynced += 1;
+        if self.sync_every > 0 && self.unsynced >= self.sync_every {
+            self.sync()?;
+        }
+        Ok(())
+    }
+}
+
+/// Replays every complete record in the log at `path` through `apply`, in write order.
+///
+/// A torn record at the tail (a crash mid-append) is not replayed and is truncated away so
+/// the log can be appended to again. Returns the number of records replayed.
+pub fn replay<T, F>(path: &Path, mut apply: F) -> io::Result<usize>
+where
+    T: IsAllowedId,
+    F: FnMut(&[T], &[u8]) -> io::Result<()>,
+{
+    let file = File::open(path)?;
+    let file_len = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+
+    let mut replayed = 0;
+    let mut durable_len = 0u64;
+    let mut ids = Vec::new();
+    let mut data = Vec::new();
+    loop {
+        match read_record(&mut reader, file_len - durable_len, &mut ids, &mut data) {
+            Ok(record_len) => {
+                apply(&ids, &data)?;
+                replayed += 1;
+                durable_len += record_len;
+            }
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e),
+        }
+    }
+
+    if durable_len < file_len {
+        OpenOptions::new()
+            .write(true)
+            .open(path)?
+            .set_len(durable_len)?;
+    }
+    Ok(replayed)
+}
+
+/// Reads one record into `ids` and `data`, returning its size in bytes. `remaining` is
+/// what is left of the log; a record claiming more is torn and reads as `UnexpectedEof`
+/// before anything is allocated for it.
+fn read_record<T: IsAllowedId, R: Read>(
+    reader: &mut R,
+    remaining: u64,
+    ids: &mut Vec<T>,
+    data: &mut Vec<u8>,
+) -> io::Result<u64> {
+    let torn = || {
+        io::Error::new(
+            ErrorKind::UnexpectedEof,
+            "torn record at the end of the log",
+        )
+    };
+    let mut len_buf = [0u8; 4];
+    reader.read_exact(&mut len_buf)?;
+    let id_count = u32::from_le_bytes(len_buf) as u64;
+    let ids_len = id_count * T::byte_size() as u64;
+    if 8 + ids_len > remaining {
+        return Err(torn());
+    }
+
+    let mut id_buf = vec![0u8; ids_len as usize];
+    reader.read_exact(&mut id_buf)?;
+    ids.clear();
+    for chunk in id_buf.chunks_exact(T::byte_size()) {
+        let id = T::from_le_bytes(chunk).map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?;
+        ids.push(id);
+    }
+
+    reader.read_exact(&mut len_buf)?;
+    let data_len = u32::from_le_bytes(len_buf) as usize;
+    if 8 + ids_len + data_len as u64 > remaining {
+        return Err(torn());
+    }
+    data.resize(data_len, 0);
+    reader.read_exact(data)?;
+
+    Ok((8 + id_buf.len() + data_len) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp::dir::TempDir;
+
+    #[test]
+    fn test_replay_drops_torn_tail() {
+        let temp_dir = TempDir::new().expect("error creating temp dir");
+        let wal_path = temp_dir.path().join("table.wal");
+        {
+            let mut wal = WalWriter::create(&wal_path).unwrap();
+            wal.append(&[1u32], b"one").unwrap();
+            wal.append(&[2u32, 3u32], b"two-three").unwrap();
+        }
+        let complete_len = std::fs::metadata(&wal_path).unwrap().len();
+
+        // A partial record: id count and half an id.
+        let mut file = OpenOptions::new().append(true).open(&wal_path).unwrap();
+        file.write_all(&[1, 0, 0, 0, 9, 0]).unwrap();
+        drop(file);
+
+        let mut seen = Vec::new();
+        let replayed = replay::<u32, _>(&wal_path, |ids, data| {
+            seen.push((ids.to_vec(), data.to_vec()));
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(replayed, 2);
+        assert_eq!(seen[0], (vec![1], b"one".to_vec()));
+        assert_eq!(seen[1], (vec![2, 3], b"two-three".to_vec()));
+        assert_eq!(std::fs::metadata(&wal_path).unwrap().len(), complete_len);
+    }
+
+    #[test]
+    fn test_replay_rejects_oversized_lengths() {
+        let temp_dir = TempDir::new().expect("error creating temp dir");
+        let wal_path = temp_dir.path().join("table.wal");
+        {
+            let mut wal = WalWriter::create(&wal_path).unwrap();
+            wal.append(&[1u32], b"one").unwrap();
+        }
+        let complete_len = std::fs::metadata(&wal_path).unwrap().len();
+
+        // A corrupt tail claiming 4G ids, then one claiming a 4 GiB payload.
+        let mut file = OpenOptions::new().append(true).open(&wal_path).unwrap();
+        file.write_all(&[0xff, 0xff, 0xff, 0xff, 1, 0]).unwrap();
+        drop(file);
+        assert_eq!(replay::<u32, _>(&wal_path, |_, _| Ok(())).unwrap(), 1);
+        assert_eq!(std::fs::metadata(&wal_path).unwrap().len(), complete_len);
+
+        let mut file = OpenOptions::new().append(true).open(&wal_path).unwrap();
+        file.write_all(&[1, 0, 0, 0, 2, 0, 0, 0, 0xff, 0xff, 0xff, 0xff])
+            .unwrap();
+        drop(file);
+        assert_eq!(replay::<u32, _>(&wal_path, |_, _| Ok(())).unwrap(), 1);
+        assert_eq!(std::fs::metadata(&wal_path).unwrap().len(), complete_len);
+    }
+
+    #[test]
+    fn test_batched_sync() {
+        let temp_dir = TempDir::new().expect("error creating temp dir");
+        let wal_path = temp_dir.path().join("table.wal");
+        let mut wal = WalWriter::create(&wal_path).unwrap().with_sync_every(3);
+        wal.append(&[1u32], b"one").unwrap();
+        wal.append(&[2u32], b"two").unwrap();
+        // still buffered: nothing has reached the file.
+        assert_eq!(std::fs::metadata(&wal_path).unwrap().len(), 0);
+        wal.append(&[3u32], b"three").unwrap();
+        let synced_len = std::fs::metadata(&wal_path).unwrap().len();
+        assert!(synced_len > 0);
+
+        wal.append(&[4u32], b"four").unwrap();
+        assert_eq!(std::fs::metadata(&wal_path).unwrap().len(), synced_len);
+        wal.sync().unwrap();
+        assert_eq!(replay::<u32, _>(&wal_path, |_, _| Ok(())).unwrap(), 4);
+    }
+}